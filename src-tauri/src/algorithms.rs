@@ -0,0 +1,246 @@
+// Per-connection SSH algorithm preferences, for old devices that only offer
+// legacy KEX, host key, cipher or MAC algorithms (e.g. diffie-hellman-group1-sha1,
+// ssh-rsa and CBC ciphers on older Cisco IOS), plus a warning when a weak one
+// ends up being negotiated.
+
+use ssh2::{MethodType, Session};
+use tauri::AppHandle;
+
+use crate::connect_error::ConnectError;
+use crate::session::emit_event;
+
+// Considered weak when negotiated: SHA-1 key exchange, signatures and MACs,
+// DSA, CBC-mode and RC4 ciphers, MD5 and truncated MACs
+const WEAK_ALGORITHMS: [&str; 11] = [
+    "diffie-hellman-group1-sha1",
+    "diffie-hellman-group14-sha1",
+    "diffie-hellman-group-exchange-sha1",
+    "ssh-rsa",
+    "ssh-dss",
+    "rijndael-cbc@lysator.liu.se",
+    "hmac-md5",
+    "hmac-md5-96",
+    "hmac-sha1",
+    "hmac-sha1-96",
+    "hmac-sha1-etm@openssh.com",
+];
+
+fn is_weak(algorithm: &str) -> bool {
+    WEAK_ALGORITHMS.contains(&algorithm) || algorithm.ends_with("-cbc") || algorithm.starts_with("arcfour")
+}
+
+// --- Types ---
+
+// Algorithm lists in order of preference; an empty list keeps libssh2's defaults.
+// Ciphers and MACs apply to both directions.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AlgorithmOverrides {
+    pub kex: Vec<String>,
+    pub host_key: Vec<String>,
+    pub cipher: Vec<String>,
+    pub mac: Vec<String>,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NegotiatedAlgorithm {
+    pub kind: String, // "kex", "hostKey", "cipher" or "mac"
+    pub name: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeakAlgorithmPayload {
+    pub session_id: String,
+    pub hostname: String,
+    pub port: u16,
+    pub algorithms: Vec<NegotiatedAlgorithm>,
+}
+
+// --- Negotiation ---
+
+impl AlgorithmOverrides {
+    // Sets the preferences on a session before its handshake.
+    // Names libssh2 does not implement are rejected rather than silently ignored.
+    pub fn apply(&self, session: &Session) -> Result<(), ConnectError> {
+        let lists: [(&str, &[String], &[MethodType]); 4] = [
+            ("key exchange", &self.kex, &[MethodType::Kex]),
+            ("host key", &self.host_key, &[MethodType::HostKey]),
+            ("cipher", &self.cipher, &[MethodType::CryptCs, MethodType::CryptSc]),
+            ("MAC", &self.mac, &[MethodType::MacCs, MethodType::MacSc]),
+        ];
+        for (label, names, method_types) in lists {
+            let names: Vec<&str> = names.iter().map(|name| name.trim()).filter(|name| !name.is_empty()).collect();
+            if names.is_empty() {
+                continue;
+            }
+            for &method_type in method_types {
+                let supported = session
+                    .supported_algs(method_type)
+                    .map_err(|e| ConnectError::Other(format!("Failed to list supported {} algorithms: {}", label, e)))?;
+                let unsupported: Vec<&str> = names.iter().copied().filter(|name| !supported.contains(name)).collect();
+                if !unsupported.is_empty() {
+                    return Err(ConnectError::Protocol(format!(
+                        "Unsupported {} algorithm(s): {} (available: {})",
+                        label,
+                        unsupported.join(", "),
+                        supported.join(", ")
+                    )));
+                }
+                session
+                    .method_pref(method_type, &names.join(","))
+                    .map_err(|e| ConnectError::Protocol(format!("Failed to set {} algorithms: {}", label, e)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// The weak ones among (kind, negotiated name) pairs
+fn weak_among<'a>(negotiated: impl IntoIterator<Item = (&'static str, Option<&'a str>)>) -> Vec<NegotiatedAlgorithm> {
+    let mut weak: Vec<NegotiatedAlgorithm> = Vec::new();
+    for (kind, name) in negotiated {
+        if let Some(name) = name.filter(|name| is_weak(name)) {
+            // Both directions usually agree; report each algorithm once
+            if !weak.iter().any(|seen| seen.kind == kind && seen.name == name) {
+                weak.push(NegotiatedAlgorithm { kind: kind.to_string(), name: name.to_string() });
+            }
+        }
+    }
+    weak
+}
+
+// The weak algorithms a completed handshake settled on, if any
+pub fn weak_negotiated(session: &Session) -> Vec<NegotiatedAlgorithm> {
+    let negotiated = [
+        ("kex", MethodType::Kex),
+        ("hostKey", MethodType::HostKey),
+        ("cipher", MethodType::CryptCs),
+        ("cipher", MethodType::CryptSc),
+        ("mac", MethodType::MacCs),
+        ("mac", MethodType::MacSc),
+    ];
+    weak_among(negotiated.map(|(kind, method_type)| (kind, session.methods(method_type))))
+}
+
+// Emits `ssh-weak-algorithms` when the handshake used weak algorithms
+pub fn warn_if_weak(app_handle: &AppHandle, session_id: &str, session: &Session, hostname: &str, port: u16) {
+    let algorithms = weak_negotiated(session);
+    if algorithms.is_empty() {
+        return;
+    }
+    let names: Vec<&str> = algorithms.iter().map(|algorithm| algorithm.name.as_str()).collect();
+    eprintln!("[{}] Weak algorithms negotiated with {}:{}: {}", session_id, hostname, port, names.join(", "));
+    emit_event(
+        app_handle,
+        "ssh-weak-algorithms",
+        WeakAlgorithmPayload { session_id: session_id.to_string(), hostname: hostname.to_string(), port, algorithms },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(algorithms: &[NegotiatedAlgorithm]) -> Vec<(&str, &str)> {
+        algorithms.iter().map(|algorithm| (algorithm.kind.as_str(), algorithm.name.as_str())).collect()
+    }
+
+    fn overrides(cipher: &[&str]) -> AlgorithmOverrides {
+        AlgorithmOverrides { cipher: cipher.iter().map(|name| name.to_string()).collect(), ..Default::default() }
+    }
+
+    #[test]
+    fn legacy_algorithms_are_weak() {
+        for name in [
+            "diffie-hellman-group1-sha1",
+            "diffie-hellman-group14-sha1",
+            "ssh-rsa",
+            "ssh-dss",
+            "3des-cbc",
+            "aes128-cbc",
+            "aes256-cbc",
+            "twofish-cbc",
+            "arcfour128",
+            "hmac-md5",
+            "hmac-sha1",
+            "hmac-sha1-96",
+        ] {
+            assert!(is_weak(name), "{} should be weak", name);
+        }
+    }
+
+    #[test]
+    fn current_algorithms_are_not_weak() {
+        for name in [
+            "curve25519-sha256",
+            "diffie-hellman-group14-sha256",
+            "ecdh-sha2-nistp256",
+            "ssh-ed25519",
+            "rsa-sha2-512",
+            "aes128-ctr",
+            "aes256-gcm@openssh.com",
+            "chacha20-poly1305@openssh.com",
+            "hmac-sha2-256",
+            "hmac-sha2-512-etm@openssh.com",
+        ] {
+            assert!(!is_weak(name), "{} should not be weak", name);
+        }
+    }
+
+    #[test]
+    fn weak_algorithms_are_reported_once_per_kind() {
+        let weak = weak_among([
+            ("kex", Some("diffie-hellman-group1-sha1")),
+            ("hostKey", Some("ssh-rsa")),
+            ("cipher", Some("aes128-cbc")),
+            ("cipher", Some("aes128-cbc")),
+            ("mac", Some("hmac-sha1")),
+            ("mac", Some("hmac-sha2-256")),
+        ]);
+        assert_eq!(
+            names(&weak),
+            vec![("kex", "diffie-hellman-group1-sha1"), ("hostKey", "ssh-rsa"), ("cipher", "aes128-cbc"), ("mac", "hmac-sha1")]
+        );
+    }
+
+    #[test]
+    fn modern_or_unknown_negotiation_reports_nothing() {
+        let weak = weak_among([("kex", Some("curve25519-sha256")), ("hostKey", None), ("cipher", Some("aes256-ctr")), ("mac", None)]);
+        assert!(weak.is_empty());
+        // Before a handshake libssh2 reports no methods at all
+        assert!(weak_negotiated(&Session::new().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn empty_lists_keep_the_defaults() {
+        let session = Session::new().unwrap();
+        assert!(AlgorithmOverrides::default().apply(&session).is_ok());
+        // Blank entries from the profile editor count as empty
+        assert!(overrides(&["", "  "]).apply(&session).is_ok());
+    }
+
+    #[test]
+    fn supported_names_are_accepted() {
+        let session = Session::new().unwrap();
+        let supported = session.supported_algs(MethodType::CryptCs).unwrap();
+        let preferred: Vec<String> = supported.iter().take(2).map(|name| format!(" {} ", name)).collect();
+        let preferred: Vec<&str> = preferred.iter().map(String::as_str).collect();
+        assert!(overrides(&preferred).apply(&session).is_ok());
+    }
+
+    #[test]
+    fn unknown_names_are_rejected() {
+        let session = Session::new().unwrap();
+        let supported = session.supported_algs(MethodType::CryptCs).unwrap();
+        let result = overrides(&[supported[0], "rot13-cbc"]).apply(&session);
+        let Err(ConnectError::Protocol(message)) = result else {
+            panic!("expected a protocol error");
+        };
+        assert!(message.starts_with("Unsupported cipher algorithm(s): rot13-cbc (available: "), "{}", message);
+
+        let kex = AlgorithmOverrides { kex: vec!["diffie-hellman-group99-sha1".to_string()], ..Default::default() };
+        assert!(matches!(kex.apply(&session), Err(ConnectError::Protocol(message)) if message.contains("key exchange")));
+    }
+}
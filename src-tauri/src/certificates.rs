@@ -0,0 +1,478 @@
+// OpenSSH user certificates (`id_ed25519-cert.pub`) issued by a CA, used in place
+// of the bare public key during public key authentication. The certificate is
+// checked before connecting so an expired one fails with a clear reason rather
+// than a generic "Permission denied" from the server.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ssh_key::{Certificate, HashAlg, PublicKey};
+use tauri::{command, AppHandle};
+
+use crate::keys::{expand_home, KeyMaterial};
+use crate::session::emit_event;
+
+// --- Errors ---
+
+#[derive(Debug, Clone)]
+pub enum CertificateError {
+    NotFound(PathBuf),
+    Unreadable(PathBuf, String),
+    Invalid(PathBuf, String),
+    HostCertificate(PathBuf),
+    KeyMismatch(PathBuf),
+    Expired { path: PathBuf, valid_before: u64 },
+    NotYetValid { path: PathBuf, valid_after: u64 },
+    NoPrivateKey(PathBuf),
+}
+
+impl fmt::Display for CertificateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CertificateError::NotFound(path) => write!(f, "Certificate {} does not exist", path.display()),
+            CertificateError::Unreadable(path, e) => write!(f, "Failed to read certificate {}: {}", path.display(), e),
+            CertificateError::Invalid(path, e) => write!(f, "Invalid certificate {}: {}", path.display(), e),
+            CertificateError::HostCertificate(path) => {
+                write!(f, "Certificate {} is a host certificate, not a user certificate", path.display())
+            }
+            CertificateError::KeyMismatch(path) => {
+                write!(f, "Certificate {} was not issued for the selected private key", path.display())
+            }
+            CertificateError::Expired { path, valid_before } => write!(
+                f,
+                "Certificate {} expired on {}; request a new one from your CA",
+                path.display(),
+                format_time(*valid_before)
+            ),
+            CertificateError::NotYetValid { path, valid_after } => write!(
+                f,
+                "Certificate {} is not valid until {}; check the system clock",
+                path.display(),
+                format_time(*valid_after)
+            ),
+            CertificateError::NoPrivateKey(path) => {
+                write!(f, "Certificate {} needs the matching private key to be selected", path.display())
+            }
+        }
+    }
+}
+
+impl From<CertificateError> for String {
+    fn from(e: CertificateError) -> Self {
+        e.to_string()
+    }
+}
+
+// --- Types ---
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CertificateStatus {
+    Valid,
+    NotYetValid,
+    Expired,
+}
+
+// What the connect dialog shows about a certificate
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateInfo {
+    pub path: String,
+    pub key_id: String,
+    pub serial: u64,
+    pub principals: Vec<String>, // Empty means valid for any user
+    pub valid_after: u64,        // Unix seconds
+    pub valid_before: u64,       // Unix seconds; u64::MAX means it never expires
+    pub ca_fingerprint: String,  // SHA256 fingerprint of the signing CA key
+    pub status: CertificateStatus,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificatePayload {
+    pub session_id: String,
+    pub hostname: String,
+    pub username: String,
+    pub certificate: CertificateInfo,
+}
+
+// A certificate ready to hand to `userauth_pubkey_memory` as the public key
+// (`openssh` is the file's own line, so the CA signature stays intact)
+pub struct UserCertificate {
+    pub openssh: String,
+    pub info: CertificateInfo,
+}
+
+// --- Loading ---
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn format_time(secs: u64) -> String {
+    i64::try_from(secs)
+        .ok()
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| "forever".to_string())
+}
+
+// Where ssh(1) looks for a key's certificate
+pub fn default_path(key_path: &Path) -> PathBuf {
+    let mut path = key_path.as_os_str().to_owned();
+    path.push("-cert.pub");
+    PathBuf::from(path)
+}
+
+// Reads the SSH wire-format fields of a certificate blob in order
+struct Fields<'a> {
+    blob: &'a [u8],
+    offset: usize,
+}
+
+impl Fields<'_> {
+    fn take(&mut self, len: usize) -> Option<std::ops::Range<usize>> {
+        let range = self.offset..self.offset.checked_add(len)?;
+        self.blob.get(range.clone())?;
+        self.offset = range.end;
+        Some(range)
+    }
+
+    fn string(&mut self) -> Option<std::ops::Range<usize>> {
+        let len = self.take(4)?;
+        let len = u32::from_be_bytes(self.blob[len].try_into().ok()?) as usize;
+        self.take(len)
+    }
+}
+
+// ssh-keygen writes "forever" as a valid-before of 2^64-1, which ssh-key
+// refuses to decode; such a line is re-encoded with it clamped to i64::MAX.
+// Returns None when the certificate has no such field.
+fn clamp_forever(line: &str) -> Option<String> {
+    let mut parts = line.split_whitespace();
+    let (algorithm, encoded) = (parts.next()?, parts.next()?);
+    let mut blob = STANDARD.decode(encoded).ok()?;
+    let mut fields = Fields { blob: &blob, offset: 0 };
+    let cert_type = fields.string()?;
+    // Public key fields between the nonce and the serial, per key type
+    let key_fields = match std::str::from_utf8(&blob[cert_type]).ok()? {
+        "ssh-ed25519-cert-v01@openssh.com" => 1,
+        "ssh-rsa-cert-v01@openssh.com" | "sk-ssh-ed25519-cert-v01@openssh.com" => 2,
+        name if name.starts_with("ecdsa-sha2-") => 2,
+        name if name.starts_with("sk-ecdsa-sha2-") => 3,
+        "ssh-dss-cert-v01@openssh.com" => 4,
+        _ => return None,
+    };
+    for _ in 0..1 + key_fields {
+        fields.string()?; // Nonce, then the key fields
+    }
+    fields.take(8 + 4)?; // Serial and certificate type
+    fields.string()?; // Key id
+    fields.string()?; // Principals
+    fields.take(8)?; // Valid after
+    let valid_before = fields.take(8)?;
+    if blob[valid_before.clone()] != [0xFF; 8] {
+        return None;
+    }
+    blob[valid_before].copy_from_slice(&(i64::MAX as u64).to_be_bytes());
+    Some(format!("{} {}", algorithm, STANDARD.encode(blob)))
+}
+
+fn parse(line: &str) -> Result<Certificate, ssh_key::Error> {
+    Certificate::from_openssh(line).or_else(|e| match clamp_forever(line) {
+        Some(clamped) => Certificate::from_openssh(&clamped).map_err(|_| e),
+        None => Err(e),
+    })
+}
+
+// Unix seconds, with the clamped "forever" reported as u64::MAX again
+fn valid_before(certificate: &Certificate) -> u64 {
+    match certificate.valid_before() {
+        secs if secs >= i64::MAX as u64 => u64::MAX,
+        secs => secs,
+    }
+}
+
+fn read(path: &Path) -> Result<(String, Certificate, CertificateInfo), CertificateError> {
+    if !path.is_file() {
+        return Err(CertificateError::NotFound(path.to_path_buf()));
+    }
+    let contents = fs::read_to_string(path).map_err(|e| CertificateError::Unreadable(path.to_path_buf(), e.to_string()))?;
+    let line = contents.trim().to_string();
+    let certificate = parse(&line).map_err(|e| CertificateError::Invalid(path.to_path_buf(), e.to_string()))?;
+    if !certificate.cert_type().is_user() {
+        return Err(CertificateError::HostCertificate(path.to_path_buf()));
+    }
+
+    let now = now();
+    let status = if now < certificate.valid_after() {
+        CertificateStatus::NotYetValid
+    } else if now >= valid_before(&certificate) {
+        CertificateStatus::Expired
+    } else {
+        CertificateStatus::Valid
+    };
+    let info = CertificateInfo {
+        path: path.display().to_string(),
+        key_id: certificate.key_id().to_string(),
+        serial: certificate.serial(),
+        principals: certificate.valid_principals().to_vec(),
+        valid_after: certificate.valid_after(),
+        valid_before: valid_before(&certificate),
+        ca_fingerprint: certificate.signature_key().fingerprint(HashAlg::Sha256).to_string(),
+        status,
+    };
+    Ok((line, certificate, info))
+}
+
+// Loads the certificate at `path` for `key`, refusing one outside its validity
+// window or issued for a different key
+fn load(path: PathBuf, key: &KeyMaterial) -> Result<UserCertificate, CertificateError> {
+    let (openssh, certificate, info) = read(&path)?;
+
+    // PEM keys carry no public key; the server will reject a mismatch anyway
+    let public_key = key.public_key.as_deref().and_then(|line| PublicKey::from_openssh(line).ok());
+    if public_key.is_some_and(|public_key| public_key.key_data() != certificate.public_key()) {
+        return Err(CertificateError::KeyMismatch(path));
+    }
+    match info.status {
+        CertificateStatus::Expired => return Err(CertificateError::Expired { path, valid_before: info.valid_before }),
+        CertificateStatus::NotYetValid => return Err(CertificateError::NotYetValid { path, valid_after: info.valid_after }),
+        CertificateStatus::Valid => {}
+    }
+    Ok(UserCertificate { openssh, info })
+}
+
+// The certificate to present with `key`: the given path, or else
+// `<key>-cert.pub` when it exists. Only a certificate the user picked fails
+// the connection; an unusable auto-discovered one is skipped with a warning.
+pub fn for_key(path: Option<&str>, key: &KeyMaterial, session_id: &str) -> Result<Option<UserCertificate>, CertificateError> {
+    if let Some(path) = path {
+        return load(expand_home(path), key).map(Some);
+    }
+    let path = default_path(&key.path);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    match load(path, key) {
+        Ok(certificate) => Ok(Some(certificate)),
+        Err(e) => {
+            eprintln!("[{}] Not using the certificate next to the key: {}", session_id, e);
+            Ok(None)
+        }
+    }
+}
+
+// Emits `ssh-certificate` so the UI can show which principals and validity
+// window the connection is using
+pub fn announce(app_handle: &AppHandle, session_id: &str, hostname: &str, username: &str, certificate: &UserCertificate) {
+    let info = &certificate.info;
+    println!(
+        "[{}] Using certificate {} (key id \"{}\", serial {}, valid until {})",
+        session_id,
+        info.path,
+        info.key_id,
+        info.serial,
+        format_time(info.valid_before)
+    );
+    if !info.principals.is_empty() && !info.principals.iter().any(|principal| principal == username) {
+        eprintln!(
+            "[{}] Certificate {} does not list {} among its principals ({}); the server will likely reject it",
+            session_id,
+            info.path,
+            username,
+            info.principals.join(", ")
+        );
+    }
+    emit_event(
+        app_handle,
+        "ssh-certificate",
+        CertificatePayload {
+            session_id: session_id.to_string(),
+            hostname: hostname.to_string(),
+            username: username.to_string(),
+            certificate: info.clone(),
+        },
+    );
+}
+
+// --- Tauri Commands ---
+
+// Reads a certificate for display, including expired ones
+#[command]
+pub async fn inspect_certificate(path: String) -> Result<CertificateInfo, String> {
+    read(&expand_home(&path)).map(|(_, _, info)| info).map_err(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::KeyFormat;
+    use ssh_key::certificate::{Builder, CertType};
+    use ssh_key::private::Ed25519Keypair;
+    use ssh_key::PrivateKey;
+    use zeroize::Zeroizing;
+
+    const HOUR: u64 = 3600;
+
+    fn private_key(seed: u8) -> PrivateKey {
+        PrivateKey::from(Ed25519Keypair::from_seed(&[seed; 32]))
+    }
+
+    // A user certificate for `subject`, signed by a throwaway CA
+    fn certificate(subject: &PrivateKey, valid_after: u64, valid_before: u64) -> String {
+        let mut builder = Builder::new([7u8; 16], subject.public_key().key_data().clone(), valid_after, valid_before).unwrap();
+        builder.cert_type(CertType::User).unwrap().key_id("alice@example").unwrap().serial(42).unwrap().valid_principal("alice").unwrap();
+        builder.sign(&private_key(0xCA)).unwrap().to_openssh().unwrap()
+    }
+
+    // Rewrites a valid-before of i64::MAX to the 2^64-1 ssh-keygen writes for "forever"
+    fn forever(line: &str) -> String {
+        let (algorithm, rest) = line.split_once(' ').unwrap();
+        let blob = STANDARD.decode(rest.split(' ').next().unwrap()).unwrap();
+        let clamped = (i64::MAX as u64).to_be_bytes();
+        let at = blob.windows(8).position(|window| window == clamped).unwrap();
+        let mut blob = blob;
+        blob[at..at + 8].copy_from_slice(&[0xFF; 8]);
+        format!("{} {}", algorithm, STANDARD.encode(blob))
+    }
+
+    struct Fixture {
+        dir: PathBuf,
+        key: KeyMaterial,
+    }
+
+    impl Fixture {
+        fn new(name: &str, subject: &PrivateKey) -> Self {
+            let dir = std::env::temp_dir().join(format!("termai-certificates-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            let key = KeyMaterial {
+                path: dir.join("id_ed25519"),
+                format: KeyFormat::OpenSsh,
+                private_key: Zeroizing::new(String::new()),
+                public_key: Some(subject.public_key().to_openssh().unwrap()),
+                passphrase: None,
+            };
+            Fixture { dir, key }
+        }
+
+        // Writes the certificate next to the key, where it is auto-discovered
+        fn auto(&self, certificate: &str) {
+            fs::write(default_path(&self.key.path), certificate).unwrap();
+        }
+
+        // Writes the certificate somewhere else; returns the path to select
+        fn explicit(&self, certificate: &str) -> String {
+            let path = self.dir.join("selected-cert.pub");
+            fs::write(&path, certificate).unwrap();
+            path.display().to_string()
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    #[test]
+    fn valid_certificate_is_used() {
+        let subject = private_key(1);
+        let fixture = Fixture::new("valid", &subject);
+        let line = certificate(&subject, now() - HOUR, now() + HOUR);
+        let path = fixture.explicit(&line);
+        let loaded = for_key(Some(&path), &fixture.key, "test").unwrap().unwrap();
+        assert_eq!(loaded.openssh, line);
+        assert_eq!(loaded.info.status, CertificateStatus::Valid);
+        assert_eq!(loaded.info.key_id, "alice@example");
+        assert_eq!(loaded.info.serial, 42);
+        assert_eq!(loaded.info.principals, vec!["alice"]);
+        assert!(loaded.info.ca_fingerprint.starts_with("SHA256:"));
+    }
+
+    #[test]
+    fn certificate_without_expiry_never_expires() {
+        let subject = private_key(1);
+        let fixture = Fixture::new("forever", &subject);
+        let line = forever(&certificate(&subject, 0, i64::MAX as u64));
+        assert!(Certificate::from_openssh(&line).is_err(), "ssh-key decodes 2^64-1 now; drop clamp_forever");
+        let path = fixture.explicit(&line);
+        let loaded = for_key(Some(&path), &fixture.key, "test").unwrap().unwrap();
+        assert_eq!(loaded.info.valid_before, u64::MAX);
+        assert_eq!(loaded.info.status, CertificateStatus::Valid);
+        // Sent as written, not re-encoded with the clamped field
+        assert_eq!(loaded.openssh, line);
+        assert_eq!(format_time(loaded.info.valid_before), "forever");
+    }
+
+    #[test]
+    fn selected_certificate_outside_its_window_is_refused() {
+        let subject = private_key(1);
+        let fixture = Fixture::new("window", &subject);
+
+        let path = fixture.explicit(&certificate(&subject, now() - 2 * HOUR, now() - HOUR));
+        let error = for_key(Some(&path), &fixture.key, "test").err().unwrap();
+        assert!(matches!(error, CertificateError::Expired { valid_before, .. } if valid_before < now()));
+        assert!(error.to_string().contains("expired"), "{}", error);
+
+        let path = fixture.explicit(&certificate(&subject, now() + HOUR, now() + 2 * HOUR));
+        let error = for_key(Some(&path), &fixture.key, "test").err().unwrap();
+        assert!(matches!(error, CertificateError::NotYetValid { valid_after, .. } if valid_after > now()));
+    }
+
+    #[test]
+    fn selected_certificate_for_another_key_is_refused() {
+        let subject = private_key(1);
+        let fixture = Fixture::new("mismatch", &subject);
+        let path = fixture.explicit(&certificate(&private_key(2), now() - HOUR, now() + HOUR));
+        let error = for_key(Some(&path), &fixture.key, "test").err().unwrap();
+        assert!(matches!(error, CertificateError::KeyMismatch(_)));
+    }
+
+    #[test]
+    fn selected_certificate_must_exist_and_parse() {
+        let subject = private_key(1);
+        let fixture = Fixture::new("missing", &subject);
+        let missing = fixture.dir.join("missing-cert.pub").display().to_string();
+        assert!(matches!(for_key(Some(&missing), &fixture.key, "test"), Err(CertificateError::NotFound(_))));
+        let path = fixture.explicit("ssh-ed25519-cert-v01@openssh.com AAAA garbage");
+        assert!(matches!(for_key(Some(&path), &fixture.key, "test"), Err(CertificateError::Invalid(..))));
+    }
+
+    #[test]
+    fn auto_discovered_certificate_is_used_when_valid() {
+        let subject = private_key(1);
+        let fixture = Fixture::new("auto-valid", &subject);
+        assert!(for_key(None, &fixture.key, "test").unwrap().is_none());
+        fixture.auto(&certificate(&subject, now() - HOUR, now() + HOUR));
+        assert!(for_key(None, &fixture.key, "test").unwrap().is_some());
+    }
+
+    #[test]
+    fn unusable_auto_discovered_certificate_is_skipped() {
+        let subject = private_key(1);
+        let fixture = Fixture::new("auto-skip", &subject);
+        for line in [
+            certificate(&subject, now() - 2 * HOUR, now() - HOUR),
+            certificate(&subject, now() + HOUR, now() + 2 * HOUR),
+            certificate(&private_key(2), now() - HOUR, now() + HOUR),
+            "not a certificate".to_string(),
+        ] {
+            fixture.auto(&line);
+            assert!(for_key(None, &fixture.key, "test").unwrap().is_none(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn clamp_forever_leaves_other_certificates_alone() {
+        let subject = private_key(1);
+        assert_eq!(clamp_forever(&certificate(&subject, 0, now())), None);
+        assert_eq!(clamp_forever("ssh-ed25519-cert-v01@openssh.com AAAA"), None);
+        assert_eq!(clamp_forever(""), None);
+    }
+}
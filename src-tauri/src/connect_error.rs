@@ -0,0 +1,196 @@
+// Typed reasons for a failed connection attempt, so the frontend can react to
+// the kind of failure (retry, fix credentials, check the host key) instead of
+// parsing messages. Serialized as `{ "kind": "authFailed", "message": "..." }`.
+
+use std::fmt;
+use std::io;
+
+use ssh2::ErrorCode;
+
+use crate::certificates::CertificateError;
+use crate::keys::KeyError;
+use crate::known_hosts::HostKeyError;
+
+// libssh2 gave up waiting on the server (session timeout)
+const LIBSSH2_ERROR_TIMEOUT: i32 = -9;
+
+// "No route to host" / "Network is unreachable" OS error codes. The matching
+// io::ErrorKind variants need a newer toolchain than our MSRV.
+#[cfg(target_os = "linux")]
+const UNREACHABLE_OS_ERRORS: [i32; 2] = [113, 101]; // EHOSTUNREACH, ENETUNREACH
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+const UNREACHABLE_OS_ERRORS: [i32; 2] = [65, 51];
+#[cfg(windows)]
+const UNREACHABLE_OS_ERRORS: [i32; 2] = [10065, 10051]; // WSAEHOSTUNREACH, WSAENETUNREACH
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "ios", target_os = "freebsd", windows)))]
+const UNREACHABLE_OS_ERRORS: [i32; 0] = [];
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "camelCase")]
+pub enum ConnectError {
+    Dns(String),             // The host name did not resolve
+    Refused(String),         // The host actively refused the connection
+    Unreachable(String),     // No route to the host or network
+    Timeout(String),         // No answer within the connect timeout
+    AuthFailed(String),      // Credentials rejected, or the private key could not be used
+    HostKeyMismatch(String), // known_hosts has a different key for this host
+    HostKeyRejected(String), // The user did not accept an unknown host key
+    Protocol(String),        // SSH handshake or channel setup failed
+    Cancelled(String),       // The user dismissed a prompt
+    Other(String),
+}
+
+impl ConnectError {
+    pub fn message(&self) -> &str {
+        match self {
+            ConnectError::Dns(message)
+            | ConnectError::Refused(message)
+            | ConnectError::Unreachable(message)
+            | ConnectError::Timeout(message)
+            | ConnectError::AuthFailed(message)
+            | ConnectError::HostKeyMismatch(message)
+            | ConnectError::HostKeyRejected(message)
+            | ConnectError::Protocol(message)
+            | ConnectError::Cancelled(message)
+            | ConnectError::Other(message) => message,
+        }
+    }
+
+    // Prefixes the message (e.g. with the jump host that failed), keeping the kind
+    pub fn context(self, prefix: &str) -> Self {
+        let wrap = |message: String| format!("{}: {}", prefix, message);
+        match self {
+            ConnectError::Dns(message) => ConnectError::Dns(wrap(message)),
+            ConnectError::Refused(message) => ConnectError::Refused(wrap(message)),
+            ConnectError::Unreachable(message) => ConnectError::Unreachable(wrap(message)),
+            ConnectError::Timeout(message) => ConnectError::Timeout(wrap(message)),
+            ConnectError::AuthFailed(message) => ConnectError::AuthFailed(wrap(message)),
+            ConnectError::HostKeyMismatch(message) => ConnectError::HostKeyMismatch(wrap(message)),
+            ConnectError::HostKeyRejected(message) => ConnectError::HostKeyRejected(wrap(message)),
+            ConnectError::Protocol(message) => ConnectError::Protocol(wrap(message)),
+            ConnectError::Cancelled(message) => ConnectError::Cancelled(wrap(message)),
+            ConnectError::Other(message) => ConnectError::Other(wrap(message)),
+        }
+    }
+
+    // Classifies a failed TCP connect
+    pub fn from_io(error: &io::Error, message: String) -> Self {
+        match error.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => ConnectError::Timeout(message),
+            io::ErrorKind::ConnectionRefused | io::ErrorKind::ConnectionReset => ConnectError::Refused(message),
+            io::ErrorKind::AddrNotAvailable => ConnectError::Unreachable(message),
+            _ if error.raw_os_error().is_some_and(|code| UNREACHABLE_OS_ERRORS.contains(&code)) => {
+                ConnectError::Unreachable(message)
+            }
+            _ => ConnectError::Other(message),
+        }
+    }
+
+    // Classifies a libssh2 failure during the handshake or login
+    pub fn from_ssh(error: &ssh2::Error, message: String) -> Self {
+        if error.code() == ErrorCode::Session(LIBSSH2_ERROR_TIMEOUT) {
+            ConnectError::Timeout(message)
+        } else {
+            ConnectError::Protocol(message)
+        }
+    }
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl From<ConnectError> for String {
+    fn from(e: ConnectError) -> Self {
+        e.to_string()
+    }
+}
+
+impl From<HostKeyError> for ConnectError {
+    fn from(e: HostKeyError) -> Self {
+        let message = e.to_string();
+        match e {
+            HostKeyError::Mismatch { .. } => ConnectError::HostKeyMismatch(message),
+            HostKeyError::Rejected { .. } => ConnectError::HostKeyRejected(message),
+            HostKeyError::Missing => ConnectError::Protocol(message),
+            HostKeyError::Store(_) => ConnectError::Other(message),
+        }
+    }
+}
+
+impl From<KeyError> for ConnectError {
+    fn from(e: KeyError) -> Self {
+        let message = e.to_string();
+        match e {
+            KeyError::Cancelled => ConnectError::Cancelled(message),
+            _ => ConnectError::AuthFailed(message),
+        }
+    }
+}
+
+impl From<CertificateError> for ConnectError {
+    fn from(e: CertificateError) -> Self {
+        ConnectError::AuthFailed(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn io_kind(kind: io::ErrorKind) -> ConnectError {
+        ConnectError::from_io(&io::Error::from(kind), "failed".to_string())
+    }
+
+    #[test]
+    fn io_errors_by_kind() {
+        assert!(matches!(io_kind(io::ErrorKind::TimedOut), ConnectError::Timeout(_)));
+        assert!(matches!(io_kind(io::ErrorKind::WouldBlock), ConnectError::Timeout(_)));
+        assert!(matches!(io_kind(io::ErrorKind::ConnectionRefused), ConnectError::Refused(_)));
+        assert!(matches!(io_kind(io::ErrorKind::ConnectionReset), ConnectError::Refused(_)));
+        assert!(matches!(io_kind(io::ErrorKind::AddrNotAvailable), ConnectError::Unreachable(_)));
+        assert!(matches!(io_kind(io::ErrorKind::PermissionDenied), ConnectError::Other(_)));
+    }
+
+    #[test]
+    fn unreachable_os_errors() {
+        for code in UNREACHABLE_OS_ERRORS {
+            let error = ConnectError::from_io(&io::Error::from_raw_os_error(code), "failed".to_string());
+            assert!(matches!(error, ConnectError::Unreachable(_)), "{}: {:?}", code, error);
+        }
+    }
+
+    #[test]
+    fn ssh_timeouts_and_protocol_errors() {
+        let timeout = ssh2::Error::new(ErrorCode::Session(LIBSSH2_ERROR_TIMEOUT), "timed out");
+        assert!(matches!(ConnectError::from_ssh(&timeout, "failed".to_string()), ConnectError::Timeout(_)));
+        let kex = ssh2::Error::new(ErrorCode::Session(-5), "kex failure");
+        assert!(matches!(ConnectError::from_ssh(&kex, "failed".to_string()), ConnectError::Protocol(_)));
+    }
+
+    #[test]
+    fn from_typed_errors() {
+        let mismatch = HostKeyError::Mismatch { host: "router".to_string(), expected: None, presented: "SHA256:x".to_string() };
+        assert!(matches!(ConnectError::from(mismatch), ConnectError::HostKeyMismatch(_)));
+        let rejected = HostKeyError::Rejected { host: "router".to_string(), fingerprint: "SHA256:x".to_string() };
+        assert!(matches!(ConnectError::from(rejected), ConnectError::HostKeyRejected(_)));
+        assert!(matches!(ConnectError::from(HostKeyError::Missing), ConnectError::Protocol(_)));
+        assert!(matches!(ConnectError::from(KeyError::Cancelled), ConnectError::Cancelled(_)));
+        assert!(matches!(ConnectError::from(KeyError::BadPassphrase), ConnectError::AuthFailed(_)));
+    }
+
+    #[test]
+    fn context_prefixes_and_keeps_kind() {
+        let error = ConnectError::Refused("Connection refused".to_string()).context("Jump host 1 (ops@bastion:22)");
+        assert!(matches!(&error, ConnectError::Refused(_)));
+        assert_eq!(error.message(), "Jump host 1 (ops@bastion:22): Connection refused");
+    }
+
+    #[test]
+    fn serializes_kind_and_message() {
+        let json = serde_json::to_value(ConnectError::AuthFailed("Password rejected".to_string())).unwrap();
+        assert_eq!(json, serde_json::json!({ "kind": "authFailed", "message": "Password rejected" }));
+    }
+}
@@ -0,0 +1,508 @@
+// Port forwarding over an open SSH session: local (-L), remote (-R) and
+// dynamic SOCKS5 (-D). Forwards live in their session's handle and stop when
+// removed or when the session closes. Status changes go out as `port-forward-status`.
+
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use ssh2::{ErrorCode, Session};
+use tauri::{command, AppHandle, State};
+
+use crate::session::{emit_event, session_transport};
+use crate::ssh_client::{retry, IDLE_POLL, LIBSSH2_ERROR_EAGAIN};
+use crate::tunnel;
+use crate::AppState;
+
+const DEFAULT_BIND_ADDRESS: &str = "127.0.0.1";
+
+// --- Types ---
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForwardKind {
+    Local,   // Listen here, connect from the server
+    Remote,  // Listen on the server, connect from here
+    Dynamic, // Local SOCKS5 proxy, connect from the server
+}
+
+// What the frontend asks for
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForwardSpec {
+    pub kind: ForwardKind,
+    pub bind_address: Option<String>, // Defaults to 127.0.0.1 (local/dynamic) or the server's choice (remote)
+    pub bind_port: u16,               // 0 picks a free port
+    pub target_host: Option<String>,  // Not used for dynamic forwards
+    pub target_port: Option<u16>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForwardInfo {
+    pub id: String,
+    pub session_id: String,
+    pub kind: ForwardKind,
+    pub bind_address: String,
+    pub bind_port: u16, // The port actually bound
+    pub target_host: Option<String>,
+    pub target_port: Option<u16>,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForwardStatus {
+    Listening,
+    Error, // A bind or channel failure; the forward may still be listening
+    Closed,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForwardStatusPayload {
+    pub session_id: String,
+    pub forward_id: String,
+    pub status: ForwardStatus,
+    pub message: String,
+}
+
+// Kept in the session handle; dropping it stops the listener.
+// Connections already accepted run until either end closes them.
+pub struct ForwardHandle {
+    pub info: ForwardInfo,
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for ForwardHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+// --- Helpers ---
+
+fn emit_status(app_handle: &AppHandle, info: &ForwardInfo, status: ForwardStatus, message: String) {
+    println!("[{}] Forward {}: {}", info.session_id, info.id, message);
+    emit_event(
+        app_handle,
+        "port-forward-status",
+        ForwardStatusPayload { session_id: info.session_id.clone(), forward_id: info.id.clone(), status, message },
+    );
+}
+
+fn describe(info: &ForwardInfo) -> String {
+    match info.kind {
+        ForwardKind::Dynamic => format!("SOCKS5 proxy on {}:{}", info.bind_address, info.bind_port),
+        _ => format!(
+            "{}:{} -> {}:{}",
+            info.bind_address,
+            info.bind_port,
+            info.target_host.as_deref().unwrap_or("?"),
+            info.target_port.unwrap_or(0)
+        ),
+    }
+}
+
+// Opens a direct-tcpip channel and relays an accepted local connection over it
+fn relay_to_remote(app_handle: &AppHandle, info: &ForwardInfo, ssh: &Session, mut socket: TcpStream, host: &str, port: u16) {
+    let peer = socket.peer_addr().ok();
+    let source = peer.map(|addr| (addr.ip().to_string(), addr.port()));
+    let channel = retry(|| ssh.channel_direct_tcpip(host, port, source.as_ref().map(|(ip, port)| (ip.as_str(), *port))));
+    match channel {
+        Ok(mut channel) => {
+            if let Err(e) = tunnel::bridge(&mut channel, &mut socket) {
+                emit_status(app_handle, info, ForwardStatus::Error, format!("Connection to {}:{} failed: {}", host, port, e));
+            }
+        }
+        Err(e) => emit_status(
+            app_handle,
+            info,
+            ForwardStatus::Error,
+            format!("Server could not open {}:{}: {}", host, port, e),
+        ),
+    }
+}
+
+// --- Local & Dynamic Forwards ---
+
+fn run_local_listener(app_handle: AppHandle, info: ForwardInfo, ssh: Session, listener: TcpListener, stop: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        if let Err(e) = listener.set_nonblocking(true) {
+            emit_status(&app_handle, &info, ForwardStatus::Error, format!("Listener setup failed: {}", e));
+            return;
+        }
+        while !stop.load(Ordering::SeqCst) {
+            match listener.accept() {
+                Ok((socket, _)) => {
+                    let _ = socket.set_nonblocking(false);
+                    let (app_handle, info, ssh) = (app_handle.clone(), info.clone(), ssh.clone());
+                    thread::spawn(move || match info.kind {
+                        ForwardKind::Dynamic => serve_socks5(&app_handle, &info, &ssh, socket),
+                        _ => {
+                            let host = info.target_host.clone().unwrap_or_default();
+                            let port = info.target_port.unwrap_or(0);
+                            relay_to_remote(&app_handle, &info, &ssh, socket, &host, port);
+                        }
+                    });
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(IDLE_POLL),
+                Err(e) => {
+                    emit_status(&app_handle, &info, ForwardStatus::Error, format!("Accept failed: {}", e));
+                    break;
+                }
+            }
+        }
+        emit_status(&app_handle, &info, ForwardStatus::Closed, format!("Stopped {}", describe(&info)));
+    });
+}
+
+// SOCKS5 reply codes (RFC 1928)
+const SOCKS_SUCCEEDED: u8 = 0x00;
+const SOCKS_HOST_UNREACHABLE: u8 = 0x04;
+const SOCKS_COMMAND_NOT_SUPPORTED: u8 = 0x07;
+const SOCKS_ADDRESS_NOT_SUPPORTED: u8 = 0x08;
+
+fn socks_reply(socket: &mut impl Write, code: u8) -> io::Result<()> {
+    socket.write_all(&[0x05, code, 0x00, 0x01, 0, 0, 0, 0, 0, 0])
+}
+
+// Reads a SOCKS5 CONNECT request (no authentication) and returns the requested host and port
+fn socks_handshake<S: Read + Write>(socket: &mut S) -> io::Result<Option<(String, u16)>> {
+    let mut header = [0u8; 2];
+    socket.read_exact(&mut header)?;
+    if header[0] != 0x05 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a SOCKS5 client"));
+    }
+    let mut methods = vec![0u8; header[1] as usize];
+    socket.read_exact(&mut methods)?;
+    if !methods.contains(&0x00) {
+        socket.write_all(&[0x05, 0xFF])?;
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "client requires SOCKS authentication"));
+    }
+    socket.write_all(&[0x05, 0x00])?;
+
+    let mut request = [0u8; 4];
+    socket.read_exact(&mut request)?;
+    let host = match request[3] {
+        0x01 => {
+            let mut octets = [0u8; 4];
+            socket.read_exact(&mut octets)?;
+            Ipv4Addr::from(octets).to_string()
+        }
+        0x03 => {
+            let mut len = [0u8; 1];
+            socket.read_exact(&mut len)?;
+            let mut name = vec![0u8; len[0] as usize];
+            socket.read_exact(&mut name)?;
+            String::from_utf8_lossy(&name).to_string()
+        }
+        0x04 => {
+            let mut octets = [0u8; 16];
+            socket.read_exact(&mut octets)?;
+            Ipv6Addr::from(octets).to_string()
+        }
+        _ => {
+            socks_reply(socket, SOCKS_ADDRESS_NOT_SUPPORTED)?;
+            return Ok(None);
+        }
+    };
+    let mut port = [0u8; 2];
+    socket.read_exact(&mut port)?;
+    if request[1] != 0x01 {
+        // Only CONNECT; BIND and UDP ASSOCIATE cannot be carried over direct-tcpip
+        socks_reply(socket, SOCKS_COMMAND_NOT_SUPPORTED)?;
+        return Ok(None);
+    }
+    Ok(Some((host, u16::from_be_bytes(port))))
+}
+
+fn serve_socks5(app_handle: &AppHandle, info: &ForwardInfo, ssh: &Session, mut socket: TcpStream) {
+    let (host, port) = match socks_handshake(&mut socket) {
+        Ok(Some(target)) => target,
+        Ok(None) => return,
+        Err(e) => {
+            emit_status(app_handle, info, ForwardStatus::Error, format!("SOCKS handshake failed: {}", e));
+            return;
+        }
+    };
+    match retry(|| ssh.channel_direct_tcpip(&host, port, None)) {
+        Ok(mut channel) => {
+            if socks_reply(&mut socket, SOCKS_SUCCEEDED).is_err() {
+                return;
+            }
+            if let Err(e) = tunnel::bridge(&mut channel, &mut socket) {
+                emit_status(app_handle, info, ForwardStatus::Error, format!("Connection to {}:{} failed: {}", host, port, e));
+            }
+        }
+        Err(e) => {
+            let _ = socks_reply(&mut socket, SOCKS_HOST_UNREACHABLE);
+            emit_status(app_handle, info, ForwardStatus::Error, format!("Server could not open {}:{}: {}", host, port, e));
+        }
+    }
+}
+
+// --- Remote Forwards ---
+
+fn run_remote_listener(app_handle: AppHandle, info: ForwardInfo, mut listener: ssh2::Listener, stop: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let host = info.target_host.clone().unwrap_or_default();
+        let port = info.target_port.unwrap_or(0);
+        while !stop.load(Ordering::SeqCst) {
+            match listener.accept() {
+                Ok(mut channel) => {
+                    let (app_handle, info, host) = (app_handle.clone(), info.clone(), host.clone());
+                    thread::spawn(move || match TcpStream::connect((host.as_str(), port)) {
+                        Ok(mut socket) => {
+                            if let Err(e) = tunnel::bridge(&mut channel, &mut socket) {
+                                emit_status(&app_handle, &info, ForwardStatus::Error, format!("Connection to {}:{} failed: {}", host, port, e));
+                            }
+                        }
+                        Err(e) => {
+                            let _ = retry(|| channel.close());
+                            emit_status(&app_handle, &info, ForwardStatus::Error, format!("Cannot connect to {}:{}: {}", host, port, e));
+                        }
+                    });
+                }
+                Err(e) if e.code() == ErrorCode::Session(LIBSSH2_ERROR_EAGAIN) => thread::sleep(IDLE_POLL),
+                Err(e) => {
+                    emit_status(&app_handle, &info, ForwardStatus::Error, format!("Remote listener failed: {}", e));
+                    break;
+                }
+            }
+        }
+        // Dropping the listener cancels the forward on the server
+        drop(listener);
+        emit_status(&app_handle, &info, ForwardStatus::Closed, format!("Stopped {}", describe(&info)));
+    });
+}
+
+// --- Tauri Commands ---
+
+#[command]
+pub async fn add_port_forward(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    session_id: String,
+    spec: ForwardSpec,
+) -> Result<ForwardInfo, String> {
+    let ssh = session_transport(&state.sessions, &session_id)?;
+    if spec.kind != ForwardKind::Dynamic && (spec.target_host.as_deref().unwrap_or("").is_empty() || spec.target_port.is_none()) {
+        return Err("Local and remote forwards need a target host and port".to_string());
+    }
+
+    let mut info = ForwardInfo {
+        id: uuid::Uuid::new_v4().to_string(),
+        session_id: session_id.clone(),
+        kind: spec.kind,
+        bind_address: String::new(),
+        bind_port: spec.bind_port,
+        target_host: spec.target_host.clone(),
+        target_port: spec.target_port,
+    };
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let bind_result = match spec.kind {
+        ForwardKind::Local | ForwardKind::Dynamic => {
+            let address = spec.bind_address.clone().unwrap_or_else(|| DEFAULT_BIND_ADDRESS.to_string());
+            let ip: IpAddr = address.parse().map_err(|_| format!("Invalid bind address {}", address))?;
+            TcpListener::bind(SocketAddr::new(ip, spec.bind_port))
+                .and_then(|listener| listener.local_addr().map(|addr| (listener, addr.port())))
+                .map(|(listener, port)| {
+                    info.bind_address = address;
+                    info.bind_port = port;
+                    run_local_listener(app_handle.clone(), info.clone(), ssh.clone(), listener, stop.clone());
+                })
+                .map_err(|e| e.to_string())
+        }
+        ForwardKind::Remote => {
+            let address = spec.bind_address.clone().filter(|a| !a.is_empty());
+            let bound = forward_listen(&ssh, spec.bind_port, address.clone()).await;
+            bound.map(|(listener, port)| {
+                info.bind_address = address.unwrap_or_else(|| "server default".to_string());
+                info.bind_port = port;
+                run_remote_listener(app_handle.clone(), info.clone(), listener, stop.clone());
+            })
+        }
+    };
+
+    if let Err(e) = bind_result {
+        let message = format!("Failed to bind {:?} forward on port {}: {}", spec.kind, spec.bind_port, e);
+        emit_status(&app_handle, &info, ForwardStatus::Error, message.clone());
+        return Err(message);
+    }
+
+    {
+        let mut guard = state.sessions.lock().map_err(|_| "Failed to lock state mutex".to_string())?;
+        let handle = guard
+            .get_mut(&session_id)
+            .ok_or_else(|| format!("Not connected: unknown session {}", session_id))?;
+        handle.forwards.insert(info.id.clone(), ForwardHandle { info: info.clone(), stop });
+    }
+    emit_status(&app_handle, &info, ForwardStatus::Listening, format!("Forwarding {}", describe(&info)));
+    Ok(info)
+}
+
+// Asks the server to listen; libssh2 calls block, so run them off the async runtime
+async fn forward_listen(
+    ssh: &Session,
+    port: u16,
+    address: Option<String>,
+) -> Result<(ssh2::Listener, u16), String> {
+    let ssh = ssh.clone();
+    tokio::task::spawn_blocking(move || {
+        retry(|| ssh.channel_forward_listen(port, address.as_deref(), None)).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| format!("Forward task failed: {}", e))?
+}
+
+#[command]
+pub async fn list_port_forwards(state: State<'_, AppState>, session_id: Option<String>) -> Result<Vec<ForwardInfo>, String> {
+    let guard = state.sessions.lock().map_err(|_| "Failed to lock state mutex".to_string())?;
+    Ok(guard
+        .iter()
+        .filter(|(id, _)| session_id.as_deref().map_or(true, |wanted| wanted == id.as_str()))
+        .flat_map(|(_, handle)| handle.forwards.values().map(|forward| forward.info.clone()))
+        .collect())
+}
+
+#[command]
+pub async fn remove_port_forward(state: State<'_, AppState>, session_id: String, forward_id: String) -> Result<(), String> {
+    let removed = {
+        let mut guard = state.sessions.lock().map_err(|_| "Failed to lock state mutex".to_string())?;
+        let handle = guard
+            .get_mut(&session_id)
+            .ok_or_else(|| format!("Not connected: unknown session {}", session_id))?;
+        handle.forwards.remove(&forward_id)
+    };
+    // Dropping the handle stops the listener thread, which reports `closed`
+    match removed {
+        Some(_) => Ok(()),
+        None => Err(format!("Unknown port forward {}", forward_id)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A SOCKS client that has sent `input` and collects what the proxy writes back
+    struct Client {
+        input: io::Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+
+    impl Read for Client {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for Client {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // Greeting offering "no authentication", then the request
+    fn handshake(request: &[u8]) -> (io::Result<Option<(String, u16)>>, Vec<u8>) {
+        let mut input = vec![0x05, 0x01, 0x00];
+        input.extend_from_slice(request);
+        let mut client = Client { input: io::Cursor::new(input), output: Vec::new() };
+        let result = socks_handshake(&mut client);
+        (result, client.output)
+    }
+
+    const METHOD_ACCEPTED: [u8; 2] = [0x05, 0x00];
+
+    fn reply(code: u8) -> Vec<u8> {
+        let mut expected = METHOD_ACCEPTED.to_vec();
+        expected.extend_from_slice(&[0x05, code, 0x00, 0x01, 0, 0, 0, 0, 0, 0]);
+        expected
+    }
+
+    #[test]
+    fn connect_to_ipv4_address() {
+        let (result, output) = handshake(&[0x05, 0x01, 0x00, 0x01, 10, 0, 0, 1, 0x00, 0x16]);
+        assert_eq!(result.unwrap(), Some(("10.0.0.1".to_string(), 22)));
+        // The success reply is sent only once the SSH channel is open
+        assert_eq!(output, METHOD_ACCEPTED);
+    }
+
+    #[test]
+    fn connect_to_domain_name() {
+        let mut request = vec![0x05, 0x01, 0x00, 0x03, 11];
+        request.extend_from_slice(b"example.com");
+        request.extend_from_slice(&443u16.to_be_bytes());
+        let (result, output) = handshake(&request);
+        assert_eq!(result.unwrap(), Some(("example.com".to_string(), 443)));
+        assert_eq!(output, METHOD_ACCEPTED);
+    }
+
+    #[test]
+    fn connect_to_ipv6_address() {
+        let mut request = vec![0x05, 0x01, 0x00, 0x04];
+        request.extend_from_slice(&"2001:db8::1".parse::<Ipv6Addr>().unwrap().octets());
+        request.extend_from_slice(&8080u16.to_be_bytes());
+        let (result, _) = handshake(&request);
+        assert_eq!(result.unwrap(), Some(("2001:db8::1".to_string(), 8080)));
+    }
+
+    #[test]
+    fn client_without_acceptable_method_is_refused() {
+        // Only username/password (0x02) offered
+        let mut client = Client { input: io::Cursor::new(vec![0x05, 0x01, 0x02]), output: Vec::new() };
+        let error = socks_handshake(&mut client).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(client.output, [0x05, 0xFF]);
+    }
+
+    #[test]
+    fn bind_and_udp_associate_are_not_supported() {
+        for command in [0x02, 0x03] {
+            let (result, output) = handshake(&[0x05, command, 0x00, 0x01, 10, 0, 0, 1, 0x00, 0x16]);
+            assert_eq!(result.unwrap(), None);
+            assert_eq!(output, reply(SOCKS_COMMAND_NOT_SUPPORTED));
+        }
+    }
+
+    #[test]
+    fn unknown_address_type_is_not_supported() {
+        let (result, output) = handshake(&[0x05, 0x01, 0x00, 0x05, 1, 2, 3, 4]);
+        assert_eq!(result.unwrap(), None);
+        assert_eq!(output, reply(SOCKS_ADDRESS_NOT_SUPPORTED));
+    }
+
+    #[test]
+    fn socks4_and_truncated_requests_fail() {
+        let mut client = Client { input: io::Cursor::new(vec![0x04, 0x01, 0x00, 0x16]), output: Vec::new() };
+        assert_eq!(socks_handshake(&mut client).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert!(client.output.is_empty());
+
+        let (result, _) = handshake(&[0x05, 0x01, 0x00, 0x01, 10, 0]);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn handshake_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(address).unwrap();
+            stream.write_all(&[0x05, 0x02, 0x02, 0x00]).unwrap();
+            let mut method = [0u8; 2];
+            stream.read_exact(&mut method).unwrap();
+            stream.write_all(&[0x05, 0x01, 0x00, 0x01, 192, 0, 2, 7, 0x01, 0xBB]).unwrap();
+            method
+        });
+        let (mut socket, _) = listener.accept().unwrap();
+        assert_eq!(socks_handshake(&mut socket).unwrap(), Some(("192.0.2.7".to_string(), 443)));
+        assert_eq!(client.join().unwrap(), METHOD_ACCEPTED);
+    }
+}
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
-use tauri::command;
+use tauri::{command, State};
 use std::env;
 
+use crate::vault::GEMINI_API_KEY_SECRET;
+use crate::AppState;
+
 // Structures based on Gemini API's generateContent method (common pattern)
 #[derive(Serialize, Debug)]
 struct GeminiRequest {
@@ -43,10 +46,26 @@ struct PartResponse {
     text: String,
 }
 
+// The key stored in the unlocked vault, else GEMINI_API_KEY from the environment/.env
+fn api_key(state: &AppState) -> Result<String, String> {
+    let mut vault = state.vault.lock().map_err(|_| "Failed to lock vault mutex".to_string())?;
+    if vault.is_unlocked() {
+        if let Ok(key) = vault.secret(GEMINI_API_KEY_SECRET) {
+            return Ok(key.to_string());
+        }
+    }
+    env::var("GEMINI_API_KEY").map_err(|_| {
+        format!(
+            "No Gemini API key: unlock the vault and store one as \"{}\", or set GEMINI_API_KEY",
+            GEMINI_API_KEY_SECRET
+        )
+    })
+}
+
 // Tauri command to send a prompt to Gemini
 #[command]
-pub async fn send_to_gemini(prompt: String) -> Result<String, String> {
-    let api_key = env::var("GEMINI_API_KEY").map_err(|e| format!("Failed to get GEMINI_API_KEY: {}", e))?;
+pub async fn send_to_gemini(state: State<'_, AppState>, prompt: String) -> Result<String, String> {
+    let api_key = api_key(&state)?;
     // Use the specific model in the endpoint URL. Adjust region/project if needed.
     // Using v1beta as an example, check current Gemini docs for stable endpoints.
     let model_name = "gemini-2.0-flash"; // Updated model name
@@ -0,0 +1,588 @@
+// Connection history: every connection attempt is recorded before it starts
+// and completed when it fails or the session ends (how it ended, exit status,
+// bytes each way), in a versioned JSON file in the app data directory, written
+// on a background thread. Entries
+// can be listed with filters and reopened with the same host, user and
+// transport settings (or profile).
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::{command, AppHandle, Manager, State};
+
+use crate::algorithms::AlgorithmOverrides;
+use crate::connect_error::ConnectError;
+use crate::local_shell;
+use crate::profiles::{self, ProfileJumpHost};
+use crate::serial::{self, SerialFlowControl, SerialParity};
+use crate::session::{SessionKind, SessionState};
+use crate::ssh_client::{self, JumpHostConfig};
+use crate::telnet;
+use crate::AppState;
+
+const HISTORY_FILE: &str = "history.json";
+const HISTORY_VERSION: u32 = 1;
+// Oldest entries are dropped beyond this
+const MAX_ENTRIES: usize = 1000;
+// Prefix of the exit status in `ssh-closed` messages ("... Exit status: 0")
+const EXIT_STATUS_PREFIX: &str = "Exit status: ";
+
+// Serializes read-modify-write cycles on the file
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+// Counters and profile of each open session, until it ends
+pub type HistoryLog = Arc<Mutex<HashMap<String, OpenEntry>>>;
+
+#[derive(Default)]
+pub struct OpenEntry {
+    bytes_in: u64,
+    bytes_out: u64,
+    profile: Option<(String, String)>, // Id and name
+}
+
+// --- Types ---
+
+// How a session was opened, without secrets, so it can be opened the same way again
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Transport {
+    #[serde(rename_all = "camelCase")]
+    Ssh {
+        private_key_path: Option<String>,
+        certificate_path: Option<String>,
+        jump_hosts: Vec<ProfileJumpHost>,
+        algorithms: AlgorithmOverrides,
+    },
+    #[serde(rename_all = "camelCase")]
+    Telnet { terminal_type: Option<String> },
+    #[serde(rename_all = "camelCase")]
+    Serial { baud_rate: u32, data_bits: u8, parity: SerialParity, stop_bits: u8, flow_control: SerialFlowControl },
+    #[serde(rename_all = "camelCase")]
+    Local { shell: Option<String>, cwd: Option<String> },
+}
+
+impl Transport {
+    pub fn ssh(
+        private_key_path: Option<String>,
+        certificate_path: Option<String>,
+        jump_hosts: &[JumpHostConfig],
+        algorithms: AlgorithmOverrides,
+    ) -> Self {
+        let jump_hosts = jump_hosts
+            .iter()
+            .map(|hop| ProfileJumpHost {
+                hostname: hop.hostname.clone(),
+                port: hop.port.unwrap_or(22),
+                username: hop.username.clone(),
+                private_key_path: hop.private_key_path.clone(),
+                certificate_path: hop.certificate_path.clone(),
+                password_secret_id: None,
+                passphrase_secret_id: None,
+                algorithms: hop.algorithms.clone(),
+            })
+            .collect();
+        Transport::Ssh { private_key_path, certificate_path, jump_hosts, algorithms }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub id: String, // The session id
+    pub profile_id: Option<String>,
+    pub profile_name: Option<String>,
+    pub kind: SessionKind,
+    pub hostname: String, // Device path for serial sessions
+    pub port: u16,
+    pub username: String,
+    pub started_at: u64, // Unix seconds, when the attempt began
+    #[serde(default)]
+    pub connected_at: Option<u64>, // None if the attempt failed (or is still running)
+    pub ended_at: Option<u64>, // None while open, or if the app quit before the session ended
+    pub end_state: Option<SessionState>, // Closed or Failed
+    pub exit_status: Option<i32>,
+    pub message: Option<String>, // The `ssh-closed` message
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    #[serde(default)]
+    pub transport: Option<Transport>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct HistoryFile {
+    version: u32,
+    #[serde(default)]
+    entries: Vec<HistoryEntry>,
+}
+
+// All fields optional; entries must match every field that is set
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistoryFilter {
+    pub query: Option<String>, // Case-insensitive match on host, user or profile name
+    pub kind: Option<SessionKind>,
+    pub profile_id: Option<String>,
+    pub since: Option<u64>, // Started at or after (Unix seconds)
+    pub until: Option<u64>, // Started before
+    pub failed_only: bool,
+    pub limit: Option<usize>,
+}
+
+impl HistoryFilter {
+    fn matches(&self, entry: &HistoryEntry) -> bool {
+        let query = self.query.as_deref().map(|query| query.trim().to_lowercase()).filter(|query| !query.is_empty());
+        let query_matches = query.map_or(true, |query| {
+            [Some(&entry.hostname), Some(&entry.username), entry.profile_name.as_ref()]
+                .into_iter()
+                .flatten()
+                .any(|field| field.to_lowercase().contains(&query))
+        });
+        query_matches
+            && self.kind.map_or(true, |kind| entry.kind == kind)
+            && self.profile_id.as_ref().map_or(true, |id| entry.profile_id.as_ref() == Some(id))
+            && self.since.map_or(true, |since| entry.started_at >= since)
+            && self.until.map_or(true, |until| entry.started_at < until)
+            && (!self.failed_only || entry.end_state == Some(SessionState::Failed))
+    }
+}
+
+// --- Storage ---
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn store_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to locate app data directory: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    Ok(dir.join(HISTORY_FILE))
+}
+
+fn load(path: &Path) -> Result<Vec<HistoryEntry>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let file: HistoryFile =
+        serde_json::from_str(&contents).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+    if file.version != HISTORY_VERSION {
+        return Err(format!("{} has unsupported format version {}", path.display(), file.version));
+    }
+    Ok(file.entries)
+}
+
+fn save(path: &Path, mut entries: Vec<HistoryEntry>) -> Result<(), String> {
+    if entries.len() > MAX_ENTRIES {
+        entries.drain(..entries.len() - MAX_ENTRIES);
+    }
+    let file = HistoryFile { version: HISTORY_VERSION, entries };
+    let contents = serde_json::to_string_pretty(&file).map_err(|e| format!("Failed to encode history: {}", e))?;
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, contents).map_err(|e| format!("Failed to write {}: {}", temp.display(), e))?;
+    fs::rename(&temp, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+fn modify(path: &Path, update: impl FnOnce(&mut Vec<HistoryEntry>)) -> Result<(), String> {
+    let _guard = STORE_LOCK.lock().map_err(|_| "Failed to lock history store".to_string())?;
+    let mut entries = load(path)?;
+    update(&mut entries);
+    save(path, entries)
+}
+
+type Update = Box<dyn FnOnce(&mut Vec<HistoryEntry>) + Send>;
+
+// A change to the file, applied by the writer thread
+struct PendingWrite {
+    app_handle: AppHandle,
+    session_id: String,
+    update: Update,
+}
+
+// One thread applies all changes in the order they were recorded, so the
+// commands opening sessions never wait on the file
+fn writer() -> &'static mpsc::Sender<PendingWrite> {
+    static WRITER: OnceLock<mpsc::Sender<PendingWrite>> = OnceLock::new();
+    WRITER.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<PendingWrite>();
+        thread::spawn(move || {
+            for write in receiver {
+                let result = store_path(&write.app_handle).and_then(|path| modify(&path, write.update));
+                if let Err(e) = result {
+                    eprintln!("[{}] Failed to update connection history: {}", write.session_id, e);
+                }
+            }
+        });
+        sender
+    })
+}
+
+fn queue(app_handle: &AppHandle, session_id: &str, update: impl FnOnce(&mut Vec<HistoryEntry>) + Send + 'static) {
+    let write = PendingWrite { app_handle: app_handle.clone(), session_id: session_id.to_string(), update: Box::new(update) };
+    if writer().send(write).is_err() {
+        eprintln!("[{}] Connection history writer has stopped", session_id);
+    }
+}
+
+fn update_entry(app_handle: &AppHandle, session_id: &str, update: impl FnOnce(&mut HistoryEntry) + Send + 'static) {
+    let id = session_id.to_string();
+    queue(app_handle, session_id, move |entries| {
+        if let Some(entry) = entries.iter_mut().rev().find(|entry| entry.id == id) {
+            update(entry);
+        }
+    });
+}
+
+fn log(app_handle: &AppHandle) -> HistoryLog {
+    app_handle.state::<AppState>().history.clone()
+}
+
+// "Connection closed. Exit status: 0" -> 0
+fn exit_status(message: &str) -> Option<i32> {
+    let start = message.rfind(EXIT_STATUS_PREFIX)? + EXIT_STATUS_PREFIX.len();
+    let digits: String = message[start..].chars().take_while(|c| c.is_ascii_digit() || *c == '-').collect();
+    digits.parse().ok()
+}
+
+// --- Recording ---
+
+// Records a connection attempt; call before anything goes over the wire, then
+// `record_connected` or `record_failure`
+pub fn record_attempt(
+    app_handle: &AppHandle,
+    session_id: &str,
+    kind: SessionKind,
+    hostname: &str,
+    port: u16,
+    username: &str,
+    transport: Transport,
+) {
+    if let Ok(mut open) = log(app_handle).lock() {
+        open.insert(session_id.to_string(), OpenEntry::default());
+    }
+    let entry = HistoryEntry {
+        id: session_id.to_string(),
+        profile_id: None,
+        profile_name: None,
+        kind,
+        hostname: hostname.to_string(),
+        port,
+        username: username.to_string(),
+        started_at: now(),
+        connected_at: None,
+        ended_at: None,
+        end_state: None,
+        exit_status: None,
+        message: None,
+        bytes_in: 0,
+        bytes_out: 0,
+        transport: Some(transport),
+    };
+    queue(app_handle, session_id, move |entries| entries.push(entry));
+}
+
+// The attempt succeeded and the session is registered
+pub fn record_connected(app_handle: &AppHandle, session_id: &str) {
+    let connected_at = now();
+    update_entry(app_handle, session_id, move |entry| entry.connected_at = Some(connected_at));
+}
+
+// The attempt failed before a session came up
+pub fn record_failure(app_handle: &AppHandle, session_id: &str, error: &impl Display) {
+    record_end(app_handle, session_id, SessionState::Failed, &error.to_string());
+}
+
+// Notes the profile a session was opened from
+pub fn attach_profile(app_handle: &AppHandle, session_id: &str, profile_id: &str, profile_name: &str) {
+    if let Ok(mut open) = log(app_handle).lock() {
+        if let Some(entry) = open.get_mut(session_id) {
+            entry.profile = Some((profile_id.to_string(), profile_name.to_string()));
+        }
+    }
+    let (profile_id, profile_name) = (profile_id.to_string(), profile_name.to_string());
+    update_entry(app_handle, session_id, move |entry| {
+        entry.profile_id = Some(profile_id);
+        entry.profile_name = Some(profile_name);
+    });
+}
+
+pub fn count_in(app_handle: &AppHandle, session_id: &str, bytes: usize) {
+    if let Some(entry) = log(app_handle).lock().ok().as_mut().and_then(|open| open.get_mut(session_id)) {
+        entry.bytes_in += bytes as u64;
+    }
+}
+
+pub fn count_out(app_handle: &AppHandle, session_id: &str, bytes: usize) {
+    if let Some(entry) = log(app_handle).lock().ok().as_mut().and_then(|open| open.get_mut(session_id)) {
+        entry.bytes_out += bytes as u64;
+    }
+}
+
+// Completes the entry with how the session ended
+pub fn record_end(app_handle: &AppHandle, session_id: &str, state: SessionState, message: &str) {
+    let open = log(app_handle).lock().ok().and_then(|mut open| open.remove(session_id));
+    let Some(open) = open else {
+        return;
+    };
+    let ended_at = now();
+    let message = message.to_string();
+    update_entry(app_handle, session_id, move |entry| {
+        entry.ended_at = Some(ended_at);
+        entry.end_state = Some(state);
+        entry.exit_status = exit_status(&message);
+        entry.message = Some(message);
+        entry.bytes_in = open.bytes_in;
+        entry.bytes_out = open.bytes_out;
+        if let Some((id, name)) = open.profile {
+            entry.profile_id = Some(id);
+            entry.profile_name = Some(name);
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+// Newest first
+#[command]
+pub async fn list_connection_history(app_handle: AppHandle, filter: Option<HistoryFilter>) -> Result<Vec<HistoryEntry>, String> {
+    let filter = filter.unwrap_or_default();
+    let entries = {
+        let _guard = STORE_LOCK.lock().map_err(|_| "Failed to lock history store".to_string())?;
+        load(&store_path(&app_handle)?)?
+    };
+    Ok(entries
+        .into_iter()
+        .rev()
+        .filter(|entry| filter.matches(entry))
+        .take(filter.limit.unwrap_or(usize::MAX))
+        .collect())
+}
+
+// Opens a new session like the one in a history entry: through its profile
+// when that still exists, otherwise with the recorded host, user and transport
+// settings. Credentials are not recorded, so SSH falls back to whatever the
+// call brings, the agent, default keys and keyboard-interactive prompts.
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn reconnect_from_history(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    entry_id: String,
+    password: Option<String>,
+    private_key_path: Option<String>,
+    passphrase: Option<String>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+) -> Result<String, ConnectError> {
+    let entry = {
+        let _guard = STORE_LOCK.lock().map_err(|_| ConnectError::Other("Failed to lock history store".to_string()))?;
+        load(&store_path(&app_handle).map_err(ConnectError::Other)?)
+            .map_err(ConnectError::Other)?
+            .into_iter()
+            .rev()
+            .find(|entry| entry.id == entry_id)
+            .ok_or_else(|| ConnectError::Other(format!("Unknown history entry {}", entry_id)))?
+    };
+    println!("Reconnecting from history entry {} ({:?} {})", entry.id, entry.kind, entry.hostname);
+
+    if let Some(profile_id) = &entry.profile_id {
+        if profiles::find(&app_handle, profile_id).is_ok() {
+            return profiles::connect_profile(app_handle, state, profile_id.clone(), password, passphrase, cols, rows).await;
+        }
+    }
+
+    let Some(transport) = entry.transport else {
+        return Err(ConnectError::Other(format!("History entry {} has no recorded connection settings", entry.id)));
+    };
+    match transport {
+        Transport::Ssh { private_key_path: recorded_key, certificate_path, jump_hosts, algorithms } => {
+            let jump_hosts: Vec<JumpHostConfig> = jump_hosts
+                .into_iter()
+                .map(|hop| JumpHostConfig {
+                    hostname: hop.hostname,
+                    port: Some(hop.port),
+                    username: hop.username,
+                    password: None,
+                    private_key_path: hop.private_key_path,
+                    passphrase: None,
+                    certificate_path: hop.certificate_path,
+                    algorithms: hop.algorithms,
+                })
+                .collect();
+            ssh_client::ssh_connect(
+                app_handle,
+                state,
+                entry.hostname,
+                entry.port,
+                entry.username,
+                password,
+                private_key_path.or(recorded_key),
+                passphrase,
+                certificate_path,
+                (!jump_hosts.is_empty()).then_some(jump_hosts),
+                cols,
+                rows,
+                None,
+                None,
+                None,
+                Some(algorithms),
+            )
+            .await
+        }
+        Transport::Telnet { terminal_type } => {
+            telnet::telnet_connect(app_handle, state, entry.hostname, Some(entry.port), Some(entry.username), terminal_type, cols, rows, None)
+                .await
+                .map_err(ConnectError::Other)
+        }
+        Transport::Serial { baud_rate, data_bits, parity, stop_bits, flow_control } => serial::serial_connect(
+            app_handle,
+            state,
+            entry.hostname,
+            Some(baud_rate),
+            Some(data_bits),
+            Some(parity),
+            Some(stop_bits),
+            Some(flow_control),
+        )
+        .await
+        .map_err(ConnectError::Other),
+        Transport::Local { shell, cwd } => local_shell::local_shell_connect(app_handle, state, shell, cwd, cols, rows)
+            .await
+            .map_err(ConnectError::Other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str) -> HistoryEntry {
+        HistoryEntry {
+            id: id.to_string(),
+            profile_id: Some("p-1".to_string()),
+            profile_name: Some("Core Router".to_string()),
+            kind: SessionKind::Ssh,
+            hostname: "rtr1.example.net".to_string(),
+            port: 22,
+            username: "netops".to_string(),
+            started_at: 1_000,
+            connected_at: Some(1_001),
+            ended_at: Some(2_000),
+            end_state: Some(SessionState::Closed),
+            exit_status: Some(0),
+            message: Some("Connection closed. Exit status: 0".to_string()),
+            bytes_in: 10,
+            bytes_out: 20,
+            transport: None,
+        }
+    }
+
+    fn test_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("termai-history-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(HISTORY_FILE)
+    }
+
+    #[test]
+    fn exit_status_from_closed_message() {
+        assert_eq!(exit_status("Connection closed. Exit status: 0"), Some(0));
+        assert_eq!(exit_status("Connection closed. Exit status: 130"), Some(130));
+        assert_eq!(exit_status("Connection closed. Exit status: -1"), Some(-1));
+        assert_eq!(exit_status("Exit status: 1. Exit status: 2"), Some(2));
+        assert_eq!(exit_status("Connection closed."), None);
+        assert_eq!(exit_status("Connection closed. Exit status: "), None);
+        assert_eq!(exit_status("Connection closed. Exit status: unknown"), None);
+    }
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        assert!(HistoryFilter::default().matches(&entry("a")));
+        let blank = HistoryFilter { query: Some("   ".to_string()), ..Default::default() };
+        assert!(blank.matches(&entry("a")));
+    }
+
+    #[test]
+    fn query_matches_host_user_or_profile_name() {
+        let query = |text: &str| HistoryFilter { query: Some(text.to_string()), ..Default::default() };
+        assert!(query("RTR1").matches(&entry("a")));
+        assert!(query("netops").matches(&entry("a")));
+        assert!(query(" core router ").matches(&entry("a")));
+        assert!(!query("switch").matches(&entry("a")));
+        let mut no_profile = entry("a");
+        no_profile.profile_name = None;
+        assert!(!query("core").matches(&no_profile));
+    }
+
+    #[test]
+    fn kind_and_profile_filters() {
+        let kind = |kind| HistoryFilter { kind: Some(kind), ..Default::default() };
+        assert!(kind(SessionKind::Ssh).matches(&entry("a")));
+        assert!(!kind(SessionKind::Telnet).matches(&entry("a")));
+
+        let profile = |id: &str| HistoryFilter { profile_id: Some(id.to_string()), ..Default::default() };
+        assert!(profile("p-1").matches(&entry("a")));
+        assert!(!profile("p-2").matches(&entry("a")));
+        let mut ad_hoc = entry("a");
+        ad_hoc.profile_id = None;
+        assert!(!profile("p-1").matches(&ad_hoc));
+    }
+
+    #[test]
+    fn time_window_includes_since_and_excludes_until() {
+        let window = |since, until| HistoryFilter { since, until, ..Default::default() };
+        assert!(window(Some(1_000), None).matches(&entry("a")));
+        assert!(!window(Some(1_001), None).matches(&entry("a")));
+        assert!(window(None, Some(1_001)).matches(&entry("a")));
+        assert!(!window(None, Some(1_000)).matches(&entry("a")));
+        assert!(window(Some(500), Some(1_500)).matches(&entry("a")));
+    }
+
+    #[test]
+    fn failed_only_skips_closed_and_open_sessions() {
+        let failed_only = HistoryFilter { failed_only: true, ..Default::default() };
+        assert!(!failed_only.matches(&entry("a")));
+        let mut failed = entry("a");
+        failed.end_state = Some(SessionState::Failed);
+        assert!(failed_only.matches(&failed));
+        let mut open = entry("a");
+        open.end_state = None;
+        assert!(!failed_only.matches(&open));
+    }
+
+    #[test]
+    fn save_keeps_the_newest_entries() {
+        let path = test_path("trim");
+        let entries: Vec<HistoryEntry> = (0..MAX_ENTRIES + 5).map(|index| entry(&index.to_string())).collect();
+        save(&path, entries).unwrap();
+        let loaded = load(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(loaded.len(), MAX_ENTRIES);
+        assert_eq!(loaded.first().unwrap().id, "5");
+        assert_eq!(loaded.last().unwrap().id, (MAX_ENTRIES + 4).to_string());
+    }
+
+    #[test]
+    fn modify_round_trips_through_the_file() {
+        let path = test_path("modify");
+        assert!(load(&path).unwrap().is_empty());
+        modify(&path, |entries| entries.push(entry("a"))).unwrap();
+        modify(&path, |entries| entries[0].bytes_in = 99).unwrap();
+        let loaded = load(&path).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].bytes_in, 99);
+        assert_eq!(loaded[0].exit_status, Some(0));
+
+        fs::write(&path, r#"{"version": 99, "entries": []}"#).unwrap();
+        let error = load(&path).unwrap_err();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert!(error.contains("unsupported format version 99"), "{}", error);
+    }
+}
@@ -0,0 +1,345 @@
+// Shared plumbing for the profile importers: merging imported hosts into the
+// profile store (or only previewing the merge) and the report sent back.
+// Inventories are synced instead: profiles that came from the same source but
+// are gone from it are removed.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::AppHandle;
+
+use crate::profiles::{self, AuthMethod, Profile};
+
+// --- Report ---
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedEntry {
+    pub name: String,
+    pub reason: String,
+}
+
+// A directive or setting the importer does not carry over, counted per kind
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsupportedItem {
+    pub directive: String,
+    pub location: String, // Where it was first seen, e.g. "/home/me/.ssh/config:12"
+    pub count: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct UnsupportedLog(pub(crate) Vec<UnsupportedItem>);
+
+impl UnsupportedLog {
+    pub fn note(&mut self, directive: &str, location: String) {
+        match self.0.iter_mut().find(|item| item.directive.eq_ignore_ascii_case(directive)) {
+            Some(item) => item.count += 1,
+            None => self.0.push(UnsupportedItem { directive: directive.to_string(), location, count: 1 }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportReport {
+    pub source: String,
+    pub dry_run: bool, // Nothing was saved; the lists show what an import would do
+    pub created: Vec<Profile>,
+    pub updated: Vec<Profile>,
+    pub unchanged: Vec<String>, // Profile names
+    pub removed: Vec<Profile>, // Only when syncing
+    pub skipped: Vec<SkippedEntry>,
+    pub unsupported: Vec<UnsupportedItem>,
+}
+
+// --- Merging ---
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// Folds imported profiles into `profiles`. A profile is only ever updated by
+// the source that created it; a name already used by a hand-made profile or
+// another source's is reported as a conflict. Imported fields replace the
+// stored ones; everything a source cannot express is kept (see `keep_local`).
+fn merge(profiles: &mut Vec<Profile>, report: &mut ImportReport, candidates: Vec<Profile>, remove_missing: bool) {
+    let now = now();
+    let mut seen: Vec<String> = Vec::new();
+    for candidate in candidates {
+        let name = candidate.name.clone();
+        let mut candidate = match profiles::normalize(candidate) {
+            Ok(candidate) => candidate,
+            Err(reason) => {
+                report.skipped.push(SkippedEntry { name, reason });
+                continue;
+            }
+        };
+        if seen.contains(&candidate.name) {
+            report.skipped.push(SkippedEntry { name, reason: "Duplicate name in this import".to_string() });
+            continue;
+        }
+        seen.push(candidate.name.clone());
+        candidate.source = Some(report.source.clone());
+
+        let source = report.source.as_str();
+        match profiles.iter_mut().find(|existing| existing.name == candidate.name && existing.source.as_deref() == Some(source)) {
+            Some(existing) => {
+                keep_local(&mut candidate, existing);
+                if *existing == candidate {
+                    report.unchanged.push(candidate.name);
+                } else {
+                    candidate.updated_at = now;
+                    *existing = candidate.clone();
+                    report.updated.push(candidate);
+                }
+            }
+            None => {
+                if let Some(other) = profiles.iter().find(|existing| existing.name == candidate.name) {
+                    let reason = match &other.source {
+                        Some(other) => format!("Conflicts with a profile of the same name imported from {}", other),
+                        None => "Conflicts with an existing profile of the same name".to_string(),
+                    };
+                    report.skipped.push(SkippedEntry { name, reason });
+                    continue;
+                }
+                candidate.id = uuid::Uuid::new_v4().to_string();
+                candidate.created_at = now;
+                candidate.updated_at = now;
+                profiles.push(candidate.clone());
+                report.created.push(candidate);
+            }
+        }
+    }
+
+    if remove_missing {
+        // Hosts skipped this time keep their profile rather than being treated as gone
+        let kept = |profile: &Profile| {
+            profile.source.as_deref() != Some(report.source.as_str())
+                || seen.contains(&profile.name)
+                || report.skipped.iter().any(|entry| entry.name == profile.name)
+        };
+        let (keep, removed): (Vec<Profile>, Vec<Profile>) = profiles.drain(..).partition(|profile| kept(profile));
+        *profiles = keep;
+        report.removed = removed;
+    }
+}
+
+// Carries over what was set in TermAI rather than by the source: connection
+// settings, hand-added tags, and the vault secrets that still fit the imported
+// auth method (also per jump host, matched by address and user)
+fn keep_local(candidate: &mut Profile, existing: &Profile) {
+    candidate.id = existing.id.clone();
+    candidate.created_at = existing.created_at;
+    candidate.updated_at = existing.updated_at;
+    if candidate.auth_method == AuthMethod::Password {
+        candidate.password_secret_id = existing.password_secret_id.clone();
+    }
+    if candidate.auth_method == AuthMethod::Key && candidate.private_key_path == existing.private_key_path {
+        candidate.passphrase_secret_id = existing.passphrase_secret_id.clone();
+    }
+    if candidate.post_login_commands.is_empty() {
+        candidate.post_login_commands = existing.post_login_commands.clone();
+    }
+    candidate.algorithms = existing.algorithms.clone();
+    candidate.reconnect = existing.reconnect.clone();
+    candidate.keepalive = existing.keepalive.clone();
+    candidate.timeout_secs = existing.timeout_secs;
+    // Tags the source no longer lists stay too; it cannot tell them from hand-added ones
+    for tag in &existing.tags {
+        if !candidate.tags.contains(tag) {
+            candidate.tags.push(tag.clone());
+        }
+    }
+    for hop in &mut candidate.jump_hosts {
+        let Some(old) = existing
+            .jump_hosts
+            .iter()
+            .find(|old| old.hostname == hop.hostname && old.port == hop.port && old.username == hop.username)
+        else {
+            continue;
+        };
+        hop.password_secret_id = old.password_secret_id.clone();
+        if hop.private_key_path == old.private_key_path {
+            hop.passphrase_secret_id = old.passphrase_secret_id.clone();
+        }
+        hop.algorithms = old.algorithms.clone();
+    }
+}
+
+// Merges the importer's output into the profile store, or with `dry_run`
+// only reports what would change
+pub fn apply(
+    app_handle: &AppHandle,
+    source: String,
+    candidates: Vec<Profile>,
+    skipped: Vec<SkippedEntry>,
+    unsupported: UnsupportedLog,
+    dry_run: bool,
+) -> Result<ImportReport, String> {
+    run(app_handle, source, candidates, skipped, unsupported, dry_run, false)
+}
+
+// Like `apply`, and also removes profiles imported from `source` earlier that
+// it no longer lists
+pub fn sync(
+    app_handle: &AppHandle,
+    source: String,
+    candidates: Vec<Profile>,
+    skipped: Vec<SkippedEntry>,
+    unsupported: UnsupportedLog,
+    dry_run: bool,
+) -> Result<ImportReport, String> {
+    run(app_handle, source, candidates, skipped, unsupported, dry_run, true)
+}
+
+fn new_report(source: String, dry_run: bool, skipped: Vec<SkippedEntry>, unsupported: UnsupportedLog) -> ImportReport {
+    ImportReport {
+        source,
+        dry_run,
+        created: Vec::new(),
+        updated: Vec::new(),
+        unchanged: Vec::new(),
+        removed: Vec::new(),
+        skipped,
+        unsupported: unsupported.0,
+    }
+}
+
+// The dry-run report for merging `candidates` into `profiles`
+pub(crate) fn preview(
+    mut profiles: Vec<Profile>,
+    source: String,
+    candidates: Vec<Profile>,
+    skipped: Vec<SkippedEntry>,
+    unsupported: UnsupportedLog,
+    remove_missing: bool,
+) -> ImportReport {
+    let mut report = new_report(source, true, skipped, unsupported);
+    merge(&mut profiles, &mut report, candidates, remove_missing);
+    report
+}
+
+fn run(
+    app_handle: &AppHandle,
+    source: String,
+    candidates: Vec<Profile>,
+    skipped: Vec<SkippedEntry>,
+    unsupported: UnsupportedLog,
+    dry_run: bool,
+    remove_missing: bool,
+) -> Result<ImportReport, String> {
+    let report = if dry_run {
+        preview(profiles::list(app_handle)?, source, candidates, skipped, unsupported, remove_missing)
+    } else {
+        let mut report = new_report(source, false, skipped, unsupported);
+        profiles::modify(app_handle, |profiles| {
+            merge(profiles, &mut report, candidates, remove_missing);
+            Ok(())
+        })?;
+        report
+    };
+    println!(
+        "Import from {}{}: {} created, {} updated, {} unchanged, {} removed, {} skipped",
+        report.source,
+        if dry_run { " (dry run)" } else { "" },
+        report.created.len(),
+        report.updated.len(),
+        report.unchanged.len(),
+        report.removed.len(),
+        report.skipped.len()
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keepalive::KeepaliveConfig;
+    use crate::reconnect::ReconnectPolicy;
+
+    fn report(source: &str) -> ImportReport {
+        new_report(source.to_string(), false, Vec::new(), UnsupportedLog::default())
+    }
+
+    fn imported(json: serde_json::Value) -> Profile {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn router() -> Profile {
+        imported(serde_json::json!({
+            "name": "core-rtr",
+            "hostname": "10.0.0.1",
+            "port": 22,
+            "username": "admin",
+            "tags": ["ssh_config"],
+            "jumpHosts": [{ "hostname": "bastion", "port": 22, "username": "jump" }],
+        }))
+    }
+
+    #[test]
+    fn resync_keeps_settings_made_in_termai() {
+        let mut profiles = Vec::new();
+        merge(&mut profiles, &mut report("ssh_config"), vec![router()], false);
+        assert_eq!(profiles.len(), 1);
+        let id = profiles[0].id.clone();
+
+        // Settings the user changed after the first import
+        let edited = &mut profiles[0];
+        edited.algorithms.cipher = vec!["aes128-cbc".to_string()];
+        edited.algorithms.kex = vec!["diffie-hellman-group14-sha1".to_string()];
+        edited.reconnect = Some(ReconnectPolicy { max_attempts: 5, initial_delay_ms: 500, max_delay_ms: 10_000, replay_commands: vec!["terminal length 0".to_string()] });
+        edited.keepalive = Some(KeepaliveConfig { interval_secs: 10, max_missed: 6 });
+        edited.timeout_secs = Some(45);
+        edited.tags.push("core".to_string());
+        edited.jump_hosts[0].password_secret_id = Some("bastion-password".to_string());
+        let edited = edited.clone();
+
+        // The source changes the address and nothing else
+        let mut changed = router();
+        changed.hostname = "10.0.0.2".to_string();
+        let mut sync = report("ssh_config");
+        merge(&mut profiles, &mut sync, vec![changed], false);
+
+        assert_eq!(sync.updated.len(), 1);
+        let profile = &profiles[0];
+        assert_eq!(profile.id, id);
+        assert_eq!(profile.hostname, "10.0.0.2");
+        assert_eq!(profile.algorithms, edited.algorithms);
+        assert_eq!(profile.reconnect, edited.reconnect);
+        assert_eq!(profile.keepalive, edited.keepalive);
+        assert_eq!(profile.timeout_secs, Some(45));
+        assert_eq!(profile.tags, vec!["ssh_config", "core"]);
+        assert_eq!(profile.jump_hosts[0].password_secret_id.as_deref(), Some("bastion-password"));
+
+        // Re-importing the same source again changes nothing
+        let mut again = report("ssh_config");
+        let mut changed = router();
+        changed.hostname = "10.0.0.2".to_string();
+        merge(&mut profiles, &mut again, vec![changed], false);
+        assert_eq!(again.unchanged, vec!["core-rtr"]);
+        assert!(again.updated.is_empty());
+    }
+
+    #[test]
+    fn jump_host_secrets_are_dropped_when_the_hop_changes() {
+        let mut profiles = Vec::new();
+        merge(&mut profiles, &mut report("ssh_config"), vec![router()], false);
+        profiles[0].jump_hosts[0].password_secret_id = Some("bastion-password".to_string());
+
+        let mut moved = router();
+        moved.jump_hosts[0].hostname = "bastion2".to_string();
+        merge(&mut profiles, &mut report("ssh_config"), vec![moved], false);
+        assert_eq!(profiles[0].jump_hosts[0].password_secret_id, None);
+    }
+
+    #[test]
+    fn name_owned_by_another_source_is_a_conflict() {
+        let mut profiles = Vec::new();
+        merge(&mut profiles, &mut report("ssh_config"), vec![router()], false);
+        let mut other = report("inventory");
+        merge(&mut profiles, &mut other, vec![router()], false);
+        assert!(other.created.is_empty());
+        assert_eq!(other.skipped.len(), 1);
+        assert!(other.skipped[0].reason.contains("ssh_config"), "{}", other.skipped[0].reason);
+    }
+}
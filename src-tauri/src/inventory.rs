@@ -0,0 +1,818 @@
+// Device import from Ansible inventories (INI, YAML or `ansible-inventory --list`
+// JSON, plus group_vars/ and host_vars/ next to the file) and CSV exports.
+// Groups become tags and the effective variables of each host are kept as
+// profile metadata. Importing is a sync: re-running it (or `resync_inventories`)
+// reports added, changed and removed devices.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_yaml::{Mapping, Value};
+use tauri::{command, AppHandle};
+
+use crate::import::{self, ImportReport, SkippedEntry, UnsupportedLog};
+use crate::keys::expand_home;
+use crate::local_shell::local_username;
+use crate::profiles::{self, AuthMethod, Profile, ProfileProtocol};
+
+// Source prefixes of inventory imports, so they can be found again for a re-sync
+const ANSIBLE_SOURCE: &str = "ansible";
+const CSV_SOURCE: &str = "csv";
+// Groups every host is in; not worth a tag
+const IMPLICIT_GROUPS: [&str; 2] = ["all", "ungrouped"];
+// Never copied into metadata; profiles keep secrets in the vault only
+const SECRET_VARS: [&str; 6] = [
+    "ansible_password",
+    "ansible_ssh_pass",
+    "ansible_ssh_password",
+    "ansible_become_password",
+    "ansible_become_pass",
+    "ansible_private_key_passphrase",
+];
+// Connection plugins that end up in an SSH shell
+const SSH_CONNECTIONS: [&str; 6] = ["ssh", "paramiko", "paramiko_ssh", "smart", "network_cli", "libssh"];
+
+type Vars = BTreeMap<String, String>;
+
+#[derive(Default)]
+struct Group {
+    hosts: Vec<String>,
+    children: Vec<String>,
+    vars: Vars,
+}
+
+#[derive(Default)]
+struct Inventory {
+    hosts: Vec<String>, // In the order first seen
+    host_vars: HashMap<String, Vars>,
+    groups: BTreeMap<String, Group>,
+}
+
+impl Inventory {
+    fn group(&mut self, name: &str) -> &mut Group {
+        self.groups.entry(name.to_string()).or_default()
+    }
+
+    fn add_host(&mut self, group: &str, host: &str, vars: Vars) {
+        if !self.hosts.iter().any(|known| known == host) {
+            self.hosts.push(host.to_string());
+        }
+        self.host_vars.entry(host.to_string()).or_default().extend(vars);
+        let group = self.group(group);
+        if !group.hosts.iter().any(|known| known == host) {
+            group.hosts.push(host.to_string());
+        }
+    }
+
+    fn add_child(&mut self, parent: &str, child: &str) {
+        self.group(child);
+        let parent = self.group(parent);
+        if !parent.children.iter().any(|known| known == child) {
+            parent.children.push(child.to_string());
+        }
+    }
+
+    // Direct parents; groups nobody lists as a child sit under "all"
+    fn parents(&self, group: &str) -> Vec<&str> {
+        let parents: Vec<&str> = self
+            .groups
+            .iter()
+            .filter(|(_, candidate)| candidate.children.iter().any(|child| child == group))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if parents.is_empty() && group != "all" {
+            vec!["all"]
+        } else {
+            parents
+        }
+    }
+
+    // Longest path from "all"; deeper groups override shallower ones, as in Ansible
+    fn depth(&self, group: &str, visiting: &mut Vec<String>) -> usize {
+        if group == "all" || visiting.iter().any(|seen| seen == group) {
+            return 0;
+        }
+        visiting.push(group.to_string());
+        let depth = self.parents(group).into_iter().map(|parent| self.depth(parent, visiting) + 1).max().unwrap_or(1);
+        visiting.pop();
+        depth
+    }
+
+    // Every group the host is in, directly or through a parent, including "all"
+    fn groups_of(&self, host: &str) -> Vec<String> {
+        let mut found: Vec<String> = Vec::new();
+        let mut pending: Vec<String> = self
+            .groups
+            .iter()
+            .filter(|(_, group)| group.hosts.iter().any(|known| known == host))
+            .map(|(name, _)| name.clone())
+            .collect();
+        pending.push("all".to_string());
+        while let Some(group) = pending.pop() {
+            if found.contains(&group) {
+                continue;
+            }
+            pending.extend(self.parents(&group).into_iter().map(str::to_string));
+            found.push(group);
+        }
+        found
+    }
+
+    // Group variables from shallow to deep (then by name), overridden by the host's own
+    fn effective_vars(&self, host: &str, groups: &[String]) -> Vars {
+        let mut ordered: Vec<(usize, &String)> =
+            groups.iter().map(|group| (self.depth(group, &mut Vec::new()), group)).collect();
+        ordered.sort();
+        let mut vars = Vars::new();
+        for (_, group) in ordered {
+            if let Some(group) = self.groups.get(group) {
+                vars.extend(group.vars.clone());
+            }
+        }
+        if let Some(host_vars) = self.host_vars.get(host) {
+            vars.extend(host_vars.clone());
+        }
+        vars
+    }
+}
+
+// --- Host patterns ---
+
+// Expands Ansible ranges such as "web[01:03].example.com" or "db-[a:c]",
+// with an optional stride ("[1:9:2]")
+fn expand_pattern(pattern: &str) -> Result<Vec<String>, String> {
+    let Some(open) = pattern.find('[') else {
+        return Ok(vec![pattern.to_string()]);
+    };
+    let close = pattern[open..].find(']').map(|offset| open + offset).ok_or_else(|| format!("Unclosed range in {}", pattern))?;
+    let (prefix, range, suffix) = (&pattern[..open], &pattern[open + 1..close], &pattern[close + 1..]);
+    let bounds: Vec<&str> = range.split(':').collect();
+    let (start, end, stride) = match bounds.as_slice() {
+        [start, end] => (*start, *end, 1),
+        [start, end, stride] => (*start, *end, stride.parse::<usize>().map_err(|_| format!("Invalid stride in {}", pattern))?),
+        _ => return Err(format!("Invalid range in {}", pattern)),
+    };
+    if stride == 0 {
+        return Err(format!("Invalid stride in {}", pattern));
+    }
+
+    let values: Vec<String> = match (start.parse::<u64>(), end.parse::<u64>()) {
+        (Ok(first), Ok(last)) if first <= last => {
+            // "01" keeps its width
+            let width = if start.starts_with('0') { start.len() } else { 0 };
+            (first..=last).step_by(stride).map(|value| format!("{:0width$}", value, width = width)).collect()
+        }
+        _ => match (start.as_bytes(), end.as_bytes()) {
+            ([first], [last]) if first.is_ascii_alphabetic() && first <= last => {
+                (*first..=*last).step_by(stride).map(|byte| (byte as char).to_string()).collect()
+            }
+            _ => return Err(format!("Invalid range in {}", pattern)),
+        },
+    };
+    let rests = expand_pattern(suffix)?;
+    let mut expanded = Vec::new();
+    for value in &values {
+        for rest in &rests {
+            expanded.push(format!("{}{}{}", prefix, value, rest));
+        }
+    }
+    Ok(expanded)
+}
+
+// --- INI inventories ---
+
+// Splits on whitespace outside quotes; quotes are removed
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut in_word = false;
+    for c in line.chars() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            // A word starting with # comments out the rest of the line
+            (None, '#') if !in_word => break,
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(current);
+    }
+    words
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if let Some(inner) = value.strip_prefix(quote).and_then(|value| value.strip_suffix(quote)) {
+            return inner.to_string();
+        }
+    }
+    value.to_string()
+}
+
+enum IniSection {
+    Hosts(String),
+    Vars(String),
+    Children(String),
+}
+
+fn parse_ini(text: &str, file: &Path, inventory: &mut Inventory, skipped: &mut Vec<SkippedEntry>) {
+    let mut section = IniSection::Hosts("ungrouped".to_string());
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            section = match header.split_once(':') {
+                Some((group, "vars")) => IniSection::Vars(group.to_string()),
+                Some((group, "children")) => IniSection::Children(group.to_string()),
+                _ => IniSection::Hosts(header.to_string()),
+            };
+            let (IniSection::Hosts(group) | IniSection::Vars(group) | IniSection::Children(group)) = &section;
+            inventory.group(group);
+            continue;
+        }
+        match &section {
+            IniSection::Hosts(group) => {
+                let words = split_words(line);
+                let Some((pattern, assignments)) = words.split_first() else {
+                    continue;
+                };
+                let mut vars: Vars = assignments
+                    .iter()
+                    .filter_map(|assignment| assignment.split_once('='))
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect();
+                // "host:2222" sets the port
+                let pattern = match pattern.rsplit_once(':') {
+                    Some((host, port)) if !host.contains(':') && port.parse::<u16>().is_ok() => {
+                        vars.entry("ansible_port".to_string()).or_insert_with(|| port.to_string());
+                        host
+                    }
+                    _ => pattern.as_str(),
+                };
+                match expand_pattern(pattern) {
+                    Ok(hosts) => {
+                        for host in hosts {
+                            inventory.add_host(group, &host, vars.clone());
+                        }
+                    }
+                    Err(reason) => skipped.push(SkippedEntry {
+                        name: pattern.to_string(),
+                        reason: format!("{}:{}: {}", file.display(), index + 1, reason),
+                    }),
+                }
+            }
+            IniSection::Vars(group) => {
+                if let Some((key, value)) = line.split_once('=') {
+                    inventory.group(group).vars.insert(key.trim().to_string(), unquote(value));
+                }
+            }
+            IniSection::Children(group) => {
+                if let Some(child) = split_words(line).first() {
+                    inventory.add_child(group, child);
+                }
+            }
+        }
+    }
+}
+
+// --- YAML inventories ---
+
+// Variables are kept as text; lists and mappings as JSON
+fn yaml_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        Value::Number(number) => Some(number.to_string()),
+        // `!vault` values only Ansible can decrypt
+        Value::Tagged(_) => None,
+        other => serde_json::to_string(other).ok(),
+    }
+}
+
+fn yaml_vars(value: &Value, location: &str, unsupported: &mut UnsupportedLog) -> Vars {
+    let mut vars = Vars::new();
+    let Value::Mapping(mapping) = value else {
+        return vars;
+    };
+    for (key, value) in mapping {
+        let Some(key) = key.as_str() else {
+            continue;
+        };
+        if let Value::Tagged(tagged) = value {
+            unsupported.note(&format!("{} ({})", key, tagged.tag), location.to_string());
+            continue;
+        }
+        if let Some(text) = yaml_text(value) {
+            vars.insert(key.to_string(), text);
+        }
+    }
+    vars
+}
+
+fn parse_yaml_group(
+    name: &str,
+    value: &Value,
+    location: &str,
+    inventory: &mut Inventory,
+    skipped: &mut Vec<SkippedEntry>,
+    unsupported: &mut UnsupportedLog,
+) {
+    inventory.group(name);
+    if let Some(Value::Mapping(hosts)) = value.get("hosts") {
+        for (pattern, host_vars) in hosts {
+            let Some(pattern) = pattern.as_str() else {
+                continue;
+            };
+            let vars = yaml_vars(host_vars, location, unsupported);
+            match expand_pattern(pattern) {
+                Ok(hosts) => {
+                    for host in hosts {
+                        inventory.add_host(name, &host, vars.clone());
+                    }
+                }
+                Err(reason) => skipped.push(SkippedEntry { name: pattern.to_string(), reason }),
+            }
+        }
+    }
+    if let Some(vars) = value.get("vars") {
+        let vars = yaml_vars(vars, location, unsupported);
+        inventory.group(name).vars.extend(vars);
+    }
+    if let Some(Value::Mapping(children)) = value.get("children") {
+        for (child, child_value) in children {
+            if let Some(child) = child.as_str() {
+                inventory.add_child(name, child);
+                parse_yaml_group(child, child_value, location, inventory, skipped, unsupported);
+            }
+        }
+    }
+}
+
+// `ansible-inventory --list` output: groups list their hosts and children by
+// name, and each host's variables sit under `_meta.hostvars`
+fn parse_inventory_list(groups: &Mapping, location: &str, inventory: &mut Inventory, unsupported: &mut UnsupportedLog) {
+    let names = |value: Option<&Value>| -> Vec<String> {
+        match value {
+            Some(Value::Sequence(names)) => names.iter().filter_map(|name| name.as_str().map(str::to_string)).collect(),
+            _ => Vec::new(),
+        }
+    };
+    for (name, value) in groups {
+        let Some(name) = name.as_str().filter(|name| *name != "_meta") else {
+            continue;
+        };
+        inventory.group(name);
+        for host in names(value.get("hosts")) {
+            inventory.add_host(name, &host, Vars::new());
+        }
+        for child in names(value.get("children")) {
+            inventory.add_child(name, &child);
+        }
+        if let Some(vars) = value.get("vars") {
+            let vars = yaml_vars(vars, location, unsupported);
+            inventory.group(name).vars.extend(vars);
+        }
+    }
+    if let Some(Value::Mapping(host_vars)) = groups.get("_meta").and_then(|meta| meta.get("hostvars")) {
+        for (host, vars) in host_vars {
+            let Some(host) = host.as_str() else {
+                continue;
+            };
+            let vars = yaml_vars(vars, location, unsupported);
+            if inventory.hosts.iter().any(|known| known == host) {
+                inventory.host_vars.entry(host.to_string()).or_default().extend(vars);
+            } else {
+                inventory.add_host("ungrouped", host, vars);
+            }
+        }
+    }
+}
+
+// YAML inventories, or JSON ones in either the YAML layout or that of `ansible-inventory --list`
+fn parse_yaml(
+    text: &str,
+    file: &Path,
+    inventory: &mut Inventory,
+    skipped: &mut Vec<SkippedEntry>,
+    unsupported: &mut UnsupportedLog,
+) -> Result<(), String> {
+    let document: Value = serde_yaml::from_str(text).map_err(|e| format!("Invalid YAML in {}: {}", file.display(), e))?;
+    let Value::Mapping(groups) = document else {
+        return Err(format!("{} is not an inventory (expected groups at the top level)", file.display()));
+    };
+    let location = file.display().to_string();
+    if groups.contains_key("_meta") {
+        parse_inventory_list(&groups, &location, inventory, unsupported);
+        return Ok(());
+    }
+    for (name, value) in &groups {
+        if let Some(name) = name.as_str() {
+            parse_yaml_group(name, value, &location, inventory, skipped, unsupported);
+        }
+    }
+    Ok(())
+}
+
+// --- group_vars/ and host_vars/ ---
+
+// `<dir>/<name>`, `<dir>/<name>.yml` / `.yaml`, or every YAML file in `<dir>/<name>/`
+fn vars_files(dir: &Path, name: &str) -> Vec<PathBuf> {
+    let base = dir.join(name);
+    if base.is_dir() {
+        let mut files: Vec<PathBuf> = fs::read_dir(&base)
+            .map(|entries| entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect())
+            .unwrap_or_default();
+        files.retain(|file| file.is_file());
+        files.sort();
+        return files;
+    }
+    ["", ".yml", ".yaml", ".json"]
+        .iter()
+        .map(|extension| dir.join(format!("{}{}", name, extension)))
+        .filter(|file| file.is_file())
+        .collect()
+}
+
+fn load_vars_files(dir: &Path, name: &str, unsupported: &mut UnsupportedLog) -> Vars {
+    let mut vars = Vars::new();
+    for file in vars_files(dir, name) {
+        let location = file.display().to_string();
+        let Ok(text) = fs::read_to_string(&file) else {
+            continue;
+        };
+        if text.starts_with("$ANSIBLE_VAULT") {
+            unsupported.note("Vault-encrypted vars file", location);
+            continue;
+        }
+        match serde_yaml::from_str::<Value>(&text) {
+            Ok(value) => vars.extend(yaml_vars(&value, &location, unsupported)),
+            Err(_) => unsupported.note("Unreadable vars file", location),
+        }
+    }
+    vars
+}
+
+// Files beside the inventory override the variables set inside it
+fn load_adjacent_vars(inventory: &mut Inventory, dir: &Path, unsupported: &mut UnsupportedLog) {
+    let group_dir = dir.join("group_vars");
+    if group_dir.is_dir() {
+        inventory.group("all");
+        let names: Vec<String> = inventory.groups.keys().cloned().collect();
+        for name in names {
+            let vars = load_vars_files(&group_dir, &name, unsupported);
+            inventory.group(&name).vars.extend(vars);
+        }
+    }
+    let host_dir = dir.join("host_vars");
+    if host_dir.is_dir() {
+        for host in inventory.hosts.clone() {
+            let vars = load_vars_files(&host_dir, &host, unsupported);
+            inventory.host_vars.entry(host).or_default().extend(vars);
+        }
+    }
+}
+
+// --- CSV ---
+
+// Columns with a meaning for the profile, mapped to the Ansible variable
+// carrying it; every other column becomes metadata as is
+fn csv_column(header: &str) -> Option<&'static str> {
+    Some(match header.to_lowercase().replace([' ', '-'], "_").as_str() {
+        "host" | "hostname" | "ip" | "ip_address" | "address" | "mgmt_ip" | "ansible_host" => "ansible_host",
+        "port" | "ansible_port" => "ansible_port",
+        "user" | "username" | "login" | "ansible_user" => "ansible_user",
+        "key" | "private_key" | "identity_file" | "ansible_ssh_private_key_file" => "ansible_ssh_private_key_file",
+        "protocol" | "ansible_connection" => "ansible_connection",
+        "os" | "platform" | "network_os" | "ansible_network_os" => "ansible_network_os",
+        _ => return None,
+    })
+}
+
+// Needs a "name" or a host column; "groups" lists groups separated by ; or |
+fn parse_csv(text: &str, file: &Path, inventory: &mut Inventory, skipped: &mut Vec<SkippedEntry>) -> Result<(), String> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).trim(csv::Trim::All).from_reader(text.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| format!("Invalid CSV in {}: {}", file.display(), e))?
+        .iter()
+        .map(str::to_string)
+        .collect();
+    let is_name = |header: &str| matches!(header.to_lowercase().as_str(), "name" | "device" | "inventory_hostname");
+    let is_groups = |header: &str| matches!(header.to_lowercase().as_str(), "group" | "groups");
+    if !headers.iter().any(|header| is_name(header) || csv_column(header) == Some("ansible_host")) {
+        return Err(format!("{} needs a name or host column", file.display()));
+    }
+
+    for (index, record) in reader.records().enumerate() {
+        let row = index + 2; // The header is line 1
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                skipped.push(SkippedEntry { name: format!("line {}", row), reason: e.to_string() });
+                continue;
+            }
+        };
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+        let mut name = None;
+        let mut groups = Vec::new();
+        let mut vars = Vars::new();
+        for (header, value) in headers.iter().zip(record.iter()) {
+            if value.is_empty() {
+                continue;
+            }
+            if is_name(header) {
+                name = Some(value.to_string());
+            } else if is_groups(header) {
+                groups.extend(value.split([';', '|']).map(str::trim).filter(|group| !group.is_empty()).map(str::to_string));
+            } else {
+                let key = csv_column(header).map(str::to_string).unwrap_or_else(|| header.clone());
+                vars.insert(key, value.to_string());
+            }
+        }
+        let Some(name) = name.or_else(|| vars.get("ansible_host").cloned()) else {
+            skipped.push(SkippedEntry { name: format!("line {}", row), reason: "No name or host".to_string() });
+            continue;
+        };
+        if groups.is_empty() {
+            groups.push("ungrouped".to_string());
+        }
+        for group in &groups {
+            inventory.add_host(group, &name, vars.clone());
+        }
+    }
+    Ok(())
+}
+
+// --- Profiles ---
+
+fn to_profile(host: &str, vars: &Vars, groups: &[String], tag: &str) -> Result<Profile, String> {
+    let var = |names: &[&str]| names.iter().find_map(|name| vars.get(*name)).map(|value| value.trim()).filter(|value| !value.is_empty());
+    for (name, value) in vars {
+        if value.contains("{{") && name.starts_with("ansible_") && !SECRET_VARS.contains(&name.as_str()) {
+            return Err(format!("{} is a Jinja template", name));
+        }
+    }
+
+    // Fully qualified names ("ansible.netcommon.network_cli") count by their last part
+    let connection = var(&["ansible_connection"]).map(|connection| connection.rsplit('.').next().unwrap_or(connection).to_lowercase());
+    let protocol = match connection.as_deref() {
+        None => ProfileProtocol::Ssh,
+        Some("telnet") => ProfileProtocol::Telnet,
+        Some(connection) if SSH_CONNECTIONS.contains(&connection) => ProfileProtocol::Ssh,
+        Some(connection) => return Err(format!("Uses the {} connection", connection)),
+    };
+    let port = match var(&["ansible_port", "ansible_ssh_port"]) {
+        Some(port) => port.parse().map_err(|_| format!("Invalid port {:?}", port))?,
+        None if protocol == ProfileProtocol::Telnet => 23,
+        None => 22,
+    };
+    let private_key_path = var(&["ansible_ssh_private_key_file", "ansible_private_key_file"]).map(str::to_string);
+    let has_password = ["ansible_password", "ansible_ssh_pass", "ansible_ssh_password"].iter().any(|name| vars.contains_key(*name));
+    let auth_method = match (&private_key_path, has_password) {
+        (Some(_), _) => AuthMethod::Key,
+        (None, true) => AuthMethod::Password,
+        (None, false) => AuthMethod::Agent,
+    };
+    let username = match var(&["ansible_user", "ansible_ssh_user"]) {
+        Some(user) => user.to_string(),
+        None if protocol == ProfileProtocol::Ssh => local_username(),
+        None => String::new(),
+    };
+
+    let mut tags = vec![tag.to_string()];
+    let mut group_tags: Vec<String> =
+        groups.iter().filter(|group| !IMPLICIT_GROUPS.contains(&group.as_str())).cloned().collect();
+    group_tags.sort();
+    tags.extend(group_tags);
+
+    Ok(Profile {
+        id: String::new(),
+        name: host.to_string(),
+        protocol,
+        hostname: var(&["ansible_host", "ansible_ssh_host"]).unwrap_or(host).to_string(),
+        port,
+        username,
+        auth_method,
+        private_key_path,
+        certificate_path: None,
+        password_secret_id: None,
+        passphrase_secret_id: None,
+        jump_hosts: Vec::new(),
+        tags,
+        metadata: vars.iter().filter(|(name, _)| !SECRET_VARS.contains(&name.as_str())).map(|(k, v)| (k.clone(), v.clone())).collect(),
+        post_login_commands: Vec::new(),
+        algorithms: Default::default(),
+        reconnect: None,
+        keepalive: None,
+        timeout_secs: None,
+        source: None,
+        created_at: 0,
+        updated_at: 0,
+    })
+}
+
+fn sync_inventory(app_handle: &AppHandle, path: &Path, dry_run: bool) -> Result<ImportReport, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let extension = path.extension().map(|extension| extension.to_string_lossy().to_lowercase()).unwrap_or_default();
+    let mut inventory = Inventory::default();
+    let mut skipped = Vec::new();
+    let mut unsupported = UnsupportedLog::default();
+
+    let tag = if extension == "csv" {
+        parse_csv(&text, path, &mut inventory, &mut skipped)?;
+        CSV_SOURCE
+    } else {
+        if matches!(extension.as_str(), "yml" | "yaml" | "json") {
+            parse_yaml(&text, path, &mut inventory, &mut skipped, &mut unsupported)?;
+        } else {
+            parse_ini(&text, path, &mut inventory, &mut skipped);
+        }
+        if let Some(dir) = path.parent() {
+            load_adjacent_vars(&mut inventory, dir, &mut unsupported);
+        }
+        ANSIBLE_SOURCE
+    };
+
+    let mut candidates = Vec::new();
+    for host in &inventory.hosts {
+        let groups = inventory.groups_of(host);
+        let vars = inventory.effective_vars(host, &groups);
+        for name in SECRET_VARS.iter().filter(|name| vars.contains_key(**name)) {
+            unsupported.note(&format!("{} (secrets are not imported)", name), format!("{} [{}]", path.display(), host));
+        }
+        match to_profile(host, &vars, &groups, tag) {
+            Ok(profile) => candidates.push(profile),
+            Err(reason) => skipped.push(SkippedEntry { name: host.clone(), reason }),
+        }
+    }
+
+    let source = format!("{}:{}", tag, path.display());
+    import::sync(app_handle, source, candidates, skipped, unsupported, dry_run)
+}
+
+// --- Tauri Commands ---
+
+// Imports an Ansible inventory (.ini or no extension, .yml/.yaml, or .json as
+// written by `ansible-inventory --list`) or a .csv file. Profiles an earlier import of the same file created but that it no
+// longer lists are removed. With `dryRun` nothing is saved.
+#[command]
+pub async fn import_inventory(app_handle: AppHandle, path: String, dry_run: Option<bool>) -> Result<ImportReport, String> {
+    let path = expand_home(path.trim());
+    println!("Importing inventory {}", path.display());
+    sync_inventory(&app_handle, &path, dry_run.unwrap_or(false))
+}
+
+// Re-reads every inventory profiles were imported from and reports what changed
+// in each. An inventory that cannot be read is reported, with its profiles kept.
+#[command]
+pub async fn resync_inventories(app_handle: AppHandle, dry_run: Option<bool>) -> Result<Vec<ImportReport>, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    let mut sources: Vec<String> = Vec::new();
+    for profile in profiles::list(&app_handle)? {
+        let Some(source) = profile.source else {
+            continue;
+        };
+        let is_inventory = [ANSIBLE_SOURCE, CSV_SOURCE]
+            .iter()
+            .any(|prefix| source.strip_prefix(prefix).is_some_and(|rest| rest.starts_with(':')));
+        if is_inventory && !sources.contains(&source) {
+            sources.push(source);
+        }
+    }
+
+    let mut reports = Vec::new();
+    for source in sources {
+        let path = PathBuf::from(source.split_once(':').map(|(_, path)| path).unwrap_or_default());
+        println!("Re-syncing inventory {}", path.display());
+        let report = sync_inventory(&app_handle, &path, dry_run).unwrap_or_else(|reason| ImportReport {
+            source: source.clone(),
+            dry_run,
+            created: Vec::new(),
+            updated: Vec::new(),
+            unchanged: Vec::new(),
+            removed: Vec::new(),
+            skipped: vec![SkippedEntry { name: path.display().to_string(), reason }],
+            unsupported: Vec::new(),
+        });
+        reports.push(report);
+    }
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ini(text: &str) -> Inventory {
+        let mut inventory = Inventory::default();
+        let mut skipped = Vec::new();
+        parse_ini(text, Path::new("hosts"), &mut inventory, &mut skipped);
+        assert!(skipped.is_empty());
+        inventory
+    }
+
+    fn vars_of(inventory: &Inventory, host: &str) -> Vars {
+        inventory.effective_vars(host, &inventory.groups_of(host))
+    }
+
+    #[test]
+    fn expands_numeric_ranges_with_padding_and_stride() {
+        assert_eq!(expand_pattern("web[01:03].lab").unwrap(), ["web01.lab", "web02.lab", "web03.lab"]);
+        assert_eq!(expand_pattern("sw[8:10]").unwrap(), ["sw8", "sw9", "sw10"]);
+        assert_eq!(expand_pattern("leaf[001:9:4]").unwrap(), ["leaf001", "leaf005", "leaf009"]);
+    }
+
+    #[test]
+    fn expands_alphabetic_and_nested_ranges() {
+        assert_eq!(expand_pattern("db-[a:c]").unwrap(), ["db-a", "db-b", "db-c"]);
+        assert_eq!(expand_pattern("r[1:2][a:b]").unwrap(), ["r1a", "r1b", "r2a", "r2b"]);
+        assert_eq!(expand_pattern("plain").unwrap(), ["plain"]);
+    }
+
+    #[test]
+    fn rejects_bad_ranges() {
+        assert!(expand_pattern("web[01:03").is_err());
+        assert!(expand_pattern("web[1:3:0]").is_err());
+        assert!(expand_pattern("web[3:1]").is_err());
+        assert!(expand_pattern("web[a:9]").is_err());
+    }
+
+    #[test]
+    fn ini_host_port_suffix() {
+        let inventory = ini("[switches]\nsw1:2222 ansible_user=admin\nsw2:2222 ansible_port=2200\nfe80::1\n");
+        assert_eq!(inventory.hosts, ["sw1", "sw2", "fe80::1"]);
+        assert_eq!(vars_of(&inventory, "sw1").get("ansible_port").map(String::as_str), Some("2222"));
+        assert_eq!(vars_of(&inventory, "sw1").get("ansible_user").map(String::as_str), Some("admin"));
+        // An explicit variable wins over the suffix
+        assert_eq!(vars_of(&inventory, "sw2").get("ansible_port").map(String::as_str), Some("2200"));
+        assert_eq!(vars_of(&inventory, "fe80::1").get("ansible_port"), None);
+    }
+
+    #[test]
+    fn effective_vars_precedence() {
+        let inventory = ini(
+            "[all:vars]\nansible_user=root\nsite=hq\nrole=any\n\
+             [network:children]\ncore\n\
+             [network:vars]\nansible_user=netops\nrole=network\n\
+             [core]\ncore1 role=spine\ncore2\n\
+             [core:vars]\nrole=core\n\
+             [other]\nsrv1\n",
+        );
+        let core1 = vars_of(&inventory, "core1");
+        assert_eq!(core1.get("role").map(String::as_str), Some("spine"));
+        assert_eq!(core1.get("ansible_user").map(String::as_str), Some("netops"));
+        assert_eq!(core1.get("site").map(String::as_str), Some("hq"));
+        assert_eq!(vars_of(&inventory, "core2").get("role").map(String::as_str), Some("core"));
+        let srv1 = vars_of(&inventory, "srv1");
+        assert_eq!(srv1.get("role").map(String::as_str), Some("any"));
+        assert_eq!(srv1.get("ansible_user").map(String::as_str), Some("root"));
+    }
+
+    #[test]
+    fn reads_ansible_inventory_list_json() {
+        let text = r#"{
+            "_meta": { "hostvars": {
+                "core1": { "ansible_host": "10.0.0.1", "ansible_port": 2222 },
+                "lonely": { "ansible_host": "10.0.0.9" }
+            } },
+            "all": { "children": ["ungrouped", "network"] },
+            "network": { "children": ["core"], "vars": { "ansible_user": "netops" } },
+            "core": { "hosts": ["core1", "core2"] }
+        }"#;
+        let mut inventory = Inventory::default();
+        let mut unsupported = UnsupportedLog::default();
+        parse_yaml(text, Path::new("inventory.json"), &mut inventory, &mut Vec::new(), &mut unsupported).unwrap();
+
+        assert_eq!(inventory.hosts, ["core1", "core2", "lonely"]);
+        let mut groups = inventory.groups_of("core1");
+        groups.sort();
+        assert_eq!(groups, ["all", "core", "network"]);
+        let core1 = vars_of(&inventory, "core1");
+        assert_eq!(core1.get("ansible_host").map(String::as_str), Some("10.0.0.1"));
+        assert_eq!(core1.get("ansible_port").map(String::as_str), Some("2222"));
+        assert_eq!(core1.get("ansible_user").map(String::as_str), Some("netops"));
+        assert!(inventory.groups_of("lonely").contains(&"ungrouped".to_string()));
+    }
+}
@@ -0,0 +1,250 @@
+// Keepalives, dead-connection detection and round-trip latency for SSH sessions.
+// libssh2 sends keepalive@openssh.com but silently drops the replies, so the
+// probe is an `env` request on the shell channel instead: servers must answer
+// it (usually with a refusal), which both keeps NAT state alive and tells us
+// the connection still works and how long the round trip took.
+
+use std::time::{Duration, Instant};
+
+use ssh2::{Channel, ErrorCode};
+use tauri::AppHandle;
+
+use crate::session::{emit_event, SessionMap};
+use crate::ssh_client::LIBSSH2_ERROR_EAGAIN;
+
+// The server answered the probe with SSH_MSG_CHANNEL_FAILURE
+const LIBSSH2_ERROR_CHANNEL_REQUEST_DENIED: i32 = -22;
+// Variable named in the probe; servers ignore it
+const PROBE_VARIABLE: &str = "TERMAI_KEEPALIVE";
+
+fn default_interval_secs() -> u64 {
+    30
+}
+
+fn default_max_missed() -> u32 {
+    3
+}
+
+// Sent with `ssh_connect`. Without one the defaults apply (probe every 30s,
+// give up after 3 unanswered intervals); an interval of 0 turns probing off.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeepaliveConfig {
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    #[serde(default = "default_max_missed")]
+    pub max_missed: u32,
+}
+
+impl Default for KeepaliveConfig {
+    fn default() -> Self {
+        KeepaliveConfig { interval_secs: default_interval_secs(), max_missed: default_max_missed() }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyPayload {
+    pub session_id: String,
+    pub latency_ms: u64,
+}
+
+// Probe state for one shell channel, driven from the session's I/O loop
+pub struct Keepalive {
+    interval: Duration,
+    timeout: Duration, // How long a probe may go unanswered before the connection counts as dead
+    last_probe: Instant,
+    pending: Option<Instant>, // Start of the probe in flight
+}
+
+impl Keepalive {
+    pub fn new(config: &KeepaliveConfig) -> Option<Self> {
+        Self::starting_at(config, Instant::now())
+    }
+
+    fn starting_at(config: &KeepaliveConfig, now: Instant) -> Option<Self> {
+        if config.interval_secs == 0 {
+            return None;
+        }
+        let interval = Duration::from_secs(config.interval_secs);
+        Some(Keepalive {
+            interval,
+            timeout: interval * config.max_missed.max(1),
+            last_probe: now,
+            pending: None,
+        })
+    }
+
+    // Starts over on a new channel (after a reconnect)
+    pub fn reset(&mut self) {
+        self.last_probe = Instant::now();
+        self.pending = None;
+    }
+
+    // Sends a probe when one is due and checks for its answer without blocking.
+    // Returns the round-trip time once answered, or an error when the server
+    // stayed silent past the threshold or the transport failed.
+    pub fn poll(&mut self, channel: &mut Channel) -> Result<Option<Duration>, String> {
+        self.poll_at(Instant::now(), || channel.setenv(PROBE_VARIABLE, "1"))
+    }
+
+    // `poll` with the clock and the probe passed in
+    fn poll_at(&mut self, now: Instant, probe: impl FnOnce() -> Result<(), ssh2::Error>) -> Result<Option<Duration>, String> {
+        let started = match self.pending {
+            Some(started) => started,
+            None if now.saturating_duration_since(self.last_probe) >= self.interval => *self.pending.insert(now),
+            None => return Ok(None),
+        };
+        let waited = now.saturating_duration_since(started);
+        match probe() {
+            Err(e) if e.code() == ErrorCode::Session(LIBSSH2_ERROR_EAGAIN) => {
+                if waited >= self.timeout {
+                    Err(format!("No response from server for {}s; the connection appears to be dead", waited.as_secs()))
+                } else {
+                    Ok(None)
+                }
+            }
+            // Accepted or refused: either way the server answered
+            Ok(()) => Ok(Some(self.answered(now, waited))),
+            Err(e) if e.code() == ErrorCode::Session(LIBSSH2_ERROR_CHANNEL_REQUEST_DENIED) => Ok(Some(self.answered(now, waited))),
+            Err(e) => Err(format!("Keepalive failed: {}", e)),
+        }
+    }
+
+    fn answered(&mut self, now: Instant, waited: Duration) -> Duration {
+        self.pending = None;
+        self.last_probe = now;
+        waited
+    }
+}
+
+// Stores the latest round trip on the session (see `list_sessions`) and tells the UI
+pub fn record_latency(app_handle: &AppHandle, sessions: &SessionMap, session_id: &str, rtt: Duration) {
+    let latency_ms = store_latency(sessions, session_id, rtt);
+    emit_event(app_handle, "session-latency", LatencyPayload { session_id: session_id.to_string(), latency_ms });
+}
+
+fn store_latency(sessions: &SessionMap, session_id: &str, rtt: Duration) -> u64 {
+    let latency_ms = rtt.as_millis() as u64;
+    if let Ok(mut guard) = sessions.lock() {
+        if let Some(handle) = guard.get_mut(session_id) {
+            handle.info.latency_ms = Some(latency_ms);
+        }
+    }
+    latency_ms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use crate::session::{SessionHandle, SessionInfo, SessionKind};
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    fn keepalive(interval_secs: u64, max_missed: u32, start: Instant) -> Keepalive {
+        Keepalive::starting_at(&KeepaliveConfig { interval_secs, max_missed }, start).unwrap()
+    }
+
+    // What libssh2 returns while the server has not answered yet
+    fn waiting() -> Result<(), ssh2::Error> {
+        Err(ssh2::Error::new(ErrorCode::Session(LIBSSH2_ERROR_EAGAIN), "would block"))
+    }
+
+    fn not_due() -> Result<(), ssh2::Error> {
+        panic!("probe sent before the interval elapsed")
+    }
+
+    #[test]
+    fn interval_zero_disables_probing() {
+        assert!(Keepalive::new(&KeepaliveConfig { interval_secs: 0, max_missed: 3 }).is_none());
+        assert!(Keepalive::new(&KeepaliveConfig::default()).is_some());
+    }
+
+    #[test]
+    fn probes_once_the_interval_has_passed() {
+        let start = Instant::now();
+        let mut keepalive = keepalive(30, 3, start);
+        assert_eq!(keepalive.poll_at(start + 29 * SECOND, not_due), Ok(None));
+        assert_eq!(keepalive.poll_at(start + 30 * SECOND, waiting), Ok(None));
+        assert_eq!(keepalive.pending, Some(start + 30 * SECOND));
+    }
+
+    #[test]
+    fn dead_after_interval_times_max_missed() {
+        let start = Instant::now();
+        let mut keepalive = keepalive(10, 3, start);
+        let sent = start + 10 * SECOND;
+        assert_eq!(keepalive.poll_at(sent, waiting), Ok(None));
+        // Unanswered intervals are counted from the probe, not from the last answer
+        assert_eq!(keepalive.poll_at(sent + 29 * SECOND, waiting), Ok(None));
+        let error = keepalive.poll_at(sent + 30 * SECOND, waiting).unwrap_err();
+        assert!(error.contains("No response from server for 30s"), "{}", error);
+    }
+
+    #[test]
+    fn max_missed_zero_allows_one_interval() {
+        let start = Instant::now();
+        let mut keepalive = keepalive(10, 0, start);
+        let sent = start + 10 * SECOND;
+        assert_eq!(keepalive.poll_at(sent, waiting), Ok(None));
+        assert_eq!(keepalive.poll_at(sent + 9 * SECOND, waiting), Ok(None));
+        assert!(keepalive.poll_at(sent + 10 * SECOND, waiting).is_err());
+    }
+
+    #[test]
+    fn answer_reports_round_trip_and_restarts_interval() {
+        let start = Instant::now();
+        let mut keepalive = keepalive(30, 3, start);
+        let sent = start + 30 * SECOND;
+        let answered = sent + Duration::from_millis(250);
+        assert_eq!(keepalive.poll_at(sent, waiting), Ok(None));
+        assert_eq!(keepalive.poll_at(answered, || Ok(())), Ok(Some(Duration::from_millis(250))));
+        assert_eq!(keepalive.pending, None);
+        assert_eq!(keepalive.poll_at(answered + 29 * SECOND, not_due), Ok(None));
+        assert_eq!(keepalive.poll_at(answered + 30 * SECOND, waiting), Ok(None));
+    }
+
+    #[test]
+    fn refusal_counts_as_an_answer() {
+        let start = Instant::now();
+        let mut keepalive = keepalive(30, 3, start);
+        let refused = || Err(ssh2::Error::new(ErrorCode::Session(LIBSSH2_ERROR_CHANNEL_REQUEST_DENIED), "denied"));
+        assert_eq!(keepalive.poll_at(start + 30 * SECOND, refused), Ok(Some(Duration::ZERO)));
+    }
+
+    #[test]
+    fn transport_errors_fail_at_once() {
+        let start = Instant::now();
+        let mut keepalive = keepalive(30, 3, start);
+        let broken = || Err(ssh2::Error::new(ErrorCode::Session(-7), "Unable to send channel request"));
+        let error = keepalive.poll_at(start + 30 * SECOND, broken).unwrap_err();
+        assert!(error.starts_with("Keepalive failed"), "{}", error);
+    }
+
+    #[test]
+    fn latency_is_stored_on_the_session() {
+        let (command_sender, _) = tokio::sync::mpsc::channel(1);
+        let info = SessionInfo {
+            id: "s1".to_string(),
+            kind: SessionKind::Ssh,
+            hostname: "core-sw1".to_string(),
+            port: 22,
+            username: "admin".to_string(),
+            connected_at: 0,
+            latency_ms: None,
+        };
+        let sessions: SessionMap = Arc::new(Mutex::new(HashMap::from([(
+            "s1".to_string(),
+            SessionHandle { command_sender, ssh: None, forwards: HashMap::new(), info },
+        )])));
+
+        assert_eq!(store_latency(&sessions, "s1", Duration::from_micros(42_700)), 42);
+        assert_eq!(sessions.lock().unwrap()["s1"].info.latency_ms, Some(42));
+        // Sessions closed in the meantime are skipped
+        assert_eq!(store_latency(&sessions, "gone", Duration::from_millis(7)), 7);
+        assert_eq!(sessions.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,332 @@
+// Keyboard-interactive authentication (OTP, RADIUS/TACACS+ challenges, ...).
+// Each round of server prompts is sent to the frontend as `ssh-auth-prompt`
+// and the connection waits for `respond_auth_prompt`.
+
+use std::borrow::Cow;
+use std::ffi::{c_char, c_int, c_uint, c_void, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+use std::{ptr, slice};
+
+use libssh2_sys as raw;
+use ssh2::{ErrorCode, Prompt, Session};
+use tauri::{command, AppHandle, State};
+use zeroize::Zeroizing;
+
+use crate::prompts::PromptRegistry;
+use crate::AppState;
+
+// How long a connection waits for the user to answer a challenge (OTP apps can be slow to find)
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(180);
+// libssh2's "invalid argument" code
+const LIBSSH2_ERROR_INVAL: c_int = -34;
+
+// Connections waiting on answers to server prompts; None means the user cancelled
+pub type AuthPrompts = PromptRegistry<Option<Vec<String>>>;
+
+// --- Event Payloads ---
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthPromptField {
+    pub text: String,
+    pub echo: bool, // False for secrets (passwords, OTPs) the UI should mask
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthPromptPayload {
+    pub prompt_id: String,
+    pub hostname: String,
+    pub username: String,
+    pub instructions: String,
+    pub prompts: Vec<AuthPromptField>,
+}
+
+// --- Prompter ---
+
+// Answers one keyboard-interactive exchange. The saved password (if any) is
+// used once for a lone masked "Password:" prompt; everything else is asked.
+pub struct InteractivePrompter<'a> {
+    pub app_handle: &'a AppHandle,
+    pub prompts: &'a AuthPrompts,
+    pub hostname: &'a str,
+    pub password: Option<&'a str>,
+    pub cancelled: bool,
+}
+
+impl InteractivePrompter<'_> {
+    fn is_password_prompt(prompts: &[Prompt<'_>]) -> bool {
+        matches!(prompts, [prompt] if !prompt.echo && prompt.text.to_lowercase().contains("password"))
+    }
+
+    // One answer per prompt for a round of server prompts
+    fn answer(&mut self, username: &str, instructions: &str, prompts: &[Prompt<'_>]) -> Vec<Zeroizing<String>> {
+        // Servers send empty rounds (e.g. a banner) that expect an empty answer
+        if prompts.is_empty() || self.cancelled {
+            return Vec::new();
+        }
+        if Self::is_password_prompt(prompts) {
+            if let Some(password) = self.password.take() {
+                return vec![Zeroizing::new(password.to_string())];
+            }
+        }
+
+        let prompt_id = uuid::Uuid::new_v4().to_string();
+        let payload = AuthPromptPayload {
+            prompt_id: prompt_id.clone(),
+            hostname: self.hostname.to_string(),
+            username: username.to_string(),
+            instructions: instructions.to_string(),
+            prompts: prompts
+                .iter()
+                .map(|prompt| AuthPromptField { text: prompt.text.to_string(), echo: prompt.echo })
+                .collect(),
+        };
+        println!("Server {} asks {} keyboard-interactive question(s)", self.hostname, prompts.len());
+
+        match self.prompts.ask(self.app_handle, "ssh-auth-prompt", &prompt_id, payload, PROMPT_TIMEOUT).flatten() {
+            Some(responses) => {
+                let mut responses: Vec<Zeroizing<String>> = responses.into_iter().map(Zeroizing::new).collect();
+                responses.resize_with(prompts.len(), Default::default);
+                responses
+            }
+            None => {
+                self.cancelled = true;
+                Vec::new()
+            }
+        }
+    }
+}
+
+// --- libssh2 Callback ---
+// ssh2's `userauth_keyboard_interactive` takes the answers as plain Strings and
+// drops them without wiping, so the exchange is driven from here: answers stay
+// in `Zeroizing` buffers until they are copied into memory libssh2 owns.
+
+// Runs keyboard-interactive authentication on a blocking session
+pub fn authenticate(session: &Session, username: &str, prompter: &mut InteractivePrompter<'_>) -> Result<(), ssh2::Error> {
+    let username = CString::new(username)
+        .map_err(|_| ssh2::Error::new(ErrorCode::Session(LIBSSH2_ERROR_INVAL), "username contains a NUL byte"))?;
+    let rc = {
+        let mut guard = session.raw();
+        let raw_session: *mut raw::LIBSSH2_SESSION = &mut *guard;
+        // SAFETY: the session is locked for the whole exchange, and the prompter
+        // outlives the call; the abstract pointer is restored before unlocking.
+        unsafe {
+            let slot = raw::libssh2_session_abstract(raw_session);
+            let previous = *slot;
+            *slot = prompter as *mut InteractivePrompter<'_> as *mut c_void;
+            let rc = raw::libssh2_userauth_keyboard_interactive_ex(
+                raw_session,
+                username.as_ptr(),
+                username.as_bytes().len() as c_uint,
+                Some(respond),
+            );
+            *slot = previous;
+            rc
+        }
+    };
+    if rc < 0 {
+        return Err(ssh2::Error::last_session_error(session)
+            .unwrap_or_else(|| ssh2::Error::new(ErrorCode::Session(rc), "keyboard-interactive authentication failed")));
+    }
+    Ok(())
+}
+
+fn lossy_text(data: *const u8, len: usize) -> String {
+    if data.is_null() || len == 0 {
+        return String::new();
+    }
+    // SAFETY: libssh2 passes `len` readable bytes at `data`
+    String::from_utf8_lossy(unsafe { slice::from_raw_parts(data, len) }).into_owned()
+}
+
+// Number of prompts in a round, or None when the arguments cannot be used
+fn prompt_count(
+    num_prompts: c_int,
+    prompts: *const raw::LIBSSH2_USERAUTH_KBDINT_PROMPT,
+    responses: *mut raw::LIBSSH2_USERAUTH_KBDINT_RESPONSE,
+) -> Option<usize> {
+    match usize::try_from(num_prompts) {
+        Ok(0) => Some(0),
+        Ok(count) if !prompts.is_null() && !responses.is_null() => Some(count),
+        _ => None,
+    }
+}
+
+fn server_prompts(raw_prompts: &[raw::LIBSSH2_USERAUTH_KBDINT_PROMPT]) -> Vec<Prompt<'static>> {
+    raw_prompts
+        .iter()
+        .map(|prompt| Prompt { text: Cow::Owned(lossy_text(prompt.text, prompt.length)), echo: prompt.echo != 0 })
+        .collect()
+}
+
+// Copies each answer into its response slot as a malloc'd C string, which
+// libssh2 frees with free(3) once sent. Slots without an answer (cancelled, or
+// fewer answers than prompts) stay empty and are sent as ""; extra answers are dropped.
+fn fill_responses(responses: &mut [raw::LIBSSH2_USERAUTH_KBDINT_RESPONSE], answers: &[Zeroizing<String>]) {
+    for (response, answer) in responses.iter_mut().zip(answers) {
+        let copy = unsafe { libc::malloc(answer.len() + 1) } as *mut c_char;
+        if copy.is_null() {
+            continue;
+        }
+        // SAFETY: `copy` has room for the answer and its terminating NUL
+        unsafe {
+            ptr::copy_nonoverlapping(answer.as_ptr() as *const c_char, copy, answer.len());
+            *copy.add(answer.len()) = 0;
+        }
+        response.text = copy;
+        response.length = answer.len() as c_uint;
+    }
+}
+
+// Called by libssh2 for each round of prompts; `abstrakt` points at the prompter
+extern "C" fn respond(
+    username: *const c_char,
+    username_len: c_int,
+    instruction: *const c_char,
+    instruction_len: c_int,
+    num_prompts: c_int,
+    prompts: *const raw::LIBSSH2_USERAUTH_KBDINT_PROMPT,
+    responses: *mut raw::LIBSSH2_USERAUTH_KBDINT_RESPONSE,
+    abstrakt: *mut *mut c_void,
+) {
+    // Panics must not unwind into C; libssh2 then sends empty answers
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+        let Some(count) = prompt_count(num_prompts, prompts, responses) else {
+            return;
+        };
+        if abstrakt.is_null() {
+            return;
+        }
+        // SAFETY: `authenticate` stored the prompter there for this call
+        let prompter = unsafe { &mut *(*abstrakt as *mut InteractivePrompter) };
+        let raw_prompts = if count == 0 { &[][..] } else { unsafe { slice::from_raw_parts(prompts, count) } };
+
+        let answers = prompter.answer(
+            &lossy_text(username as *const u8, username_len.max(0) as usize),
+            &lossy_text(instruction as *const u8, instruction_len.max(0) as usize),
+            &server_prompts(raw_prompts),
+        );
+        if count == 0 {
+            return;
+        }
+        // SAFETY: libssh2 provides one zeroed response slot per prompt
+        fill_responses(unsafe { slice::from_raw_parts_mut(responses, count) }, &answers);
+    }));
+}
+
+// --- Tauri Commands ---
+
+// One response per prompt, in order; None cancels the login
+#[command]
+pub fn respond_auth_prompt(
+    state: State<'_, AppState>,
+    prompt_id: String,
+    responses: Option<Vec<String>>,
+) -> Result<(), String> {
+    state.auth_prompts.answer(&prompt_id, responses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    fn empty_slots(count: usize) -> Vec<raw::LIBSSH2_USERAUTH_KBDINT_RESPONSE> {
+        (0..count).map(|_| raw::LIBSSH2_USERAUTH_KBDINT_RESPONSE { text: ptr::null_mut(), length: 0 }).collect()
+    }
+
+    fn answers(values: &[&str]) -> Vec<Zeroizing<String>> {
+        values.iter().map(|value| Zeroizing::new(value.to_string())).collect()
+    }
+
+    // Reads back the slots and frees them the way libssh2 would
+    fn take(responses: Vec<raw::LIBSSH2_USERAUTH_KBDINT_RESPONSE>) -> Vec<Option<String>> {
+        responses
+            .into_iter()
+            .map(|response| {
+                if response.text.is_null() {
+                    assert_eq!(response.length, 0);
+                    return None;
+                }
+                let text = unsafe { CStr::from_ptr(response.text) }.to_str().unwrap().to_string();
+                assert_eq!(response.length as usize, text.len());
+                unsafe { libc::free(response.text as *mut c_void) };
+                Some(text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn each_answer_fills_its_slot() {
+        let mut responses = empty_slots(2);
+        fill_responses(&mut responses, &answers(&["123456", "pässword"]));
+        assert_eq!(take(responses), vec![Some("123456".to_string()), Some("pässword".to_string())]);
+    }
+
+    #[test]
+    fn missing_answers_leave_slots_empty() {
+        let mut responses = empty_slots(3);
+        fill_responses(&mut responses, &answers(&["only one"]));
+        assert_eq!(take(responses), vec![Some("only one".to_string()), None, None]);
+    }
+
+    #[test]
+    fn extra_answers_are_dropped() {
+        let mut responses = empty_slots(1);
+        fill_responses(&mut responses, &answers(&["first", "second"]));
+        assert_eq!(take(responses), vec![Some("first".to_string())]);
+    }
+
+    #[test]
+    fn cancelled_round_sends_empty_answers() {
+        // A cancelled prompter answers nothing
+        let mut responses = empty_slots(2);
+        fill_responses(&mut responses, &[]);
+        assert_eq!(take(responses), vec![None, None]);
+    }
+
+    #[test]
+    fn empty_answer_is_an_empty_string() {
+        let mut responses = empty_slots(1);
+        fill_responses(&mut responses, &answers(&[""]));
+        assert_eq!(take(responses), vec![Some(String::new())]);
+    }
+
+    #[test]
+    fn prompt_count_rejects_unusable_arguments() {
+        let prompt = raw::LIBSSH2_USERAUTH_KBDINT_PROMPT { text: ptr::null_mut(), length: 0, echo: 0 };
+        let mut response = raw::LIBSSH2_USERAUTH_KBDINT_RESPONSE { text: ptr::null_mut(), length: 0 };
+        let (prompts, responses) = (&prompt as *const _, &mut response as *mut _);
+        assert_eq!(prompt_count(1, prompts, responses), Some(1));
+        assert_eq!(prompt_count(0, ptr::null(), ptr::null_mut()), Some(0));
+        assert_eq!(prompt_count(-1, prompts, responses), None);
+        assert_eq!(prompt_count(1, ptr::null(), responses), None);
+        assert_eq!(prompt_count(1, prompts, ptr::null_mut()), None);
+    }
+
+    #[test]
+    fn prompt_text_handles_null_and_invalid_utf8() {
+        let mut otp = *b"OTP: ";
+        let mut invalid = [b'P', 0xFF, b':'];
+        let raw_prompts = [
+            raw::LIBSSH2_USERAUTH_KBDINT_PROMPT { text: otp.as_mut_ptr(), length: otp.len(), echo: 1 },
+            raw::LIBSSH2_USERAUTH_KBDINT_PROMPT { text: invalid.as_mut_ptr(), length: invalid.len(), echo: 0 },
+            raw::LIBSSH2_USERAUTH_KBDINT_PROMPT { text: ptr::null_mut(), length: 8, echo: 0 },
+            raw::LIBSSH2_USERAUTH_KBDINT_PROMPT { text: otp.as_mut_ptr(), length: 0, echo: 0 },
+        ];
+        let prompts: Vec<(String, bool)> =
+            server_prompts(&raw_prompts).into_iter().map(|prompt| (prompt.text.into_owned(), prompt.echo)).collect();
+        assert_eq!(
+            prompts,
+            vec![
+                ("OTP: ".to_string(), true),
+                ("P\u{FFFD}:".to_string(), false),
+                (String::new(), false),
+                (String::new(), false),
+            ]
+        );
+    }
+}
@@ -0,0 +1,470 @@
+// Host key verification against an app-managed known_hosts file.
+// Unknown or changed keys are shown to the user through the
+// `ssh-hostkey-prompt` event and the connection waits for their answer.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD};
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use ssh2::{CheckResult, HostKeyType, KnownHostFileKind, KnownHosts, Session};
+use tauri::{command, AppHandle, Manager, State};
+
+use crate::prompts::PromptRegistry;
+use crate::AppState;
+
+// How long a connection waits for the user to answer a host key prompt
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(120);
+const KNOWN_HOSTS_FILE: &str = "known_hosts";
+
+// Connections waiting on a host key decision
+pub type HostKeyPrompts = PromptRegistry<HostKeyDecision>;
+
+// --- Decisions & Errors ---
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HostKeyDecision {
+    AcceptOnce,   // Connect this time, remember nothing
+    AcceptAlways, // Connect and store the key in known_hosts
+    Reject,
+}
+
+#[derive(Debug, Clone)]
+pub enum HostKeyError {
+    // The stored key differs from the one the server presented and the user did not accept it
+    Mismatch { host: String, expected: Option<String>, presented: String },
+    // The key was unknown and the user rejected it (or did not answer in time)
+    Rejected { host: String, fingerprint: String },
+    // The server did not present a host key at all
+    Missing,
+    // known_hosts could not be read or written
+    Store(String),
+}
+
+impl fmt::Display for HostKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HostKeyError::Mismatch { host, expected, presented } => write!(
+                f,
+                "Host key mismatch for {}: server presented {} but known_hosts has {}. \
+                 The host may have been reinstalled, or someone may be intercepting the connection.",
+                host,
+                presented,
+                expected.as_deref().unwrap_or("a different key")
+            ),
+            HostKeyError::Rejected { host, fingerprint } => {
+                write!(f, "Host key for {} ({}) was not accepted.", host, fingerprint)
+            }
+            HostKeyError::Missing => write!(f, "Server did not present a host key."),
+            HostKeyError::Store(msg) => write!(f, "known_hosts error: {}", msg),
+        }
+    }
+}
+
+impl From<HostKeyError> for String {
+    fn from(e: HostKeyError) -> Self {
+        e.to_string()
+    }
+}
+
+// --- Event Payloads ---
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HostKeyStatus {
+    Unknown,
+    Changed,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostKeyPromptPayload {
+    pub prompt_id: String,
+    pub hostname: String,
+    pub port: u16,
+    pub key_type: String,
+    pub fingerprint: String, // "SHA256:..." as printed by OpenSSH
+    pub status: HostKeyStatus,
+    pub previous_fingerprint: Option<String>, // Only set for changed keys
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnownHostEntry {
+    pub host: String,
+    pub fingerprint: String,
+}
+
+// --- Helpers ---
+
+// Name used in known_hosts; OpenSSH writes non-default ports as "[host]:port"
+fn entry_name(hostname: &str, port: u16) -> String {
+    if port == 22 {
+        hostname.to_string()
+    } else {
+        format!("[{}]:{}", hostname, port)
+    }
+}
+
+fn key_type_name(key_type: HostKeyType) -> &'static str {
+    match key_type {
+        HostKeyType::Rsa => "ssh-rsa",
+        HostKeyType::Dss => "ssh-dss",
+        HostKeyType::Ecdsa256 => "ecdsa-sha2-nistp256",
+        HostKeyType::Ecdsa384 => "ecdsa-sha2-nistp384",
+        HostKeyType::Ecdsa521 => "ecdsa-sha2-nistp521",
+        HostKeyType::Ed25519 => "ssh-ed25519",
+        HostKeyType::Unknown => "unknown",
+    }
+}
+
+// OpenSSH-style SHA256 fingerprint of a raw key blob
+pub fn fingerprint(key: &[u8]) -> String {
+    format!("SHA256:{}", STANDARD_NO_PAD.encode(Sha256::digest(key)))
+}
+
+fn store_path(app_handle: &AppHandle) -> Result<PathBuf, HostKeyError> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| HostKeyError::Store(format!("Failed to locate app data directory: {}", e)))?;
+    fs::create_dir_all(&dir).map_err(|e| HostKeyError::Store(format!("Failed to create {}: {}", dir.display(), e)))?;
+    Ok(dir.join(KNOWN_HOSTS_FILE))
+}
+
+// Loads the app's known_hosts into libssh2's collection (missing file = empty)
+fn load(session: &Session, path: &Path) -> Result<KnownHosts, HostKeyError> {
+    let mut known_hosts = session
+        .known_hosts()
+        .map_err(|e| HostKeyError::Store(format!("Failed to initialise known hosts: {}", e)))?;
+    if path.exists() {
+        known_hosts
+            .read_file(path, KnownHostFileKind::OpenSSH)
+            .map_err(|e| HostKeyError::Store(format!("Failed to read {}: {}", path.display(), e)))?;
+    }
+    Ok(known_hosts)
+}
+
+fn save(known_hosts: &KnownHosts, path: &Path) -> Result<(), HostKeyError> {
+    known_hosts
+        .write_file(path, KnownHostFileKind::OpenSSH)
+        .map_err(|e| HostKeyError::Store(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+// Fingerprint of the entry currently stored for `name`, if any
+fn stored_fingerprint(known_hosts: &KnownHosts, name: &str) -> Option<String> {
+    known_hosts
+        .hosts()
+        .ok()?
+        .iter()
+        .find(|host| host.name() == Some(name))
+        .and_then(|host| STANDARD.decode(host.key()).ok())
+        .map(|key| fingerprint(&key))
+}
+
+fn remove_entries(known_hosts: &KnownHosts, name: &str) -> Result<(), HostKeyError> {
+    let hosts = known_hosts
+        .hosts()
+        .map_err(|e| HostKeyError::Store(format!("Failed to list known hosts: {}", e)))?;
+    for host in hosts.iter().filter(|host| host.name() == Some(name)) {
+        known_hosts
+            .remove(host)
+            .map_err(|e| HostKeyError::Store(format!("Failed to remove old key for {}: {}", name, e)))?;
+    }
+    Ok(())
+}
+
+// --- Verification ---
+
+// The key a server presented, under its known_hosts name
+struct PresentedKey {
+    name: String,
+    key: Vec<u8>,
+    key_type: HostKeyType,
+    fingerprint: String,
+}
+
+// None when known_hosts has this key for the host; otherwise the status to
+// prompt with and, for a changed key, the stored key's fingerprint. Looks up
+// the exact entry name: libssh2's port-aware check would also accept the
+// port-22 entry's key on any other port.
+fn check(known_hosts: &KnownHosts, presented: &PresentedKey) -> Result<Option<(HostKeyStatus, Option<String>)>, HostKeyError> {
+    match known_hosts.check(&presented.name, &presented.key) {
+        CheckResult::Match => Ok(None),
+        CheckResult::NotFound => Ok(Some((HostKeyStatus::Unknown, None))),
+        CheckResult::Mismatch => Ok(Some((HostKeyStatus::Changed, stored_fingerprint(known_hosts, &presented.name)))),
+        CheckResult::Failure => Err(HostKeyError::Store(format!("Failed to check host key for {}", presented.name))),
+    }
+}
+
+// Carries out the user's answer; AcceptAlways replaces any stored key for the host
+fn apply_decision(
+    known_hosts: &mut KnownHosts,
+    path: &Path,
+    presented: PresentedKey,
+    status: &HostKeyStatus,
+    previous_fingerprint: Option<String>,
+    decision: HostKeyDecision,
+) -> Result<(), HostKeyError> {
+    let changed = *status == HostKeyStatus::Changed;
+    let PresentedKey { name, key, key_type, fingerprint } = presented;
+    match decision {
+        HostKeyDecision::AcceptOnce => Ok(()),
+        HostKeyDecision::AcceptAlways => {
+            if changed {
+                remove_entries(known_hosts, &name)?;
+            }
+            known_hosts
+                .add(&name, &key, "added by TermAI", key_type.into())
+                .map_err(|e| HostKeyError::Store(format!("Failed to add key for {}: {}", name, e)))?;
+            save(known_hosts, path)?;
+            println!("Stored host key for {} in {}", name, path.display());
+            Ok(())
+        }
+        HostKeyDecision::Reject if changed => Err(HostKeyError::Mismatch {
+            host: name,
+            expected: previous_fingerprint,
+            presented: fingerprint,
+        }),
+        HostKeyDecision::Reject => Err(HostKeyError::Rejected { host: name, fingerprint }),
+    }
+}
+
+// Checks the server's host key right after the handshake, before any
+// credentials are sent. Blocking: waits for the user when the key is new or changed.
+pub struct HostKeyVerifier {
+    pub app_handle: AppHandle,
+    pub prompts: HostKeyPrompts,
+}
+
+impl HostKeyVerifier {
+    pub fn verify(&self, session: &Session, hostname: &str, port: u16) -> Result<(), HostKeyError> {
+        let (key, key_type) = session.host_key().ok_or(HostKeyError::Missing)?;
+        let presented =
+            PresentedKey { name: entry_name(hostname, port), key: key.to_vec(), key_type, fingerprint: fingerprint(key) };
+
+        let path = store_path(&self.app_handle)?;
+        let mut known_hosts = load(session, &path)?;
+        let Some((status, previous_fingerprint)) = check(&known_hosts, &presented)? else {
+            return Ok(());
+        };
+        println!(
+            "Host key for {} needs confirmation ({}, changed: {})",
+            presented.name,
+            presented.fingerprint,
+            status == HostKeyStatus::Changed
+        );
+
+        let decision = self.ask(HostKeyPromptPayload {
+            prompt_id: uuid::Uuid::new_v4().to_string(),
+            hostname: hostname.to_string(),
+            port,
+            key_type: key_type_name(key_type).to_string(),
+            fingerprint: presented.fingerprint.clone(),
+            status: status.clone(),
+            previous_fingerprint: previous_fingerprint.clone(),
+        });
+        apply_decision(&mut known_hosts, &path, presented, &status, previous_fingerprint, decision)
+    }
+
+    // Emits the prompt and blocks until the frontend answers; no answer counts as a rejection
+    fn ask(&self, payload: HostKeyPromptPayload) -> HostKeyDecision {
+        let prompt_id = payload.prompt_id.clone();
+        self.prompts
+            .ask(&self.app_handle, "ssh-hostkey-prompt", &prompt_id, payload, PROMPT_TIMEOUT)
+            .unwrap_or(HostKeyDecision::Reject)
+    }
+}
+
+// --- Tauri Commands ---
+
+#[command]
+pub fn respond_hostkey_prompt(state: State<'_, AppState>, prompt_id: String, decision: HostKeyDecision) -> Result<(), String> {
+    state.hostkey_prompts.answer(&prompt_id, decision)
+}
+
+#[command]
+pub fn list_known_hosts(app_handle: AppHandle) -> Result<Vec<KnownHostEntry>, String> {
+    let path = store_path(&app_handle)?;
+    let session = Session::new().map_err(|e| format!("Failed to create SSH session: {}", e))?;
+    let known_hosts = load(&session, &path)?;
+    let hosts = known_hosts.hosts().map_err(|e| format!("Failed to list known hosts: {}", e))?;
+    Ok(hosts
+        .iter()
+        .map(|host| KnownHostEntry {
+            host: host.name().unwrap_or("(hashed)").to_string(),
+            fingerprint: STANDARD
+                .decode(host.key())
+                .map(|key| fingerprint(&key))
+                .unwrap_or_else(|_| "invalid key".to_string()),
+        })
+        .collect())
+}
+
+#[command]
+pub fn remove_known_host(app_handle: AppHandle, hostname: String, port: u16) -> Result<(), String> {
+    let path = store_path(&app_handle)?;
+    let session = Session::new().map_err(|e| format!("Failed to create SSH session: {}", e))?;
+    let known_hosts = load(&session, &path)?;
+    remove_entries(&known_hosts, &entry_name(&hostname, port))?;
+    save(&known_hosts, &path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // An ssh-ed25519 public key blob with every key byte set to `fill`
+    fn ed25519_blob(fill: u8) -> Vec<u8> {
+        let mut blob = Vec::new();
+        blob.extend_from_slice(&11u32.to_be_bytes());
+        blob.extend_from_slice(b"ssh-ed25519");
+        blob.extend_from_slice(&32u32.to_be_bytes());
+        blob.extend_from_slice(&[fill; 32]);
+        blob
+    }
+
+    fn presented(hostname: &str, port: u16, fill: u8) -> PresentedKey {
+        let key = ed25519_blob(fill);
+        PresentedKey { name: entry_name(hostname, port), fingerprint: fingerprint(&key), key, key_type: HostKeyType::Ed25519 }
+    }
+
+    // A known_hosts file with rtr1 on port 22 (key 1) and on port 2222 (key 2)
+    fn fixture(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("termai-known-hosts-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(KNOWN_HOSTS_FILE);
+        let contents = format!(
+            "rtr1 ssh-ed25519 {}\n[rtr1]:2222 ssh-ed25519 {}\n",
+            STANDARD.encode(ed25519_blob(1)),
+            STANDARD.encode(ed25519_blob(2))
+        );
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn cleanup(path: &Path) {
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn entry_name_brackets_non_default_ports() {
+        assert_eq!(entry_name("rtr1", 22), "rtr1");
+        assert_eq!(entry_name("rtr1", 2222), "[rtr1]:2222");
+        assert_eq!(entry_name("2001:db8::1", 830), "[2001:db8::1]:830");
+    }
+
+    #[test]
+    fn fingerprint_matches_openssh_format() {
+        // SHA-256 of nothing, base64 without padding
+        assert_eq!(fingerprint(b""), "SHA256:47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU");
+        assert_ne!(fingerprint(&ed25519_blob(1)), fingerprint(&ed25519_blob(2)));
+    }
+
+    #[test]
+    fn stored_fingerprint_per_entry_name() {
+        let path = fixture("stored");
+        let session = Session::new().unwrap();
+        let known_hosts = load(&session, &path).unwrap();
+        assert_eq!(stored_fingerprint(&known_hosts, "rtr1"), Some(fingerprint(&ed25519_blob(1))));
+        assert_eq!(stored_fingerprint(&known_hosts, "[rtr1]:2222"), Some(fingerprint(&ed25519_blob(2))));
+        assert_eq!(stored_fingerprint(&known_hosts, "rtr2"), None);
+        cleanup(&path);
+    }
+
+    #[test]
+    fn check_tells_unknown_changed_and_matching_keys_apart() {
+        let path = fixture("check");
+        let session = Session::new().unwrap();
+        let known_hosts = load(&session, &path).unwrap();
+
+        assert_eq!(check(&known_hosts, &presented("rtr1", 22, 1)).unwrap(), None);
+        assert_eq!(check(&known_hosts, &presented("rtr1", 2222, 2)).unwrap(), None);
+        assert_eq!(
+            check(&known_hosts, &presented("rtr2", 22, 1)).unwrap(),
+            Some((HostKeyStatus::Unknown, None))
+        );
+        // Entries are per port: the port-22 key is not trusted on 830
+        assert_eq!(
+            check(&known_hosts, &presented("rtr1", 830, 1)).unwrap(),
+            Some((HostKeyStatus::Unknown, None))
+        );
+        // The port-22 key presented on 2222 is a change for [rtr1]:2222
+        assert_eq!(
+            check(&known_hosts, &presented("rtr1", 2222, 1)).unwrap(),
+            Some((HostKeyStatus::Changed, Some(fingerprint(&ed25519_blob(2)))))
+        );
+        assert_eq!(
+            check(&known_hosts, &presented("rtr1", 22, 3)).unwrap(),
+            Some((HostKeyStatus::Changed, Some(fingerprint(&ed25519_blob(1)))))
+        );
+        cleanup(&path);
+    }
+
+    #[test]
+    fn rejecting_a_changed_key_is_a_mismatch() {
+        let path = fixture("reject-changed");
+        let session = Session::new().unwrap();
+        let mut known_hosts = load(&session, &path).unwrap();
+        let key = presented("rtr1", 2222, 3);
+        let (status, previous) = check(&known_hosts, &key).unwrap().unwrap();
+        let error = apply_decision(&mut known_hosts, &path, key, &status, previous, HostKeyDecision::Reject).unwrap_err();
+        match error {
+            HostKeyError::Mismatch { host, expected, presented } => {
+                assert_eq!(host, "[rtr1]:2222");
+                assert_eq!(expected, Some(fingerprint(&ed25519_blob(2))));
+                assert_eq!(presented, fingerprint(&ed25519_blob(3)));
+            }
+            other => panic!("expected a mismatch, got {:?}", other),
+        }
+        cleanup(&path);
+    }
+
+    #[test]
+    fn rejecting_an_unknown_key() {
+        let path = fixture("reject-unknown");
+        let session = Session::new().unwrap();
+        let mut known_hosts = load(&session, &path).unwrap();
+        let key = presented("rtr2", 22, 3);
+        let (status, previous) = check(&known_hosts, &key).unwrap().unwrap();
+        let error = apply_decision(&mut known_hosts, &path, key, &status, previous, HostKeyDecision::Reject).unwrap_err();
+        assert!(matches!(error, HostKeyError::Rejected { host, .. } if host == "rtr2"));
+        cleanup(&path);
+    }
+
+    #[test]
+    fn accepting_once_stores_nothing() {
+        let path = fixture("accept-once");
+        let before = fs::read_to_string(&path).unwrap();
+        let session = Session::new().unwrap();
+        let mut known_hosts = load(&session, &path).unwrap();
+        let key = presented("rtr2", 22, 3);
+        let (status, previous) = check(&known_hosts, &key).unwrap().unwrap();
+        apply_decision(&mut known_hosts, &path, key, &status, previous, HostKeyDecision::AcceptOnce).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), before);
+        cleanup(&path);
+    }
+
+    #[test]
+    fn accepting_always_replaces_the_stored_key() {
+        let path = fixture("accept-always");
+        let session = Session::new().unwrap();
+        let mut known_hosts = load(&session, &path).unwrap();
+        let key = presented("rtr1", 2222, 3);
+        let (status, previous) = check(&known_hosts, &key).unwrap().unwrap();
+        apply_decision(&mut known_hosts, &path, key, &status, previous, HostKeyDecision::AcceptAlways).unwrap();
+
+        let known_hosts = load(&session, &path).unwrap();
+        assert_eq!(check(&known_hosts, &presented("rtr1", 2222, 3)).unwrap(), None);
+        assert!(check(&known_hosts, &presented("rtr1", 2222, 2)).unwrap().is_some());
+        // The port-22 entry is untouched
+        assert_eq!(check(&known_hosts, &presented("rtr1", 22, 1)).unwrap(), None);
+        cleanup(&path);
+    }
+}
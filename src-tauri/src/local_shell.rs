@@ -0,0 +1,193 @@
+// Local shell sessions: the user's own shell in a PTY on the workstation, for
+// ping/traceroute/ansible/git next to the remote sessions. Same events and
+// write/resize/disconnect commands as the remote transports.
+
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+use tauri::{command, AppHandle, State};
+use tokio::sync::mpsc::{self, Receiver};
+
+use crate::history::{self, Transport};
+use crate::keys::{expand_home, home_dir};
+use crate::session::{
+    emit_error, emit_output, emit_state, finish_session, SessionCommand, SessionHandle, SessionInfo, SessionKind,
+    SessionMap, SessionState, DEFAULT_PTY_COLS, DEFAULT_PTY_ROWS,
+};
+use crate::AppState;
+
+fn pty_size(cols: u16, rows: u16) -> PtySize {
+    PtySize { rows, cols, pixel_width: 0, pixel_height: 0 }
+}
+
+// The controlling side of the shell's PTY
+struct Pty {
+    master: Box<dyn MasterPty + Send>, // Kept for resizes; dropping it closes the PTY
+    reader: Box<dyn Read + Send>,
+    writer: Box<dyn Write + Send>,
+}
+
+pub fn local_username() -> String {
+    std::env::var("USER").or_else(|_| std::env::var("USERNAME")).unwrap_or_default()
+}
+
+// --- Local Shell I/O ---
+
+// PTY reads block, so output is pumped by its own thread while a second one
+// applies commands. The output thread ends the session once the shell exits.
+fn spawn_local_io(
+    app_handle: AppHandle,
+    session_id: String,
+    sessions: SessionMap,
+    pty: Pty,
+    mut child: Box<dyn Child + Send + Sync>,
+    mut command_receiver: Receiver<SessionCommand>,
+) {
+    let Pty { master, mut reader, mut writer } = pty;
+    let mut killer = child.clone_killer();
+    let closed_by_user = Arc::new(AtomicBool::new(false));
+
+    let output_session_id = session_id.clone();
+    let output_app_handle = app_handle.clone();
+    let output_closed_by_user = closed_by_user.clone();
+    thread::spawn(move || {
+        let session_id = output_session_id;
+        println!("[{}] Local shell output thread started.", session_id);
+        let mut buffer = [0; 4096];
+        loop {
+            match reader.read(&mut buffer) {
+                // EOF, or EIO on Linux once the shell has exited
+                Ok(0) | Err(_) => break,
+                Ok(n) => emit_output(&output_app_handle, &session_id, &buffer[..n]),
+            }
+        }
+
+        let exit_message = if output_closed_by_user.load(Ordering::SeqCst) {
+            "Connection closed by user.".to_string()
+        } else {
+            match child.wait() {
+                Ok(status) => format!("Shell exited. Exit status: {}", status.exit_code()),
+                Err(_) => "Shell exited.".to_string(),
+            }
+        };
+        finish_session(&output_app_handle, &sessions, &session_id, SessionState::Closed, exit_message);
+        println!("[{}] Local shell output thread finished.", session_id);
+    });
+
+    thread::spawn(move || {
+        // Returns None once the session is removed, i.e. after the shell exited
+        while let Some(command) = command_receiver.blocking_recv() {
+            match command {
+                SessionCommand::Write(data) => {
+                    if let Err(e) = writer.write_all(&data).and_then(|()| writer.flush()) {
+                        emit_error(&app_handle, &session_id, format!("Error writing to local shell: {}", e));
+                    }
+                }
+                SessionCommand::Resize { cols, rows } => {
+                    if let Err(e) = master.resize(pty_size(cols, rows)) {
+                        emit_error(&app_handle, &session_id, format!("Failed to resize PTY: {}", e));
+                    }
+                }
+                SessionCommand::Break(_) => {
+                    emit_error(&app_handle, &session_id, "BREAK is not supported on local shells".to_string());
+                }
+                SessionCommand::Disconnect => {
+                    println!("[{}] I/O thread received disconnect.", session_id);
+                    closed_by_user.store(true, Ordering::SeqCst);
+                    let _ = killer.kill();
+                    break;
+                }
+            }
+        }
+        // Dropping the master closes the PTY
+        drop(writer);
+        drop(master);
+    });
+}
+
+// --- Tauri Commands ---
+
+// Starts a local shell and returns its session id. Without `shell` the user's
+// login shell is used ($SHELL, or the ComSpec shell on Windows); `cwd` defaults
+// to the home directory.
+#[command]
+pub async fn local_shell_connect(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    shell: Option<String>,
+    cwd: Option<String>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+) -> Result<String, String> {
+    let cols = cols.unwrap_or(DEFAULT_PTY_COLS);
+    let rows = rows.unwrap_or(DEFAULT_PTY_ROWS);
+    let shell = shell.filter(|shell| !shell.trim().is_empty());
+    let cwd = cwd.filter(|cwd| !cwd.trim().is_empty());
+
+    let mut command = match shell.as_deref() {
+        Some(shell) => CommandBuilder::new(shell.trim()),
+        None => CommandBuilder::new_default_prog(),
+    };
+    if let Some(dir) = cwd.as_deref().map(expand_home).or_else(home_dir) {
+        command.cwd(dir);
+    }
+    command.env("TERM", "xterm-256color");
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let username = local_username();
+    history::record_attempt(&app_handle, &session_id, SessionKind::Local, "localhost", 0, &username, Transport::Local { shell, cwd });
+    let spawned = (|| {
+        let pair = native_pty_system()
+            .openpty(pty_size(cols, rows))
+            .map_err(|e| format!("Failed to open PTY: {}", e))?;
+        let mut child = pair
+            .slave
+            .spawn_command(command)
+            .map_err(|e| format!("Failed to start local shell: {}", e))?;
+        // Only the child keeps the slave open, so the reader sees EOF when it exits
+        drop(pair.slave);
+        let streams = pair
+            .master
+            .try_clone_reader()
+            .and_then(|reader| Ok((reader, pair.master.take_writer()?)))
+            .map_err(|e| format!("Failed to attach to PTY: {}", e));
+        match streams {
+            Ok((reader, writer)) => Ok((Pty { master: pair.master, reader, writer }, child)),
+            Err(e) => {
+                let _ = child.kill();
+                Err(e)
+            }
+        }
+    })();
+    let (pty, child) = spawned.inspect_err(|e| history::record_failure(&app_handle, &session_id, e))?;
+    println!("[{}] Started local shell (pid {:?})", session_id, child.process_id());
+
+    let (command_tx, command_rx) = mpsc::channel::<SessionCommand>(32);
+    {
+        let mut sessions_guard = state.sessions.lock().map_err(|_| "Failed to lock state mutex".to_string())?;
+        sessions_guard.insert(session_id.clone(), SessionHandle {
+            command_sender: command_tx,
+            ssh: None,
+            forwards: HashMap::new(),
+            info: SessionInfo {
+                id: session_id.clone(),
+                kind: SessionKind::Local,
+                hostname: "localhost".to_string(),
+                port: 0,
+                username,
+                connected_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+                latency_ms: None,
+            },
+        });
+    }
+
+    history::record_connected(&app_handle, &session_id);
+    emit_state(&app_handle, &session_id, SessionState::Connected, None, None);
+    spawn_local_io(app_handle, session_id.clone(), state.sessions.clone(), pty, child, command_rx);
+    Ok(session_id)
+}
@@ -0,0 +1,537 @@
+// Saved connection profiles, persisted as versioned JSON in the app data
+// directory. Passwords and passphrases are never written here: a profile names
+// the vault secrets to use, or they are passed in when it is connected.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::{command, AppHandle, Manager, State};
+
+use crate::algorithms::AlgorithmOverrides;
+use crate::connect_error::ConnectError;
+use crate::history;
+use crate::keepalive::KeepaliveConfig;
+use crate::reconnect::ReconnectPolicy;
+use crate::session::{session_sender, SessionCommand};
+use crate::ssh_client::{self, JumpHostConfig};
+use crate::telnet;
+use crate::vault::VaultHandle;
+use crate::AppState;
+
+const PROFILES_FILE: &str = "profiles.json";
+// Bumped whenever the file layout changes incompatibly; see `migrate`
+const PROFILES_VERSION: u32 = 1;
+
+// Serializes read-modify-write cycles on the file
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+fn default_port() -> u16 {
+    22
+}
+
+// --- Types ---
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfileProtocol {
+    #[default]
+    Ssh,
+    Telnet,
+}
+
+impl ProfileProtocol {
+    pub fn default_port(self) -> u16 {
+        match self {
+            ProfileProtocol::Ssh => 22,
+            ProfileProtocol::Telnet => 23,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthMethod {
+    Password,
+    Key,
+    #[default]
+    Agent, // SSH agent and default identities, then keyboard-interactive
+}
+
+// A bastion in a profile's jump chain; credentials beyond the key come from the agent or prompts
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileJumpHost {
+    pub hostname: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    pub username: String,
+    #[serde(default)]
+    pub private_key_path: Option<String>,
+    #[serde(default)]
+    pub certificate_path: Option<String>,
+    #[serde(default)]
+    pub password_secret_id: Option<String>,
+    #[serde(default)]
+    pub passphrase_secret_id: Option<String>,
+    #[serde(default)]
+    pub algorithms: AlgorithmOverrides,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    #[serde(default)]
+    pub id: String, // Assigned by `save_profile` when empty
+    pub name: String,
+    #[serde(default)]
+    pub protocol: ProfileProtocol,
+    pub hostname: String,
+    #[serde(default)]
+    pub port: u16, // 0 picks the protocol's default port
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub auth_method: AuthMethod,
+    #[serde(default)]
+    pub private_key_path: Option<String>,
+    #[serde(default)]
+    pub certificate_path: Option<String>,
+    #[serde(default)]
+    pub password_secret_id: Option<String>, // Vault secrets, used when the call brings none
+    #[serde(default)]
+    pub passphrase_secret_id: Option<String>,
+    #[serde(default)]
+    pub jump_hosts: Vec<ProfileJumpHost>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>, // Imported inventory variables (e.g. ansible_network_os)
+    #[serde(default)]
+    pub post_login_commands: Vec<String>, // Sent to the shell once connected (e.g. "terminal length 0")
+    #[serde(default)]
+    pub algorithms: AlgorithmOverrides, // e.g. legacy ciphers for old IOS devices
+    #[serde(default)]
+    pub reconnect: Option<ReconnectPolicy>,
+    #[serde(default)]
+    pub keepalive: Option<KeepaliveConfig>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub source: Option<String>, // What the profile was imported from (e.g. "ssh_config:/home/me/.ssh/config")
+    #[serde(default)]
+    pub created_at: u64, // Unix seconds
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+// On-disk layout of profiles.json
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ProfileFile {
+    version: u32,
+    #[serde(default)]
+    profiles: Vec<Profile>,
+}
+
+// --- Storage ---
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn store_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to locate app data directory: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    Ok(dir.join(PROFILES_FILE))
+}
+
+// Upgrades a file written by an older version of the app
+fn migrate(file: ProfileFile, path: &Path) -> Result<ProfileFile, String> {
+    match file.version {
+        PROFILES_VERSION => Ok(file),
+        version if version > PROFILES_VERSION => Err(format!(
+            "{} was written by a newer version of TermAI (format {}, this version reads up to {})",
+            path.display(),
+            version,
+            PROFILES_VERSION
+        )),
+        version => Err(format!("{} has an unknown format version {}", path.display(), version)),
+    }
+}
+
+// Missing file = no profiles yet
+fn load(path: &Path) -> Result<Vec<Profile>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let file: ProfileFile =
+        serde_json::from_str(&contents).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+    Ok(migrate(file, path)?.profiles)
+}
+
+// Writes to a temporary file first so a crash never leaves a truncated store
+fn save(path: &Path, profiles: Vec<Profile>) -> Result<(), String> {
+    let file = ProfileFile { version: PROFILES_VERSION, profiles };
+    let contents = serde_json::to_string_pretty(&file).map_err(|e| format!("Failed to encode profiles: {}", e))?;
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, contents).map_err(|e| format!("Failed to write {}: {}", temp.display(), e))?;
+    fs::rename(&temp, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+// Runs `update` on the stored profiles under the store lock and writes the result back
+pub fn modify<T>(app_handle: &AppHandle, update: impl FnOnce(&mut Vec<Profile>) -> Result<T, String>) -> Result<T, String> {
+    modify_file(&store_path(app_handle)?, update)
+}
+
+fn modify_file<T>(path: &Path, update: impl FnOnce(&mut Vec<Profile>) -> Result<T, String>) -> Result<T, String> {
+    let _guard = STORE_LOCK.lock().map_err(|_| "Failed to lock profile store".to_string())?;
+    let mut profiles = load(path)?;
+    let result = update(&mut profiles)?;
+    save(path, profiles)?;
+    Ok(result)
+}
+
+pub fn list(app_handle: &AppHandle) -> Result<Vec<Profile>, String> {
+    let _guard = STORE_LOCK.lock().map_err(|_| "Failed to lock profile store".to_string())?;
+    load(&store_path(app_handle)?)
+}
+
+pub fn find(app_handle: &AppHandle, profile_id: &str) -> Result<Profile, String> {
+    list(app_handle)?
+        .into_iter()
+        .find(|profile| profile.id == profile_id)
+        .ok_or_else(|| format!("Unknown profile {}", profile_id))
+}
+
+// Trims the free-text fields and rejects profiles that could never connect
+pub fn normalize(mut profile: Profile) -> Result<Profile, String> {
+    profile.name = profile.name.trim().to_string();
+    profile.hostname = profile.hostname.trim().to_string();
+    profile.username = profile.username.trim().to_string();
+    if profile.name.is_empty() {
+        return Err("Profile name is required".to_string());
+    }
+    if profile.hostname.is_empty() {
+        return Err(format!("Profile {} has no hostname", profile.name));
+    }
+    if profile.port == 0 {
+        profile.port = profile.protocol.default_port();
+    }
+    if profile.protocol == ProfileProtocol::Ssh && profile.username.is_empty() {
+        return Err(format!("Profile {} has no username", profile.name));
+    }
+    // Blank fields from the profile editor mean "not set"
+    profile.private_key_path = profile.private_key_path.filter(|path| !path.trim().is_empty());
+    profile.certificate_path = profile.certificate_path.filter(|path| !path.trim().is_empty());
+    profile.password_secret_id = profile.password_secret_id.filter(|id| !id.trim().is_empty());
+    profile.passphrase_secret_id = profile.passphrase_secret_id.filter(|id| !id.trim().is_empty());
+    for hop in &mut profile.jump_hosts {
+        hop.password_secret_id = hop.password_secret_id.take().filter(|id| !id.trim().is_empty());
+        hop.passphrase_secret_id = hop.passphrase_secret_id.take().filter(|id| !id.trim().is_empty());
+    }
+    if profile.auth_method == AuthMethod::Key && profile.private_key_path.is_none() {
+        return Err(format!("Profile {} uses key authentication but has no private key path", profile.name));
+    }
+    profile.tags = profile.tags.iter().map(|tag| tag.trim().to_string()).filter(|tag| !tag.is_empty()).collect();
+    profile.post_login_commands.retain(|command| !command.trim().is_empty());
+    Ok(profile)
+}
+
+// Inserts a new profile or replaces the one with the same id; returns what was stored
+pub fn upsert(app_handle: &AppHandle, profile: Profile) -> Result<Profile, String> {
+    let mut profile = normalize(profile)?;
+    modify(app_handle, |profiles| {
+        let now = now();
+        profile.updated_at = now;
+        match profiles.iter_mut().find(|existing| !profile.id.is_empty() && existing.id == profile.id) {
+            Some(existing) => {
+                profile.created_at = existing.created_at;
+                *existing = profile.clone();
+            }
+            None => {
+                if profile.id.is_empty() {
+                    profile.id = uuid::Uuid::new_v4().to_string();
+                }
+                profile.created_at = now;
+                profiles.push(profile.clone());
+            }
+        }
+        Ok(profile)
+    })
+}
+
+// Fills in a credential from the vault when the caller did not supply one
+fn resolve_secret(vault: &VaultHandle, given: Option<String>, secret_id: Option<&str>) -> Result<Option<String>, ConnectError> {
+    match (given.filter(|value| !value.is_empty()), secret_id) {
+        (Some(value), _) => Ok(Some(value)),
+        (None, Some(secret_id)) => {
+            let mut vault = vault.lock().map_err(|_| ConnectError::Other("Failed to lock vault mutex".to_string()))?;
+            let secret = vault.secret(secret_id).map_err(|e| ConnectError::AuthFailed(e.to_string()))?;
+            Ok(Some(secret.to_string()))
+        }
+        (None, None) => Ok(None),
+    }
+}
+
+// --- Tauri Commands ---
+
+#[command]
+pub async fn list_profiles(app_handle: AppHandle) -> Result<Vec<Profile>, String> {
+    let mut profiles = list(&app_handle)?;
+    profiles.sort_by_key(|profile| profile.name.to_lowercase());
+    Ok(profiles)
+}
+
+// Creates a profile (empty id) or updates an existing one
+#[command]
+pub async fn save_profile(app_handle: AppHandle, profile: Profile) -> Result<Profile, String> {
+    let profile = upsert(&app_handle, profile)?;
+    println!("Saved profile {} ({})", profile.name, profile.id);
+    Ok(profile)
+}
+
+#[command]
+pub async fn delete_profile(app_handle: AppHandle, profile_id: String) -> Result<(), String> {
+    modify(&app_handle, |profiles| {
+        let before = profiles.len();
+        profiles.retain(|profile| profile.id != profile_id);
+        if profiles.len() == before {
+            return Err(format!("Unknown profile {}", profile_id));
+        }
+        Ok(())
+    })?;
+    println!("Deleted profile {}", profile_id);
+    Ok(())
+}
+
+// Opens a session from a saved profile and sends its post-login commands.
+// A password or passphrase given with the call wins over the profile's vault
+// secrets; a passphrase found in neither is prompted for as usual.
+#[command]
+pub async fn connect_profile(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    profile_id: String,
+    password: Option<String>,
+    passphrase: Option<String>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+) -> Result<String, ConnectError> {
+    let profile = find(&app_handle, &profile_id).map_err(ConnectError::Other)?;
+    println!("Connecting profile {} ({})", profile.name, profile.id);
+
+    let session_id = match profile.protocol {
+        ProfileProtocol::Ssh => {
+            let (password, private_key_path, certificate_path, passphrase) = match profile.auth_method {
+                AuthMethod::Password => {
+                    let password = resolve_secret(&state.vault, password, profile.password_secret_id.as_deref())?
+                        .ok_or_else(|| ConnectError::AuthFailed(format!("Profile {} needs a password", profile.name)))?;
+                    (Some(password), None, None, None)
+                }
+                AuthMethod::Key => {
+                    let passphrase = resolve_secret(&state.vault, passphrase, profile.passphrase_secret_id.as_deref())?;
+                    (None, profile.private_key_path.clone(), profile.certificate_path.clone(), passphrase)
+                }
+                AuthMethod::Agent => (None, None, None, None),
+            };
+            let mut jump_hosts: Vec<JumpHostConfig> = Vec::new();
+            for hop in &profile.jump_hosts {
+                jump_hosts.push(JumpHostConfig {
+                    hostname: hop.hostname.clone(),
+                    port: Some(hop.port),
+                    username: hop.username.clone(),
+                    password: resolve_secret(&state.vault, None, hop.password_secret_id.as_deref())?,
+                    private_key_path: hop.private_key_path.clone(),
+                    passphrase: resolve_secret(&state.vault, None, hop.passphrase_secret_id.as_deref())?,
+                    certificate_path: hop.certificate_path.clone(),
+                    algorithms: hop.algorithms.clone(),
+                });
+            }
+            ssh_client::ssh_connect(
+                app_handle.clone(),
+                state.clone(),
+                profile.hostname.clone(),
+                profile.port,
+                profile.username.clone(),
+                password,
+                private_key_path,
+                passphrase,
+                certificate_path,
+                (!jump_hosts.is_empty()).then_some(jump_hosts),
+                cols,
+                rows,
+                profile.reconnect.clone(),
+                profile.keepalive.clone(),
+                profile.timeout_secs,
+                Some(profile.algorithms.clone()),
+            )
+            .await?
+        }
+        ProfileProtocol::Telnet => {
+            telnet::telnet_connect(
+                app_handle.clone(),
+                state.clone(),
+                profile.hostname.clone(),
+                Some(profile.port),
+                Some(profile.username.clone()),
+                None,
+                cols,
+                rows,
+                profile.timeout_secs,
+            )
+            .await
+            .map_err(ConnectError::Other)?
+        }
+    };
+
+    history::attach_profile(&app_handle, &session_id, &profile.id, &profile.name);
+    if !profile.post_login_commands.is_empty() {
+        if let Some(sender) = session_sender(&state.sessions, &session_id).map_err(ConnectError::Other)? {
+            for command in &profile.post_login_commands {
+                let line = format!("{}\r", command);
+                if sender.send(SessionCommand::Write(line.into_bytes())).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(session_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Only the fields the editor always sends; serde fills in the rest
+    fn profile(name: &str, protocol: &str) -> Profile {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "protocol": protocol,
+            "hostname": " core-sw1.example.net ",
+            "username": " admin ",
+        }))
+        .unwrap()
+    }
+
+    fn profile_with(change: impl FnOnce(&mut Profile), protocol: &str) -> Profile {
+        let mut profile = profile("Core switch", protocol);
+        change(&mut profile);
+        profile
+    }
+
+    fn test_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("termai-profiles-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(PROFILES_FILE)
+    }
+
+    #[test]
+    fn normalize_trims_and_fills_defaults() {
+        let mut input = profile("  Core switch ", "ssh");
+        input.private_key_path = Some("  ".to_string());
+        input.password_secret_id = Some(String::new());
+        input.tags = vec![" lab ".to_string(), " ".to_string()];
+        input.post_login_commands = vec!["terminal length 0".to_string(), "  ".to_string()];
+        input.jump_hosts = vec![serde_json::from_value(serde_json::json!({
+            "hostname": "bastion",
+            "username": "jump",
+            "passwordSecretId": " ",
+        }))
+        .unwrap()];
+
+        let profile = normalize(input).unwrap();
+        assert_eq!(profile.name, "Core switch");
+        assert_eq!(profile.hostname, "core-sw1.example.net");
+        assert_eq!(profile.username, "admin");
+        assert_eq!(profile.port, 22);
+        assert_eq!(profile.private_key_path, None);
+        assert_eq!(profile.password_secret_id, None);
+        assert_eq!(profile.jump_hosts[0].port, 22);
+        assert_eq!(profile.jump_hosts[0].password_secret_id, None);
+        assert_eq!(profile.tags, ["lab"]);
+        assert_eq!(profile.post_login_commands, ["terminal length 0"]);
+
+        // Telnet logs in through the terminal when there is no username
+        let telnet = normalize(profile_with(|p| p.username.clear(), "telnet")).unwrap();
+        assert_eq!(telnet.port, 23);
+        let custom_port = normalize(profile_with(|p| p.port = 2323, "telnet")).unwrap();
+        assert_eq!(custom_port.port, 2323);
+    }
+
+    #[test]
+    fn normalize_rejects_profiles_that_cannot_connect() {
+        let error = |profile: Profile| normalize(profile).unwrap_err();
+        assert_eq!(error(profile_with(|p| p.name = " ".to_string(), "ssh")), "Profile name is required");
+        assert!(error(profile_with(|p| p.hostname.clear(), "ssh")).contains("no hostname"));
+        assert!(error(profile_with(|p| p.username = " ".to_string(), "ssh")).contains("no username"));
+        assert!(error(profile_with(|p| p.auth_method = AuthMethod::Key, "ssh")).contains("no private key path"));
+        let key = profile_with(
+            |p| {
+                p.auth_method = AuthMethod::Key;
+                p.private_key_path = Some("~/.ssh/id_ed25519".to_string());
+            },
+            "ssh",
+        );
+        assert!(normalize(key).is_ok());
+    }
+
+    #[test]
+    fn missing_store_has_no_profiles() {
+        assert!(load(&test_path("missing")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn store_round_trips_with_version() {
+        let path = test_path("round-trip");
+        let stored = normalize(profile("Core switch", "ssh")).unwrap();
+        save(&path, vec![stored.clone()]).unwrap();
+
+        let written: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written["version"], PROFILES_VERSION);
+        assert_eq!(written["profiles"][0]["hostname"], "core-sw1.example.net");
+        assert_eq!(load(&path).unwrap(), [stored]);
+        assert!(!path.with_extension("json.tmp").exists());
+    }
+
+    #[test]
+    fn refuses_unknown_versions() {
+        let path = test_path("versions");
+        fs::write(&path, r#"{"version": 2, "profiles": []}"#).unwrap();
+        assert!(load(&path).unwrap_err().contains("newer version of TermAI (format 2"));
+        fs::write(&path, r#"{"version": 0}"#).unwrap();
+        assert!(load(&path).unwrap_err().contains("unknown format version 0"));
+        fs::write(&path, r#"{"version": 1}"#).unwrap();
+        assert!(load(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn concurrent_modifications_are_not_lost() {
+        let path = test_path("concurrent");
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    modify_file(&path, |profiles| {
+                        profiles.push(profile(&format!("Switch {}", i), "ssh"));
+                        Ok(())
+                    })
+                    .unwrap()
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let mut names: Vec<String> = load(&path).unwrap().into_iter().map(|profile| profile.name).collect();
+        names.sort();
+        assert_eq!(names, (0..8).map(|i| format!("Switch {}", i)).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,74 @@
+// Questions the backend has to ask the user in the middle of a blocking
+// operation (host key confirmation, key passphrase, ...). The operation emits
+// an event carrying a prompt id and waits; a Tauri command delivers the answer.
+
+use std::collections::HashMap;
+use std::sync::mpsc as std_mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::AppHandle;
+
+use crate::session::emit_event;
+
+pub struct PromptRegistry<T> {
+    pending: Arc<Mutex<HashMap<String, std_mpsc::Sender<T>>>>,
+}
+
+impl<T> Clone for PromptRegistry<T> {
+    fn clone(&self) -> Self {
+        PromptRegistry { pending: Arc::clone(&self.pending) }
+    }
+}
+
+impl<T: Send + 'static> Default for PromptRegistry<T> {
+    fn default() -> Self {
+        PromptRegistry { pending: Arc::new(Mutex::new(HashMap::new())) }
+    }
+}
+
+impl<T: Send + 'static> PromptRegistry<T> {
+    // Emits `event` and blocks until the frontend answers `prompt_id`.
+    // Returns None on timeout. Only call from a blocking thread.
+    pub fn ask<P: serde::Serialize + Clone>(
+        &self,
+        app_handle: &AppHandle,
+        event: &str,
+        prompt_id: &str,
+        payload: P,
+        timeout: Duration,
+    ) -> Option<T> {
+        let (tx, rx) = std_mpsc::channel();
+        match self.pending.lock() {
+            Ok(mut guard) => {
+                guard.insert(prompt_id.to_string(), tx);
+            }
+            Err(_) => return None,
+        }
+
+        emit_event(app_handle, event, payload);
+        let answer = rx.recv_timeout(timeout).ok();
+        if answer.is_none() {
+            println!("Prompt {} ({}) timed out.", prompt_id, event);
+        }
+
+        if let Ok(mut guard) = self.pending.lock() {
+            guard.remove(prompt_id);
+        }
+        answer
+    }
+
+    // Delivers the user's answer to whoever is waiting on `prompt_id`
+    pub fn answer(&self, prompt_id: &str, value: T) -> Result<(), String> {
+        let sender = {
+            let mut guard = self.pending.lock().map_err(|_| "Failed to lock prompt registry".to_string())?;
+            guard.remove(prompt_id)
+        };
+        match sender {
+            Some(sender) => sender
+                .send(value)
+                .map_err(|_| format!("Operation waiting on prompt {} is gone", prompt_id)),
+            None => Err(format!("Unknown or expired prompt {}", prompt_id)),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+// Opt-in automatic reconnect for SSH sessions whose transport drops (VPN blips,
+// idle NAT timeouts). The session keeps its id, so the terminal and AI agent
+// carry on once the new shell is up.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc::{error::TryRecvError, Receiver};
+
+use crate::session::SessionCommand;
+
+// Granularity of the backoff sleep; disconnects are noticed this quickly
+const WAIT_POLL: Duration = Duration::from_millis(100);
+
+fn default_initial_delay_ms() -> u64 {
+    1000
+}
+
+fn default_max_delay_ms() -> u64 {
+    30_000
+}
+
+// Sent with `ssh_connect`; without one a dropped session just closes
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconnectPolicy {
+    pub max_attempts: u32,
+    #[serde(default = "default_initial_delay_ms")]
+    pub initial_delay_ms: u64, // Delay before the first attempt, doubled after each failure
+    #[serde(default = "default_max_delay_ms")]
+    pub max_delay_ms: u64,
+    #[serde(default)]
+    pub replay_commands: Vec<String>, // Sent to the new shell after each reconnect (e.g. "terminal length 0")
+}
+
+impl ReconnectPolicy {
+    // Exponential backoff for the given 1-based attempt
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 1u64.checked_shl(attempt.saturating_sub(1)).unwrap_or(u64::MAX);
+        Duration::from_millis(self.initial_delay_ms.saturating_mul(factor).min(self.max_delay_ms))
+    }
+}
+
+// Sleeps for `delay` while draining session commands. Resizes are remembered
+// for the next shell; input typed while disconnected is dropped.
+// Returns false if the user disconnected in the meantime.
+pub fn wait_for_retry(command_receiver: &mut Receiver<SessionCommand>, delay: Duration, size: &mut (u16, u16)) -> bool {
+    let deadline = Instant::now() + delay;
+    loop {
+        loop {
+            match command_receiver.try_recv() {
+                Ok(SessionCommand::Resize { cols, rows }) => *size = (cols, rows),
+                Ok(SessionCommand::Write(_)) | Ok(SessionCommand::Break(_)) => {}
+                Ok(SessionCommand::Disconnect) | Err(TryRecvError::Disconnected) => return false,
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return true;
+        }
+        thread::sleep(WAIT_POLL.min(deadline - now));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(initial_delay_ms: u64, max_delay_ms: u64) -> ReconnectPolicy {
+        ReconnectPolicy { max_attempts: 10, initial_delay_ms, max_delay_ms, replay_commands: Vec::new() }
+    }
+
+    #[test]
+    fn delay_doubles_up_to_the_cap() {
+        let policy = policy(1000, 30_000);
+        let delays: Vec<u64> = (1..=7).map(|attempt| policy.delay(attempt).as_millis() as u64).collect();
+        assert_eq!(delays, [1000, 2000, 4000, 8000, 16_000, 30_000, 30_000]);
+    }
+
+    #[test]
+    fn delay_does_not_overflow() {
+        let policy = policy(1000, 30_000);
+        for attempt in [63, 64, 65, u32::MAX] {
+            assert_eq!(policy.delay(attempt), Duration::from_millis(30_000));
+        }
+        // Attempt 0 is treated as the first
+        assert_eq!(policy.delay(0), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn defaults_apply_when_only_attempts_are_given() {
+        let policy: ReconnectPolicy = serde_json::from_str(r#"{ "maxAttempts": 3 }"#).unwrap();
+        assert_eq!(policy, ReconnectPolicy { max_attempts: 3, ..self::policy(1000, 30_000) });
+    }
+}
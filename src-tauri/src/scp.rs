@@ -0,0 +1,262 @@
+// SCP push/pull, mainly for network devices (IOS images, configs) that speak
+// SCP but not SFTP. Transfers run in the background, report through
+// `file-transfer` and are checked afterwards by size and, on request, MD5.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use md5::{Digest, Md5};
+use ssh2::{Channel, Session};
+use tauri::{command, AppHandle, State};
+use tokio::task;
+
+use crate::session::session_transport;
+use crate::ssh_client::{retry, retry_io, run_exec, write_all_nonblocking};
+use crate::transfers::{Transfer, TransferDirection};
+use crate::AppState;
+
+// Mode for files created on the remote side
+const UPLOAD_MODE: i32 = 0o644;
+
+// --- Checksums ---
+
+// Device filesystems are written "flash:", "bootflash:/dir/file", "disk0:"...
+fn is_device_path(path: &str) -> bool {
+    match path.find(':') {
+        Some(colon) => !path[..colon].contains('/') && colon > 0,
+        None => false,
+    }
+}
+
+// Command that prints the MD5 of a remote file: Cisco's `verify /md5` on
+// device filesystems, `md5sum` everywhere else
+fn remote_md5_command(path: &str) -> String {
+    if is_device_path(path) {
+        format!("verify /md5 {}", path)
+    } else {
+        format!("md5sum '{}'", path.replace('\'', "'\\''"))
+    }
+}
+
+// First 32-hex-digit word in the command output
+fn parse_md5(output: &str) -> Option<String> {
+    output
+        .split(|c: char| !c.is_ascii_hexdigit())
+        .find(|word| word.len() == 32)
+        .map(|word| word.to_ascii_lowercase())
+}
+
+fn remote_md5(ssh: &Session, path: &str) -> Result<String, String> {
+    let command = remote_md5_command(path);
+    let (_, output) = run_exec(ssh, &command)?;
+    parse_md5(&output).ok_or_else(|| format!("Could not read an MD5 from `{}`: {}", command, output.trim()))
+}
+
+// Compares the local digest with the expected one and/or the remote file's.
+// Returns whether a check was actually made.
+fn check_md5(
+    ssh: &Session,
+    remote_path: &str,
+    local_md5: &str,
+    check_remote: bool,
+    expected_md5: Option<&str>,
+) -> Result<bool, String> {
+    let mut checked = false;
+    if let Some(expected) = expected_md5.map(|e| e.trim().to_ascii_lowercase()).filter(|e| !e.is_empty()) {
+        if expected != local_md5 {
+            return Err(format!("MD5 mismatch: expected {}, transferred data is {}", expected, local_md5));
+        }
+        checked = true;
+    }
+    if check_remote {
+        let remote = remote_md5(ssh, remote_path)?;
+        if remote != local_md5 {
+            return Err(format!("MD5 mismatch: remote file is {}, local copy is {}", remote, local_md5));
+        }
+        checked = true;
+    }
+    Ok(checked)
+}
+
+// Waits for the remote scp to acknowledge the end of the file
+fn close_channel(channel: &mut Channel) -> Result<i32, String> {
+    retry(|| channel.send_eof()).map_err(|e| format!("Failed to finish transfer: {}", e))?;
+    retry(|| channel.wait_eof()).map_err(|e| format!("Remote did not confirm the transfer: {}", e))?;
+    retry(|| channel.close()).map_err(|e| format!("Failed to close SCP channel: {}", e))?;
+    retry(|| channel.wait_close()).map_err(|e| format!("Failed to close SCP channel: {}", e))?;
+    channel.exit_status().map_err(|e| format!("Failed to read scp exit status: {}", e))
+}
+
+// Error for an upload that stopped early; the remote scp keeps what it got
+fn partial_upload(error: &str, remote_path: &str, sent: u64, size: u64) -> String {
+    format!("{}. {} on the remote side is incomplete ({} of {} bytes) and should be deleted", error, remote_path, sent, size)
+}
+
+// --- Tauri Commands ---
+
+// Uploads a local file. `verify_md5` compares against the device's own MD5
+// afterwards; `expected_md5` (e.g. a published image hash) is checked too.
+#[command]
+pub async fn scp_upload(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    session_id: String,
+    local_path: String,
+    remote_path: String,
+    verify_md5: Option<bool>,
+    expected_md5: Option<String>,
+) -> Result<String, String> {
+    let ssh = session_transport(&state.sessions, &session_id)?;
+    let mut transfer = Transfer::start(app_handle, &state.transfers, &session_id, TransferDirection::Upload, &remote_path, &local_path)?;
+    let transfer_id = transfer.id().to_string();
+
+    task::spawn_blocking(move || {
+        let result = (|| {
+            let mut local = fs::File::open(&local_path).map_err(|e| format!("Cannot open {}: {}", local_path, e))?;
+            let size = local.metadata().map_err(|e| format!("Cannot read {}: {}", local_path, e))?.len();
+            transfer.set_total(Some(size));
+
+            let mut channel = retry(|| ssh.scp_send(Path::new(&remote_path), UPLOAD_MODE, size, None))
+                .map_err(|e| format!("Device refused SCP upload to {}: {}", remote_path, e))?;
+            let mut hasher = Md5::new();
+            let copied = transfer
+                .copy(&mut local, &mut channel, |r, buf| r.read(buf), |w, data| {
+                    hasher.update(data);
+                    write_all_nonblocking(w, data)
+                })
+                .and_then(|()| match transfer.bytes() {
+                    sent if sent == size => Ok(()),
+                    sent => Err(format!("Size mismatch: sent {} of {} bytes", sent, size)),
+                });
+            if let Err(e) = copied {
+                // EOF ends the remote scp rather than leaving it waiting for the rest
+                let _ = close_channel(&mut channel);
+                return Err(partial_upload(&e, &remote_path, transfer.bytes(), size));
+            }
+            let status = close_channel(&mut channel)?;
+            if status != 0 {
+                return Err(format!("Remote scp exited with status {}", status));
+            }
+
+            let md5 = format!("{:x}", hasher.finalize());
+            let checked = check_md5(&ssh, &remote_path, &md5, verify_md5.unwrap_or(false), expected_md5.as_deref())
+                .inspect_err(|_| transfer.set_verification(Some(md5.clone()), Some(false)))?;
+            transfer.set_verification(Some(md5), checked.then_some(true));
+            Ok(())
+        })();
+        transfer.finish(&result);
+    });
+    Ok(transfer_id)
+}
+
+// Downloads a remote file; the received size must match what the device announced
+#[command]
+pub async fn scp_download(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    session_id: String,
+    remote_path: String,
+    local_path: String,
+    verify_md5: Option<bool>,
+    expected_md5: Option<String>,
+) -> Result<String, String> {
+    let ssh = session_transport(&state.sessions, &session_id)?;
+    let mut transfer = Transfer::start(app_handle, &state.transfers, &session_id, TransferDirection::Download, &remote_path, &local_path)?;
+    let transfer_id = transfer.id().to_string();
+
+    task::spawn_blocking(move || {
+        let result = (|| {
+            let (channel, stat) = retry(|| ssh.scp_recv(Path::new(&remote_path)))
+                .map_err(|e| format!("Device refused SCP download of {}: {}", remote_path, e))?;
+            let size = stat.size();
+            transfer.set_total(Some(size));
+
+            let mut local = fs::File::create(&local_path).map_err(|e| format!("Cannot create {}: {}", local_path, e))?;
+            let mut hasher = Md5::new();
+            let mut body = channel.take(size);
+            let copied = transfer
+                .copy(&mut body, &mut local, |r, buf| retry_io(|| r.read(buf)), |w, data| {
+                    hasher.update(data);
+                    w.write_all(data)
+                })
+                .and_then(|()| match transfer.bytes() {
+                    received if received == size => Ok(()),
+                    received => Err(format!("Size mismatch: received {} of {} bytes", received, size)),
+                })
+                .and_then(|()| local.flush().map_err(|e| format!("Cannot write {}: {}", local_path, e)));
+            let mut channel = body.into_inner();
+            let _ = close_channel(&mut channel);
+            if let Err(e) = copied {
+                drop(local);
+                let _ = fs::remove_file(&local_path);
+                return Err(e);
+            }
+
+            let md5 = format!("{:x}", hasher.finalize());
+            let checked = check_md5(&ssh, &remote_path, &md5, verify_md5.unwrap_or(false), expected_md5.as_deref())
+                .inspect_err(|_| transfer.set_verification(Some(md5.clone()), Some(false)))?;
+            transfer.set_verification(Some(md5), checked.then_some(true));
+            Ok(())
+        })();
+        transfer.finish(&result);
+    });
+    Ok(transfer_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn device_paths_have_a_filesystem_prefix() {
+        assert!(is_device_path("flash:c2960-lanbasek9-mz.150-2.SE11.bin"));
+        assert!(is_device_path("bootflash:/images/nxos.bin"));
+        assert!(is_device_path("disk0:"));
+        assert!(!is_device_path("/tmp/flash:image.bin"));
+        assert!(!is_device_path("images/flash:image.bin"));
+        assert!(!is_device_path(":image.bin"));
+        assert!(!is_device_path("/var/tmp/image.bin"));
+        assert!(!is_device_path("image.bin"));
+    }
+
+    #[test]
+    fn md5_command_per_path_kind() {
+        assert_eq!(remote_md5_command("flash:/ios.bin"), "verify /md5 flash:/ios.bin");
+        assert_eq!(remote_md5_command("/tmp/ios.bin"), "md5sum '/tmp/ios.bin'");
+        assert_eq!(remote_md5_command("/tmp/my image.bin"), "md5sum '/tmp/my image.bin'");
+        // A quote cannot end the quoted argument early
+        assert_eq!(remote_md5_command("/tmp/it's; rm -rf ~"), r"md5sum '/tmp/it'\''s; rm -rf ~'");
+    }
+
+    #[test]
+    fn md5_from_ios_verify_output() {
+        let output = "\
+.......................................................................Done!
+verify /md5 (flash:/c2960-lanbasek9-mz.150-2.SE11.bin) = 8A4BA7D1E6D4C2E2B1F0E9D8C7B6A594
+";
+        assert_eq!(parse_md5(output).as_deref(), Some("8a4ba7d1e6d4c2e2b1f0e9d8c7b6a594"));
+    }
+
+    #[test]
+    fn md5_from_md5sum_output() {
+        let output = "d41d8cd98f00b204e9800998ecf8427e  /tmp/empty\n";
+        assert_eq!(parse_md5(output).as_deref(), Some("d41d8cd98f00b204e9800998ecf8427e"));
+    }
+
+    #[test]
+    fn md5_missing_from_output() {
+        assert_eq!(parse_md5("md5sum: /tmp/missing: No such file or directory\n"), None);
+        assert_eq!(parse_md5("%Error opening flash:/missing.bin (No such file or directory)"), None);
+        // Longer hex runs (e.g. a SHA-256) are not an MD5
+        assert_eq!(parse_md5("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  file"), None);
+    }
+
+    #[test]
+    fn partial_upload_names_the_remote_file() {
+        assert_eq!(
+            partial_upload("Transfer cancelled", "flash:ios.bin", 1024, 4096),
+            "Transfer cancelled. flash:ios.bin on the remote side is incomplete (1024 of 4096 bytes) and should be deleted"
+        );
+    }
+}
@@ -0,0 +1,286 @@
+// Serial console sessions over a USB/RS-232 console cable, for out-of-band
+// access when the device is unreachable over the network. Output and input go
+// through the same session events and commands as SSH and Telnet.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serialport::{DataBits, FlowControl, Parity, SerialPort, SerialPortBuilder, StopBits};
+use tauri::{command, AppHandle, State};
+use tokio::sync::mpsc::{self, error::TryRecvError, Receiver};
+
+use crate::history::{self, Transport};
+use crate::session::{
+    emit_error, emit_output, emit_state, finish_session, SessionCommand, SessionHandle, SessionInfo, SessionKind,
+    SessionMap, SessionState,
+};
+use crate::ssh_client::IDLE_POLL;
+use crate::AppState;
+
+// Cisco/Juniper console default
+const DEFAULT_BAUD_RATE: u32 = 9600;
+
+// --- Types ---
+
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SerialParity {
+    None,
+    Odd,
+    Even,
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SerialFlowControl {
+    None,
+    Software, // XON/XOFF
+    Hardware, // RTS/CTS
+}
+
+// A port the OS reports, for the device picker
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerialPortEntry {
+    pub path: String,
+    pub description: Option<String>, // USB product name, when known
+}
+
+// --- Helpers ---
+
+fn data_bits(bits: u8) -> Result<DataBits, String> {
+    match bits {
+        5 => Ok(DataBits::Five),
+        6 => Ok(DataBits::Six),
+        7 => Ok(DataBits::Seven),
+        8 => Ok(DataBits::Eight),
+        _ => Err(format!("Unsupported data bits {} (expected 5-8)", bits)),
+    }
+}
+
+fn stop_bits(bits: u8) -> Result<StopBits, String> {
+    match bits {
+        1 => Ok(StopBits::One),
+        2 => Ok(StopBits::Two),
+        _ => Err(format!("Unsupported stop bits {} (expected 1 or 2)", bits)),
+    }
+}
+
+// Validates the line settings; the port is opened with `.open()`
+fn port_builder(
+    path: &str,
+    baud_rate: u32,
+    data_bits: u8,
+    parity: SerialParity,
+    stop_bits: u8,
+    flow_control: SerialFlowControl,
+) -> Result<SerialPortBuilder, String> {
+    Ok(serialport::new(path, baud_rate)
+        .data_bits(self::data_bits(data_bits)?)
+        .parity(match parity {
+            SerialParity::None => Parity::None,
+            SerialParity::Odd => Parity::Odd,
+            SerialParity::Even => Parity::Even,
+        })
+        .stop_bits(self::stop_bits(stop_bits)?)
+        .flow_control(match flow_control {
+            SerialFlowControl::None => FlowControl::None,
+            SerialFlowControl::Software => FlowControl::Software,
+            SerialFlowControl::Hardware => FlowControl::Hardware,
+        })
+        .timeout(IDLE_POLL))
+}
+
+// Holds the line in the break condition for `duration`
+fn send_break(port: &mut dyn SerialPort, duration: Duration) -> serialport::Result<()> {
+    port.set_break()?;
+    thread::sleep(duration);
+    port.clear_break()
+}
+
+// --- Serial I/O Loop ---
+
+// Owns the port: reads with a short timeout so queued commands are applied promptly
+fn spawn_serial_io(
+    app_handle: AppHandle,
+    session_id: String,
+    sessions: SessionMap,
+    mut port: Box<dyn SerialPort>,
+    mut command_receiver: Receiver<SessionCommand>,
+) {
+    thread::spawn(move || {
+        println!("[{}] Serial I/O thread started.", session_id);
+        let mut buffer = [0; 4096];
+        let mut exit_message = "Serial port closed.".to_string();
+
+        'io: loop {
+            loop {
+                match command_receiver.try_recv() {
+                    Ok(SessionCommand::Write(data)) => {
+                        if let Err(e) = port.write_all(&data).and_then(|()| port.flush()) {
+                            emit_error(&app_handle, &session_id, format!("Error writing to serial port: {}", e));
+                        }
+                    }
+                    // A console line has no window size
+                    Ok(SessionCommand::Resize { .. }) => {}
+                    Ok(SessionCommand::Break(duration)) => {
+                        println!("[{}] Sending BREAK for {:?}.", session_id, duration);
+                        if let Err(e) = send_break(port.as_mut(), duration) {
+                            emit_error(&app_handle, &session_id, format!("Failed to send BREAK: {}", e));
+                        }
+                    }
+                    Ok(SessionCommand::Disconnect) | Err(TryRecvError::Disconnected) => {
+                        println!("[{}] I/O thread received disconnect.", session_id);
+                        exit_message = "Serial port closed by user.".to_string();
+                        break 'io;
+                    }
+                    Err(TryRecvError::Empty) => break,
+                }
+            }
+
+            // Blocks for at most the port timeout
+            match port.read(&mut buffer) {
+                Ok(0) => {}
+                Ok(n) => emit_output(&app_handle, &session_id, &buffer[..n]),
+                Err(ref e) if e.kind() == io::ErrorKind::TimedOut || e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    // Typically the cable was unplugged
+                    emit_error(&app_handle, &session_id, format!("Error reading serial port: {}", e));
+                    break;
+                }
+            }
+        }
+
+        drop(port);
+        finish_session(&app_handle, &sessions, &session_id, SessionState::Closed, exit_message);
+        println!("[{}] Serial I/O thread finished.", session_id);
+    });
+}
+
+// --- Tauri Commands ---
+
+#[command]
+pub async fn list_serial_ports() -> Result<Vec<SerialPortEntry>, String> {
+    let ports = serialport::available_ports().map_err(|e| format!("Failed to list serial ports: {}", e))?;
+    Ok(ports
+        .into_iter()
+        .map(|port| SerialPortEntry {
+            path: port.port_name,
+            description: match port.port_type {
+                serialport::SerialPortType::UsbPort(usb) => usb.product,
+                _ => None,
+            },
+        })
+        .collect())
+}
+
+// Opens a console session on a serial device (e.g. /dev/ttyUSB0, COM3).
+// Defaults to 9600 8N1 without flow control.
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn serial_connect(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    path: String,
+    baud_rate: Option<u32>,
+    data_bits: Option<u8>,
+    parity: Option<SerialParity>,
+    stop_bits: Option<u8>,
+    flow_control: Option<SerialFlowControl>,
+) -> Result<String, String> {
+    let baud_rate = baud_rate.unwrap_or(DEFAULT_BAUD_RATE);
+    println!("Attempting serial connection to {} at {} baud", path, baud_rate);
+
+    let (data_bits, stop_bits) = (data_bits.unwrap_or(8), stop_bits.unwrap_or(1));
+    let (parity, flow_control) = (parity.unwrap_or(SerialParity::None), flow_control.unwrap_or(SerialFlowControl::None));
+    let builder = port_builder(&path, baud_rate, data_bits, parity, stop_bits, flow_control)?;
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let transport = Transport::Serial { baud_rate, data_bits, parity, stop_bits, flow_control };
+    history::record_attempt(&app_handle, &session_id, SessionKind::Serial, &path, 0, "", transport);
+    let port = builder
+        .open()
+        .map_err(|e| format!("Failed to open serial port {}: {}", path, e))
+        .inspect_err(|e| history::record_failure(&app_handle, &session_id, e))?;
+    println!("[{}] Opened serial port {}", session_id, path);
+
+    let (command_tx, command_rx) = mpsc::channel::<SessionCommand>(32);
+    {
+        let mut sessions_guard = state.sessions.lock().map_err(|_| "Failed to lock state mutex".to_string())?;
+        sessions_guard.insert(session_id.clone(), SessionHandle {
+            command_sender: command_tx,
+            ssh: None,
+            forwards: HashMap::new(),
+            info: SessionInfo {
+                id: session_id.clone(),
+                kind: SessionKind::Serial,
+                hostname: path, // The device path stands in for the host
+                port: 0,
+                username: String::new(),
+                connected_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+                latency_ms: None,
+            },
+        });
+    }
+
+    history::record_connected(&app_handle, &session_id);
+    emit_state(&app_handle, &session_id, SessionState::Connected, None, None);
+    spawn_serial_io(app_handle, session_id.clone(), state.sessions.clone(), port, command_rx);
+    Ok(session_id)
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use portable_pty::{native_pty_system, PtySize};
+
+    #[test]
+    fn rejects_unsupported_line_settings() {
+        assert!(port_builder("/dev/null", 9600, 9, SerialParity::None, 1, SerialFlowControl::None).is_err());
+        assert!(port_builder("/dev/null", 9600, 8, SerialParity::None, 3, SerialFlowControl::None).is_err());
+    }
+
+    // The pty slave stands in for the console cable; the master is the device
+    #[test]
+    fn pty_write_output_and_break() {
+        let pair = native_pty_system().openpty(PtySize::default()).unwrap();
+        let path = pair.master.tty_name().expect("pty has a slave path");
+        let mut port = port_builder(&path.to_string_lossy(), 115_200, 8, SerialParity::None, 1, SerialFlowControl::None)
+            .unwrap()
+            .open()
+            .unwrap();
+        let mut device_in = pair.master.try_clone_reader().unwrap();
+        let mut device_out = pair.master.take_writer().unwrap();
+
+        port.write_all(b"show version\r").unwrap();
+        port.flush().unwrap();
+        let mut received = [0; 13];
+        device_in.read_exact(&mut received).unwrap();
+        assert_eq!(&received, b"show version\r");
+
+        device_out.write_all(b"Router>").unwrap();
+        device_out.flush().unwrap();
+        let mut output = Vec::new();
+        let mut buffer = [0; 64];
+        for _ in 0..100 {
+            match port.read(&mut buffer) {
+                Ok(n) => output.extend_from_slice(&buffer[..n]),
+                Err(e) if e.kind() == io::ErrorKind::TimedOut => {}
+                Err(e) => panic!("read failed: {}", e),
+            }
+            if output.len() >= 7 {
+                break;
+            }
+        }
+        assert_eq!(output, b"Router>");
+
+        send_break(port.as_mut(), Duration::from_millis(10)).unwrap();
+        // The line still carries data after the break is cleared
+        port.write_all(b"\r").unwrap();
+        let mut received = [0; 1];
+        device_in.read_exact(&mut received).unwrap();
+        assert_eq!(&received, b"\r");
+    }
+}
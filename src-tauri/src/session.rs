@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::{command, AppHandle, Emitter, Manager, State};
+use tokio::sync::mpsc::Sender;
+
+use crate::forwarding::ForwardHandle;
+use crate::history;
+use crate::AppState;
+
+// --- Communication Messages ---
+// Sent from the Tauri commands to the I/O loop that owns a session's channel
+#[derive(Debug)]
+pub enum SessionCommand {
+    Write(Vec<u8>),
+    Resize { cols: u16, rows: u16 },
+    Break(Duration), // Serial line BREAK (Telnet sends IAC BRK instead)
+    Disconnect,
+}
+
+// Terminal size used when the frontend does not send one with `ssh_connect`
+pub const DEFAULT_PTY_COLS: u16 = 80;
+pub const DEFAULT_PTY_ROWS: u16 = 24;
+// How long a BREAK is held when the frontend does not say; ROMMON wants at least ~250ms
+const DEFAULT_BREAK_MS: u64 = 500;
+
+// --- Event Payloads --- Keep these as they define the frontend contract
+// Every payload carries the id of the session it belongs to, so the frontend
+// can route output to the right terminal when several devices are open.
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshOutputPayload {
+    pub session_id: String,
+    pub data: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshErrorPayload {
+    pub session_id: String,
+    pub message: String,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshClosedPayload {
+    pub session_id: String,
+    pub message: String,
+}
+
+// Lifecycle of a session, reported through `session-state`
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionState {
+    Connecting,
+    Connected,
+    Reconnecting,
+    Closed, // Ended normally or by the user
+    Failed, // Lost and not (or no longer) reconnecting
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStatePayload {
+    pub session_id: String,
+    pub state: SessionState,
+    pub attempt: Option<u32>, // Current reconnect attempt, while reconnecting
+    pub message: Option<String>,
+}
+
+// Transport a session runs over
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionKind {
+    Ssh,
+    Telnet,
+    Serial,
+    Local,
+}
+
+// Summary of an open session, returned by `list_sessions`
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    pub id: String,
+    pub kind: SessionKind,
+    pub hostname: String,
+    pub port: u16,
+    pub username: String,
+    pub connected_at: u64, // Unix timestamp (seconds)
+    pub latency_ms: Option<u64>, // Last measured round trip (SSH keepalive probes)
+}
+
+// --- State Management ---
+
+// Everything the commands need to reach a running session
+pub struct SessionHandle {
+    pub command_sender: Sender<SessionCommand>, // To send write/resize/disconnect commands
+    pub ssh: Option<ssh2::Session>, // Shared SSH transport for extra channels (exec, SFTP, ...); None for Telnet
+    pub forwards: HashMap<String, ForwardHandle>, // Port forwards by id; dropped (stopped) with the session
+    pub info: SessionInfo,
+}
+
+// All open sessions, keyed by the session id handed back from `ssh_connect`
+pub type SessionMap = Arc<Mutex<HashMap<String, SessionHandle>>>;
+
+// --- Helper function to emit events ---
+pub fn emit_event<P: serde::Serialize + Clone>(app_handle: &AppHandle, event: &str, payload: P) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        if let Err(e) = window.emit(event, payload) {
+            eprintln!("Failed to emit event '{}': {}", event, e);
+        }
+    } else {
+         eprintln!("Event emit failed: Main window not found for event '{}'.", event);
+    }
+}
+
+// Forwards terminal output to the frontend as `ssh-output`, counting it for the history
+pub fn emit_output(app_handle: &AppHandle, session_id: &str, data: &[u8]) {
+    history::count_in(app_handle, session_id, data.len());
+    // Attempt to convert to UTF-8. Handle invalid sequences gracefully.
+    let data_str = String::from_utf8_lossy(data).to_string();
+    emit_event(app_handle, "ssh-output", SshOutputPayload { session_id: session_id.to_string(), data: data_str });
+}
+
+// Logs an error and forwards it to the frontend as `ssh-error`
+pub fn emit_error(app_handle: &AppHandle, session_id: &str, message: String) {
+    eprintln!("[{}] {}", session_id, message);
+    emit_event(app_handle, "ssh-error", SshErrorPayload { session_id: session_id.to_string(), message });
+}
+
+pub fn emit_state(app_handle: &AppHandle, session_id: &str, state: SessionState, attempt: Option<u32>, message: Option<String>) {
+    println!("[{}] Session state: {:?}", session_id, state);
+    emit_event(app_handle, "session-state", SessionStatePayload { session_id: session_id.to_string(), state, attempt, message });
+}
+
+// Looks up the command channel of a session without holding the lock across an await
+pub fn session_sender(sessions: &SessionMap, session_id: &str) -> Result<Option<Sender<SessionCommand>>, String> {
+    let guard = sessions.lock().map_err(|_| "Failed to lock state mutex".to_string())?;
+    Ok(guard.get(session_id).map(|handle| handle.command_sender.clone()))
+}
+
+// Looks up the SSH transport of a session so another channel can be opened on it
+pub fn session_transport(sessions: &SessionMap, session_id: &str) -> Result<ssh2::Session, String> {
+    let guard = sessions.lock().map_err(|_| "Failed to lock state mutex".to_string())?;
+    let handle = guard.get(session_id).ok_or_else(|| format!("Not connected: unknown session {}", session_id))?;
+    handle
+        .ssh
+        .clone()
+        .ok_or_else(|| format!("Session {} is a {:?} session; this needs SSH", session_id, handle.info.kind))
+}
+
+// Reports the end of a session to the frontend and drops it from the registry.
+// Called by the I/O thread that owned the session.
+pub fn finish_session(app_handle: &AppHandle, sessions: &SessionMap, session_id: &str, state: SessionState, message: String) {
+    println!("[{}] {}", session_id, message);
+    emit_event(app_handle, "ssh-closed", SshClosedPayload { session_id: session_id.to_string(), message: message.clone() });
+    emit_state(app_handle, session_id, state, None, Some(message.clone()));
+    history::record_end(app_handle, session_id, state, &message);
+
+    if let Ok(mut guard) = sessions.lock() {
+        if guard.remove(session_id).is_some() {
+            println!("[{}] Session removed from state by I/O thread.", session_id);
+        }
+    }
+}
+
+// --- Tauri Commands ---
+
+#[command]
+pub async fn write_to_ssh(app_handle: AppHandle, state: State<'_, AppState>, session_id: String, data: String) -> Result<(), String> {
+    if let Some(sender) = session_sender(&state.sessions, &session_id)? {
+        history::count_out(&app_handle, &session_id, data.len());
+        sender
+            .send(SessionCommand::Write(data.into_bytes()))
+            .await
+            .map_err(|e| format!("Failed to send write command: {}", e))
+    } else {
+        Err(format!("Not connected: unknown session {}", session_id))
+    }
+}
+
+#[command]
+pub async fn resize_pty(state: State<'_, AppState>, session_id: String, cols: u16, rows: u16) -> Result<(), String> {
+    if cols == 0 || rows == 0 {
+        return Err(format!("Invalid terminal size {}x{}", cols, rows));
+    }
+    if let Some(sender) = session_sender(&state.sessions, &session_id)? {
+        sender
+            .send(SessionCommand::Resize { cols, rows })
+            .await
+            .map_err(|e| format!("Failed to send resize command: {}", e))
+    } else {
+        Err(format!("Not connected: unknown session {}", session_id))
+    }
+}
+
+// Sends a BREAK, e.g. to drop a router into ROMMON for password recovery
+#[command]
+pub async fn send_break(state: State<'_, AppState>, session_id: String, duration_ms: Option<u64>) -> Result<(), String> {
+    let duration = Duration::from_millis(duration_ms.unwrap_or(DEFAULT_BREAK_MS));
+    if let Some(sender) = session_sender(&state.sessions, &session_id)? {
+        sender
+            .send(SessionCommand::Break(duration))
+            .await
+            .map_err(|e| format!("Failed to send break command: {}", e))
+    } else {
+        Err(format!("Not connected: unknown session {}", session_id))
+    }
+}
+
+#[command]
+pub async fn disconnect_ssh(state: State<'_, AppState>, session_id: String) -> Result<(), String> {
+    println!("[{}] Disconnect command received.", session_id);
+    disconnect_session(&state.sessions, &session_id).await
+}
+
+// Internal disconnect logic
+pub async fn disconnect_session(sessions: &SessionMap, session_id: &str) -> Result<(), String> {
+    println!("[{}] Attempting to disconnect session...", session_id);
+
+    let command_sender = {
+        let mut guard = sessions.lock().map_err(|_| "Failed to lock state mutex for disconnect".to_string())?;
+        guard.remove(session_id).map(|handle| handle.command_sender)
+    };
+
+    if let Some(sender) = command_sender {
+        println!("[{}] Found active session. Sending disconnect command...", session_id);
+        let _ = sender.send(SessionCommand::Disconnect).await;
+        println!("[{}] Disconnect command sent to I/O loop.", session_id);
+    } else {
+        println!("[{}] No active session found to disconnect.", session_id);
+    }
+
+    Ok(())
+}
+
+#[command]
+pub async fn list_sessions(state: State<'_, AppState>) -> Result<Vec<SessionInfo>, String> {
+    let guard = state.sessions.lock().map_err(|_| "Failed to lock state mutex".to_string())?;
+    let mut sessions: Vec<SessionInfo> = guard.values().map(|handle| handle.info.clone()).collect();
+    sessions.sort_by_key(|info| info.connected_at);
+    Ok(sessions)
+}
+
+// --- AI Interaction Command ---
+
+#[command]
+pub async fn ai_write_to_ssh(app_handle: AppHandle, state: State<'_, AppState>, session_id: String, data: String) -> Result<(), String> {
+    println!("[{}] AI attempting to write to SSH: {:?}", session_id, data); // Log AI writes
+    if let Some(sender) = session_sender(&state.sessions, &session_id)? {
+        history::count_out(&app_handle, &session_id, data.len());
+        sender
+            .send(SessionCommand::Write(data.into_bytes()))
+            .await
+            .map_err(|e| format!("AI failed to send write command: {}", e))
+    } else {
+        Err(format!("AI write failed: Not connected (unknown session {})", session_id))
+    }
+}
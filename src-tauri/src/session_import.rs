@@ -0,0 +1,829 @@
+// Importers for sessions saved by Windows terminal tools: PuTTY registry
+// exports (.reg), SecureCRT session folders (.ini files) or XML exports, and
+// MobaXterm .mxtsessions files. Folders become tags ("Site", "Site/Core"), and
+// sessions using a protocol profiles cannot open are skipped with a reason.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tauri::{command, AppHandle};
+
+use crate::import::{self, ImportReport, SkippedEntry, UnsupportedLog};
+use crate::keys::expand_home;
+use crate::local_shell::local_username;
+use crate::profiles::{AuthMethod, Profile, ProfileJumpHost, ProfileProtocol};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SessionFormat {
+    Putty,     // .reg export of HKCU\Software\SimonTatham\PuTTY\Sessions (KiTTY works too)
+    SecureCrt, // Config/Sessions folder of .ini files, or an XML settings export
+    MobaXterm, // .mxtsessions export (or the [Bookmarks] sections of MobaXterm.ini)
+}
+
+impl SessionFormat {
+    fn tag(self) -> &'static str {
+        match self {
+            SessionFormat::Putty => "putty",
+            SessionFormat::SecureCrt => "securecrt",
+            SessionFormat::MobaXterm => "mobaxterm",
+        }
+    }
+}
+
+// One session read from an export, before it becomes a profile
+struct ImportedSession {
+    name: String,
+    folders: Vec<String>,
+    protocol: ProfileProtocol,
+    hostname: String,
+    port: Option<u16>,
+    username: String,
+    private_key_path: Option<String>,
+    certificate_path: Option<String>,
+    jump_host: Option<ProfileJumpHost>,
+}
+
+// Sessions and skipped entries collected by one parser run
+#[derive(Default)]
+struct Collected {
+    sessions: Vec<ImportedSession>,
+    skipped: Vec<SkippedEntry>,
+    unsupported: UnsupportedLog,
+}
+
+impl Collected {
+    fn skip(&mut self, name: &str, reason: String) {
+        self.skipped.push(SkippedEntry { name: name.to_string(), reason });
+    }
+}
+
+// --- Shared helpers ---
+
+// A named value as stored by PuTTY (registry) and SecureCRT (ini/xml)
+enum Value {
+    Str(String),
+    Dword(u32),
+}
+
+type Values = HashMap<String, Value>;
+
+fn string<'a>(values: &'a Values, name: &str) -> Option<&'a str> {
+    match values.get(name) {
+        Some(Value::Str(value)) if !value.trim().is_empty() => Some(value.trim()),
+        _ => None,
+    }
+}
+
+fn dword(values: &Values, name: &str) -> Option<u32> {
+    match values.get(name) {
+        Some(Value::Dword(value)) => Some(*value),
+        _ => None,
+    }
+}
+
+// Exports from Windows tools come as UTF-16 (regedit) or UTF-8/ANSI text
+fn read_text(path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let utf16 = |bytes: &[u8], little_endian: bool| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| if little_endian { u16::from_le_bytes([pair[0], pair[1]]) } else { u16::from_be_bytes([pair[0], pair[1]]) })
+            .collect();
+        String::from_utf16_lossy(&units)
+    };
+    Ok(match bytes.as_slice() {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, true),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, false),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).to_string(),
+        _ => String::from_utf8_lossy(&bytes).to_string(),
+    })
+}
+
+// Splits "user@host", which PuTTY and MobaXterm both accept in the host field
+fn split_user(host: &str) -> (Option<&str>, &str) {
+    match host.rsplit_once('@') {
+        Some((user, host)) => (Some(user), host),
+        None => (None, host),
+    }
+}
+
+fn to_profiles(sessions: Vec<ImportedSession>, format: SessionFormat) -> Vec<Profile> {
+    let mut name_counts: HashMap<String, usize> = HashMap::new();
+    for session in &sessions {
+        *name_counts.entry(session.name.clone()).or_default() += 1;
+    }
+    sessions
+        .into_iter()
+        .map(|session| {
+            // The same session name in two folders gets the folder path as a prefix
+            let name = if name_counts[&session.name] > 1 && !session.folders.is_empty() {
+                format!("{}/{}", session.folders.join("/"), session.name)
+            } else {
+                session.name
+            };
+            let mut tags = vec![format.tag().to_string()];
+            for depth in 1..=session.folders.len() {
+                tags.push(session.folders[..depth].join("/"));
+            }
+            let username = match (&session.protocol, session.username.is_empty()) {
+                (ProfileProtocol::Ssh, true) => local_username(),
+                _ => session.username,
+            };
+            Profile {
+                id: String::new(),
+                name,
+                port: session.port.unwrap_or(match session.protocol {
+                    ProfileProtocol::Ssh => 22,
+                    ProfileProtocol::Telnet => 23,
+                }),
+                protocol: session.protocol,
+                hostname: session.hostname,
+                username,
+                auth_method: if session.private_key_path.is_some() { AuthMethod::Key } else { AuthMethod::Agent },
+                private_key_path: session.private_key_path,
+                certificate_path: session.certificate_path,
+                password_secret_id: None,
+                passphrase_secret_id: None,
+                jump_hosts: session.jump_host.into_iter().collect(),
+                tags,
+                metadata: BTreeMap::new(),
+                post_login_commands: Vec::new(),
+                algorithms: Default::default(),
+                reconnect: None,
+                keepalive: None,
+                timeout_secs: None,
+                source: None,
+                created_at: 0,
+                updated_at: 0,
+            }
+        })
+        .collect()
+}
+
+// --- PuTTY ---
+
+// Reads a quoted .reg string starting at `text`; returns it unescaped and the rest of the line
+fn reg_string(text: &str) -> Option<(String, &str)> {
+    let mut chars = text.strip_prefix('"')?.char_indices();
+    let mut value = String::new();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => value.push(chars.next()?.1),
+            '"' => return Some((value, &text[index + 2..])),
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+// PuTTY stores session names %XX-escaped in the key name
+fn unescape_putty(name: &str) -> String {
+    let bytes = name.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| name.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn putty_session(name: String, values: &Values, location: &str, collected: &mut Collected) {
+    if name == "Default Settings" {
+        return;
+    }
+    let protocol = match string(values, "Protocol").unwrap_or("ssh") {
+        "ssh" => ProfileProtocol::Ssh,
+        "telnet" => ProfileProtocol::Telnet,
+        other => return collected.skip(&name, format!("Unsupported protocol {}", other)),
+    };
+    let Some(host) = string(values, "HostName") else {
+        return collected.skip(&name, "No host name".to_string());
+    };
+    let (user, hostname) = split_user(host);
+    if string(values, "ProxyHost").is_some() && dword(values, "ProxyMethod").unwrap_or(0) != 0 {
+        collected.unsupported.note("ProxyMethod", location.to_string());
+    }
+    for setting in ["PortForwardings", "RemoteCommand"] {
+        if string(values, setting).is_some() {
+            collected.unsupported.note(setting, location.to_string());
+        }
+    }
+
+    // PuTTY has no folders; session managers built on it write "Folder/Name"
+    let mut folders: Vec<String> = name.split('/').map(str::to_string).collect();
+    let name = folders.pop().unwrap_or_default();
+    collected.sessions.push(ImportedSession {
+        name,
+        folders,
+        protocol,
+        hostname: hostname.to_string(),
+        port: dword(values, "PortNumber").and_then(|port| u16::try_from(port).ok()).filter(|port| *port != 0),
+        username: user.or(string(values, "UserName")).unwrap_or_default().to_string(),
+        private_key_path: string(values, "PublicKeyFile").map(str::to_string),
+        certificate_path: string(values, "DetachedCertificate").map(str::to_string),
+        jump_host: None,
+    });
+}
+
+fn parse_putty(path: &Path, collected: &mut Collected) -> Result<(), String> {
+    let text = read_text(path)?;
+    if !text.starts_with("Windows Registry Editor") && !text.starts_with("REGEDIT4") {
+        return Err(format!("{} is not a registry export", path.display()));
+    }
+    let mut current: Option<(String, Values)> = None;
+    for line in text.lines().map(str::trim) {
+        if let Some(key) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            if let Some((name, values)) = current.take() {
+                let location = format!("{} [{}]", path.display(), name);
+                putty_session(name, &values, &location, collected);
+            }
+            // Only direct children of ...\Sessions are sessions. ASCII-only
+            // lowercasing keeps byte offsets valid for slicing `key`.
+            let lower = key.to_ascii_lowercase();
+            current = lower
+                .rfind("\\sessions\\")
+                .map(|start| &key[start + "\\sessions\\".len()..])
+                .filter(|name| !name.is_empty() && !name.contains('\\') && !key.starts_with('-'))
+                .map(|name| (unescape_putty(name), Values::new()));
+            continue;
+        }
+        let Some((_, values)) = current.as_mut() else {
+            continue;
+        };
+        let Some((name, rest)) = reg_string(line) else {
+            continue;
+        };
+        let Some(rest) = rest.trim_start().strip_prefix('=') else {
+            continue;
+        };
+        let value = if let Some(hex) = rest.strip_prefix("dword:") {
+            u32::from_str_radix(hex.trim(), 16).ok().map(Value::Dword)
+        } else {
+            reg_string(rest).map(|(value, _)| Value::Str(value))
+        };
+        // hex: and other binary values are not used by sessions we import
+        if let Some(value) = value {
+            values.insert(name, value);
+        }
+    }
+    if let Some((name, values)) = current {
+        let location = format!("{} [{}]", path.display(), name);
+        putty_session(name, &values, &location, collected);
+    }
+    Ok(())
+}
+
+// --- SecureCRT ---
+
+fn securecrt_session(name: String, folders: Vec<String>, values: &Values, location: &str, collected: &mut Collected) {
+    let (protocol, port_key) = match string(values, "Protocol Name").unwrap_or("SSH2") {
+        "SSH2" => (ProfileProtocol::Ssh, "[SSH2] Port"),
+        "Telnet" => (ProfileProtocol::Telnet, "Port"),
+        other => return collected.skip(&name, format!("Unsupported protocol {}", other)),
+    };
+    let Some(hostname) = string(values, "Hostname") else {
+        return collected.skip(&name, "No host name".to_string());
+    };
+    if string(values, "Firewall Name").is_some_and(|firewall| firewall != "None") {
+        collected.unsupported.note("Firewall Name", location.to_string());
+    }
+    // "Identity Filename V2" appends "::rawkey" or similar to the path
+    let identity = string(values, "Identity Filename V2")
+        .or(string(values, "Identity Filename"))
+        .map(|path| path.split("::").next().unwrap_or(path).to_string())
+        .filter(|path| !path.is_empty());
+    let use_identity = dword(values, "Use Global Public Key").unwrap_or(0) == 0;
+    collected.sessions.push(ImportedSession {
+        name,
+        folders,
+        protocol,
+        hostname: hostname.to_string(),
+        port: dword(values, port_key).and_then(|port| u16::try_from(port).ok()).filter(|port| *port != 0),
+        username: string(values, "Username").unwrap_or_default().to_string(),
+        private_key_path: identity.filter(|_| use_identity),
+        certificate_path: None,
+        jump_host: None,
+    });
+}
+
+// Session .ini files hold lines like `S:"Hostname"=host` and `D:"[SSH2] Port"=00000016`
+fn securecrt_ini_values(text: &str) -> Values {
+    let mut values = Values::new();
+    for line in text.lines().map(str::trim) {
+        let Some((kind, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let Some((name, rest)) = reg_string(rest) else {
+            continue;
+        };
+        let Some(value) = rest.strip_prefix('=') else {
+            continue;
+        };
+        match kind {
+            "S" => {
+                values.insert(name, Value::Str(value.to_string()));
+            }
+            "D" => {
+                if let Ok(value) = u32::from_str_radix(value.trim(), 16) {
+                    values.insert(name, Value::Dword(value));
+                }
+            }
+            // Z: (string lists) and B: (binary) span several lines and are not needed
+            _ => {}
+        }
+    }
+    values
+}
+
+fn is_securecrt_metadata(file_name: &str) -> bool {
+    file_name.eq_ignore_ascii_case("__FolderData__.ini") || file_name.eq_ignore_ascii_case("Default.ini")
+}
+
+fn parse_securecrt_dir(root: &Path, dir: &Path, collected: &mut Collected) -> Result<(), String> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    entries.sort();
+    for entry in entries {
+        if entry.is_dir() {
+            parse_securecrt_dir(root, &entry, collected)?;
+            continue;
+        }
+        let file_name = entry.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let is_ini = entry.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("ini"));
+        if !is_ini || is_securecrt_metadata(&file_name) {
+            continue;
+        }
+        let folders = entry
+            .parent()
+            .and_then(|parent| parent.strip_prefix(root).ok())
+            .map(|relative| relative.components().map(|part| part.as_os_str().to_string_lossy().to_string()).collect())
+            .unwrap_or_default();
+        let name = entry.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+        let values = securecrt_ini_values(&read_text(&entry)?);
+        securecrt_session(name, folders, &values, &entry.display().to_string(), collected);
+    }
+    Ok(())
+}
+
+fn securecrt_xml_key(node: roxmltree::Node, folders: &[String], file: &Path, collected: &mut Collected) {
+    for key in node.children().filter(|child| child.has_tag_name("key")) {
+        let name = key.attribute("name").unwrap_or_default().to_string();
+        let mut values = Values::new();
+        for value in key.children().filter(roxmltree::Node::is_element) {
+            let (Some(value_name), text) = (value.attribute("name"), value.text().unwrap_or_default()) else {
+                continue;
+            };
+            match value.tag_name().name() {
+                "string" => {
+                    values.insert(value_name.to_string(), Value::Str(text.to_string()));
+                }
+                "dword" => {
+                    if let Ok(number) = text.trim().parse() {
+                        values.insert(value_name.to_string(), Value::Dword(number));
+                    }
+                }
+                _ => {}
+            }
+        }
+        // Keys holding connection settings are sessions; the rest are folders
+        if values.contains_key("Hostname") || values.contains_key("Protocol Name") {
+            if folders.is_empty() && name == "Default" {
+                continue;
+            }
+            let location = format!("{} [{}]", file.display(), name);
+            securecrt_session(name, folders.to_vec(), &values, &location, collected);
+        } else if name != "__FolderData__" {
+            let mut nested = folders.to_vec();
+            nested.push(name);
+            securecrt_xml_key(key, &nested, file, collected);
+        }
+    }
+}
+
+fn parse_securecrt(path: &Path, collected: &mut Collected) -> Result<(), String> {
+    if path.is_dir() {
+        return parse_securecrt_dir(path, path, collected);
+    }
+    let is_xml = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("xml"));
+    if !is_xml {
+        // A single session file
+        let name = path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default();
+        let values = securecrt_ini_values(&read_text(path)?);
+        securecrt_session(name, Vec::new(), &values, &path.display().to_string(), collected);
+        return Ok(());
+    }
+    let text = read_text(path)?;
+    let document = roxmltree::Document::parse(&text).map_err(|e| format!("Invalid XML in {}: {}", path.display(), e))?;
+    let sessions = document
+        .descendants()
+        .find(|node| node.has_tag_name("key") && node.attribute("name") == Some("Sessions"))
+        .ok_or_else(|| format!("{} has no Sessions key", path.display()))?;
+    securecrt_xml_key(sessions, &[], path, collected);
+    Ok(())
+}
+
+// --- MobaXterm ---
+
+// Characters MobaXterm cannot store literally in session lines
+fn unescape_moba(text: &str) -> String {
+    text.replace("__PIPE__", "|").replace("__DIEZE__", "#").replace("__PTVIRG__", ";").replace("__PERCENT__", "%")
+}
+
+// A session line is `name= #icon#type%host%port%user%...#font settings#...`.
+// The fields used here: 1 host, 2 port, 3 user, 8-10 SSH gateway host/port/user
+// and 14 the private key.
+fn moba_session(name: &str, value: &str, folders: &[String], location: &str, collected: &mut Collected) {
+    let name = unescape_moba(name);
+    let settings = value.trim().split('#').nth(2).unwrap_or_default();
+    let fields: Vec<String> = settings.split('%').map(unescape_moba).collect();
+    let field = |index: usize| fields.get(index).map(|field| field.trim()).filter(|field| !field.is_empty());
+    let protocol = match field(0) {
+        Some("0") => ProfileProtocol::Ssh,
+        Some("1") => ProfileProtocol::Telnet,
+        other => return collected.skip(&name, format!("Unsupported MobaXterm session type {}", other.unwrap_or("?"))),
+    };
+    let Some(host) = field(1) else {
+        return collected.skip(&name, "No host name".to_string());
+    };
+    let (user, hostname) = split_user(host);
+    let port = |index: usize| field(index).and_then(|port| port.parse::<u16>().ok()).filter(|port| *port != 0);
+
+    let mut jump_host = None;
+    if protocol == ProfileProtocol::Ssh {
+        if let Some(gateway) = field(8) {
+            // Newer versions allow a chain of gateways, separated by "|"
+            if gateway.contains('|') {
+                collected.unsupported.note("Multiple SSH gateways", location.to_string());
+            }
+            let gateway = gateway.split('|').next().unwrap_or(gateway);
+            let (gateway_user, gateway_host) = split_user(gateway);
+            jump_host = Some(ProfileJumpHost {
+                hostname: gateway_host.to_string(),
+                port: port(9).unwrap_or(22),
+                username: gateway_user.or(field(10)).map(str::to_string).unwrap_or_else(local_username),
+                private_key_path: None,
+                certificate_path: None,
+                password_secret_id: None,
+                passphrase_secret_id: None,
+                algorithms: Default::default(),
+            });
+        }
+    }
+    collected.sessions.push(ImportedSession {
+        name,
+        folders: folders.to_vec(),
+        hostname: hostname.to_string(),
+        port: port(2),
+        username: user.or(field(3)).unwrap_or_default().to_string(),
+        private_key_path: field(14).filter(|_| protocol == ProfileProtocol::Ssh).map(str::to_string),
+        certificate_path: None,
+        protocol,
+        jump_host,
+    });
+}
+
+fn parse_mobaxterm(path: &Path, collected: &mut Collected) -> Result<(), String> {
+    let text = read_text(path)?;
+    let mut in_bookmarks = false;
+    let mut folders: Vec<String> = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim_end();
+        if let Some(section) = line.trim().strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            // [Bookmarks], [Bookmarks_1], ... each hold one folder
+            in_bookmarks = section.to_lowercase().starts_with("bookmarks");
+            folders.clear();
+            continue;
+        }
+        if !in_bookmarks {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match key.trim() {
+            "SubRep" => {
+                folders = value.split('\\').filter(|part| !part.is_empty()).map(unescape_moba).collect();
+            }
+            "ImgNum" => {}
+            name if value.trim_start().starts_with('#') => {
+                let location = format!("{}:{}", path.display(), index + 1);
+                moba_session(name, value, &folders, &location, collected);
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// --- Tauri Commands ---
+
+// Imports the sessions saved in a PuTTY, SecureCRT or MobaXterm export as
+// profiles. With `dryRun` nothing is saved and the report lists what would be
+// created or updated.
+#[command]
+pub async fn import_sessions(
+    app_handle: AppHandle,
+    format: SessionFormat,
+    path: String,
+    dry_run: Option<bool>,
+) -> Result<ImportReport, String> {
+    let path = expand_home(path.trim());
+    if !path.exists() {
+        return Err(format!("{} does not exist", path.display()));
+    }
+    println!("Importing {} sessions from {}", format.tag(), path.display());
+
+    let mut collected = Collected::default();
+    match format {
+        SessionFormat::Putty => parse_putty(&path, &mut collected)?,
+        SessionFormat::SecureCrt => parse_securecrt(&path, &mut collected)?,
+        SessionFormat::MobaXterm => parse_mobaxterm(&path, &mut collected)?,
+    }
+
+    let source = format!("{}:{}", format.tag(), path.display());
+    let candidates = to_profiles(collected.sessions, format);
+    import::apply(&app_handle, source, candidates, collected.skipped, collected.unsupported, dry_run.unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("termai-session-import-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // Runs a parser and returns the dry-run report against an empty profile store
+    fn import(format: SessionFormat, path: &Path) -> ImportReport {
+        let mut collected = Collected::default();
+        match format {
+            SessionFormat::Putty => parse_putty(path, &mut collected).unwrap(),
+            SessionFormat::SecureCrt => parse_securecrt(path, &mut collected).unwrap(),
+            SessionFormat::MobaXterm => parse_mobaxterm(path, &mut collected).unwrap(),
+        }
+        let candidates = to_profiles(collected.sessions, format);
+        import::preview(Vec::new(), format.tag().to_string(), candidates, collected.skipped, collected.unsupported, false)
+    }
+
+    fn created<'a>(report: &'a ImportReport, name: &str) -> &'a Profile {
+        report.created.iter().find(|profile| profile.name == name).unwrap_or_else(|| panic!("{} was not created", name))
+    }
+
+    fn skipped(report: &ImportReport) -> Vec<(&str, &str)> {
+        report.skipped.iter().map(|entry| (entry.name.as_str(), entry.reason.as_str())).collect()
+    }
+
+    fn unsupported(report: &ImportReport) -> Vec<(&str, usize)> {
+        report.unsupported.iter().map(|item| (item.directive.as_str(), item.count)).collect()
+    }
+
+    const PUTTY_REG: &str = r#"Windows Registry Editor Version 5.00
+
+[HKEY_CURRENT_USER\Software\SimonTatham\PuTTY\Sessions]
+
+[HKEY_CURRENT_USER\Software\SimonTatham\PuTTY\Sessions\Core%2Frtr%20one]
+"HostName"="admin@10.0.0.1"
+"PortNumber"=dword:00000822
+"Protocol"="ssh"
+"PublicKeyFile"="C:\\Users\\me\\rtr.ppk"
+"PortForwardings"="L8080=localhost:80"
+
+[HKEY_CURRENT_USER\Software\SimonTatham\PuTTY\Sessions\switch]
+"HostName"="10.0.0.2"
+"PortNumber"=dword:00000017
+"Protocol"="telnet"
+"UserName"="ops"
+
+[HKEY_CURRENT_USER\Software\SimonTatham\PuTTY\Sessions\console]
+"HostName"="COM1"
+"Protocol"="serial"
+
+[HKEY_CURRENT_USER\Software\SimonTatham\PuTTY\Sessions\switch\Nested]
+"HostName"="10.0.0.99"
+
+[HKEY_CURRENT_USER\Software\SimonTatham\PuTTY\Sessions\Default%20Settings]
+"HostName"=""
+
+[HKEY_CURRENT_USER\Software\İstanbul\PuTTY\SESSIONS\edge]
+"HostName"="edge@10.0.0.3"
+"#;
+
+    #[test]
+    fn putty_utf16_registry_export() {
+        let dir = fixture_dir("putty");
+        let path = dir.join("putty.reg");
+        // regedit writes UTF-16LE with a byte order mark
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(PUTTY_REG.replace('\n', "\r\n").encode_utf16().flat_map(u16::to_le_bytes));
+        fs::write(&path, bytes).unwrap();
+        let report = import(SessionFormat::Putty, &path);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(report.dry_run);
+        let names: Vec<&str> = report.created.iter().map(|profile| profile.name.as_str()).collect();
+        assert_eq!(names, vec!["rtr one", "switch", "edge"]);
+
+        let rtr = created(&report, "rtr one");
+        assert_eq!((rtr.protocol, rtr.hostname.as_str(), rtr.port), (ProfileProtocol::Ssh, "10.0.0.1", 2082));
+        assert_eq!(rtr.username, "admin");
+        assert_eq!(rtr.auth_method, AuthMethod::Key);
+        assert_eq!(rtr.private_key_path.as_deref(), Some(r"C:\Users\me\rtr.ppk"));
+        assert_eq!(rtr.tags, vec!["putty", "Core"]);
+        assert_eq!(rtr.source.as_deref(), Some("putty"));
+
+        let switch = created(&report, "switch");
+        assert_eq!((switch.protocol, switch.hostname.as_str(), switch.port), (ProfileProtocol::Telnet, "10.0.0.2", 23));
+        assert_eq!(switch.username, "ops");
+        assert_eq!(switch.tags, vec!["putty"]);
+
+        // Matched case-insensitively under a key with non-ASCII characters before it
+        let edge = created(&report, "edge");
+        assert_eq!((edge.hostname.as_str(), edge.port, edge.username.as_str()), ("10.0.0.3", 22, "edge"));
+
+        assert_eq!(skipped(&report), vec![("console", "Unsupported protocol serial")]);
+        assert_eq!(unsupported(&report), vec![("PortForwardings", 1)]);
+    }
+
+    #[test]
+    fn putty_requires_a_registry_export() {
+        let dir = fixture_dir("putty-invalid");
+        let path = dir.join("sessions.txt");
+        fs::write(&path, "HostName=10.0.0.1\n").unwrap();
+        let result = parse_putty(&path, &mut Collected::default());
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(result.unwrap_err().contains("is not a registry export"));
+    }
+
+    #[test]
+    fn securecrt_session_folder() {
+        let dir = fixture_dir("securecrt-ini");
+        fs::create_dir_all(dir.join("Site").join("Core")).unwrap();
+        fs::write(dir.join("__FolderData__.ini"), "S:\"Folder List\"=Site\n").unwrap();
+        fs::write(dir.join("Default.ini"), "S:\"Protocol Name\"=SSH2\nS:\"Hostname\"=default.example\n").unwrap();
+        fs::write(
+            dir.join("Site").join("Core").join("rtr1.ini"),
+            concat!(
+                "S:\"Protocol Name\"=SSH2\r\n",
+                "S:\"Hostname\"=10.1.0.1\r\n",
+                "D:\"[SSH2] Port\"=00000016\r\n",
+                "S:\"Username\"=netops\r\n",
+                "S:\"Identity Filename V2\"=C:\\keys\\id_ed25519::rawkey\r\n",
+                "D:\"Use Global Public Key\"=00000000\r\n",
+                "S:\"Firewall Name\"=Jump\r\n",
+                "Z:\"Port Forward Table V2\"=00000000\r\n",
+            ),
+        )
+        .unwrap();
+        fs::write(dir.join("Site").join("web.ini"), "S:\"Protocol Name\"=Telnet\nS:\"Hostname\"=10.1.0.9\nD:\"Port\"=00000917\n").unwrap();
+        fs::write(dir.join("Site").join("console.ini"), "S:\"Protocol Name\"=Serial\nS:\"Hostname\"=\n").unwrap();
+        let report = import(SessionFormat::SecureCrt, &dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<&str> = report.created.iter().map(|profile| profile.name.as_str()).collect();
+        assert_eq!(names, vec!["rtr1", "web"]);
+
+        let rtr = created(&report, "rtr1");
+        assert_eq!((rtr.protocol, rtr.hostname.as_str(), rtr.port), (ProfileProtocol::Ssh, "10.1.0.1", 22));
+        assert_eq!(rtr.username, "netops");
+        assert_eq!(rtr.private_key_path.as_deref(), Some(r"C:\keys\id_ed25519"));
+        assert_eq!(rtr.tags, vec!["securecrt", "Site", "Site/Core"]);
+
+        let web = created(&report, "web");
+        assert_eq!((web.protocol, web.hostname.as_str(), web.port), (ProfileProtocol::Telnet, "10.1.0.9", 2327));
+        assert_eq!(web.tags, vec!["securecrt", "Site"]);
+
+        assert_eq!(skipped(&report), vec![("console", "Unsupported protocol Serial")]);
+        assert_eq!(unsupported(&report), vec![("Firewall Name", 1)]);
+    }
+
+    const SECURECRT_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<VanDyke version="3.0">
+  <key name="Sessions">
+    <key name="Default">
+      <string name="Protocol Name">SSH2</string>
+      <string name="Hostname"></string>
+    </key>
+    <key name="Lab">
+      <key name="__FolderData__">
+        <string name="Folder List">sw1</string>
+      </key>
+      <key name="sw1">
+        <string name="Protocol Name">SSH2</string>
+        <string name="Hostname">192.0.2.10</string>
+        <dword name="[SSH2] Port">2222</dword>
+        <string name="Username">lab</string>
+        <string name="Identity Filename V2">C:\keys\lab</string>
+        <dword name="Use Global Public Key">1</dword>
+      </key>
+      <key name="rlogin">
+        <string name="Protocol Name">RLogin</string>
+        <string name="Hostname">192.0.2.11</string>
+      </key>
+    </key>
+  </key>
+</VanDyke>
+"#;
+
+    #[test]
+    fn securecrt_xml_export() {
+        let dir = fixture_dir("securecrt-xml");
+        let path = dir.join("SecureCRT.xml");
+        fs::write(&path, SECURECRT_XML).unwrap();
+        let report = import(SessionFormat::SecureCrt, &path);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(report.created.len(), 1);
+        let sw1 = created(&report, "sw1");
+        assert_eq!((sw1.protocol, sw1.hostname.as_str(), sw1.port), (ProfileProtocol::Ssh, "192.0.2.10", 2222));
+        assert_eq!(sw1.username, "lab");
+        // The global key is used, so the session's own identity is ignored
+        assert_eq!(sw1.private_key_path, None);
+        assert_eq!(sw1.auth_method, AuthMethod::Agent);
+        assert_eq!(sw1.tags, vec!["securecrt", "Lab"]);
+        assert_eq!(skipped(&report), vec![("rlogin", "Unsupported protocol RLogin")]);
+    }
+
+    const MOBAXTERM: &str = r"[Bookmarks]
+SubRep=
+ImgNum=42
+rtr__PIPE__a= #109#0%10.2.0.1%2222%netops%%-1%-1%%bastion.example%22%jump%0%0%0%C:\keys\id_rsa%%-1%0%0%0%%1080%%0%0%1#MobaFont%10%0%0%-1%15%236,236,236%30,30,30%180,180,192%0%-1%0%%xterm%-1%-1%_Std_Colors_0_%80%24%0%1%-1%<none>%%0%1%-1#0# #-1
+
+[Bookmarks_1]
+SubRep=DC\Core
+ImgNum=41
+sw1= #98#1%ops@10.2.0.9%23%%%2%%%%%%%%%%%%%%%%%%%#MobaFont%10#0# #-1
+rdp1= #91#4%10.2.0.20%3389%admin%0%-1%-1%-1%-1%0%0%-1#MobaFont%10#0# #-1
+
+[Misc]
+notasession= #109#0%10.9.9.9%22%x#MobaFont#0# #-1
+";
+
+    #[test]
+    fn mobaxterm_sessions() {
+        let dir = fixture_dir("mobaxterm");
+        let path = dir.join("sessions.mxtsessions");
+        fs::write(&path, MOBAXTERM.replace('\n', "\r\n")).unwrap();
+        let report = import(SessionFormat::MobaXterm, &path);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<&str> = report.created.iter().map(|profile| profile.name.as_str()).collect();
+        assert_eq!(names, vec!["rtr|a", "sw1"]);
+
+        let rtr = created(&report, "rtr|a");
+        assert_eq!((rtr.protocol, rtr.hostname.as_str(), rtr.port), (ProfileProtocol::Ssh, "10.2.0.1", 2222));
+        assert_eq!(rtr.username, "netops");
+        assert_eq!(rtr.private_key_path.as_deref(), Some(r"C:\keys\id_rsa"));
+        assert_eq!(rtr.tags, vec!["mobaxterm"]);
+        assert_eq!(rtr.jump_hosts.len(), 1);
+        let hop = &rtr.jump_hosts[0];
+        assert_eq!((hop.hostname.as_str(), hop.port, hop.username.as_str()), ("bastion.example", 22, "jump"));
+
+        let sw1 = created(&report, "sw1");
+        assert_eq!((sw1.protocol, sw1.hostname.as_str(), sw1.port), (ProfileProtocol::Telnet, "10.2.0.9", 23));
+        assert_eq!(sw1.username, "ops");
+        assert!(sw1.jump_hosts.is_empty());
+        assert_eq!(sw1.tags, vec!["mobaxterm", "DC", "DC/Core"]);
+
+        assert_eq!(skipped(&report), vec![("rdp1", "Unsupported MobaXterm session type 4")]);
+        assert!(report.unsupported.is_empty());
+    }
+
+    #[test]
+    fn same_name_in_two_folders_gets_the_folder_prefix() {
+        let session = |folders: &[&str]| ImportedSession {
+            name: "rtr".to_string(),
+            folders: folders.iter().map(|folder| folder.to_string()).collect(),
+            protocol: ProfileProtocol::Telnet,
+            hostname: "10.0.0.1".to_string(),
+            port: None,
+            username: String::new(),
+            private_key_path: None,
+            certificate_path: None,
+            jump_host: None,
+        };
+        let profiles = to_profiles(vec![session(&["East"]), session(&["West", "Core"])], SessionFormat::SecureCrt);
+        let names: Vec<&str> = profiles.iter().map(|profile| profile.name.as_str()).collect();
+        assert_eq!(names, vec!["East/rtr", "West/Core/rtr"]);
+    }
+}
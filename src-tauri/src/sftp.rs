@@ -0,0 +1,217 @@
+// SFTP file browser for connected SSH sessions.
+// Each command opens its own SFTP channel on the session's shared transport;
+// uploads and downloads run in the background and report through `file-transfer`.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use ssh2::{FileStat, FileType, Session, Sftp};
+use tauri::{command, AppHandle, State};
+use tokio::task;
+
+use crate::session::session_transport;
+use crate::ssh_client::{retry, retry_io, write_all_nonblocking};
+use crate::transfers::{Transfer, TransferDirection};
+use crate::AppState;
+
+// --- Types ---
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RemoteEntryKind {
+    File,
+    Directory,
+    Symlink,
+    Other,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteEntry {
+    pub name: String,
+    pub path: String,
+    pub kind: RemoteEntryKind,
+    pub size: Option<u64>,
+    pub permissions: Option<u32>, // Mode bits as reported by the server
+    pub modified: Option<u64>,    // Unix timestamp (seconds)
+}
+
+// --- Helpers ---
+
+fn open_sftp(ssh: &Session) -> Result<Sftp, String> {
+    retry(|| ssh.sftp()).map_err(|e| format!("Failed to start SFTP subsystem: {}", e))
+}
+
+fn entry(path: &Path, stat: &FileStat) -> RemoteEntry {
+    let kind = match stat.file_type() {
+        FileType::Directory => RemoteEntryKind::Directory,
+        FileType::RegularFile => RemoteEntryKind::File,
+        FileType::Symlink => RemoteEntryKind::Symlink,
+        _ => RemoteEntryKind::Other,
+    };
+    RemoteEntry {
+        name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| path.display().to_string()),
+        path: path.to_string_lossy().to_string(),
+        kind,
+        size: stat.size,
+        permissions: stat.perm.map(|perm| perm & 0o7777),
+        modified: stat.mtime,
+    }
+}
+
+// An empty path means the login directory
+fn remote_path(path: &str) -> PathBuf {
+    if path.trim().is_empty() {
+        PathBuf::from(".")
+    } else {
+        PathBuf::from(path)
+    }
+}
+
+// Runs a blocking SFTP operation for a session on the blocking pool
+async fn with_sftp<T: Send + 'static>(
+    state: &State<'_, AppState>,
+    session_id: &str,
+    op: impl FnOnce(&Sftp) -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    let ssh = session_transport(&state.sessions, session_id)?;
+    task::spawn_blocking(move || op(&open_sftp(&ssh)?))
+        .await
+        .map_err(|e| format!("SFTP task failed: {}", e))?
+}
+
+// --- Tauri Commands ---
+
+#[command]
+pub async fn sftp_list_dir(state: State<'_, AppState>, session_id: String, path: String) -> Result<Vec<RemoteEntry>, String> {
+    with_sftp(&state, &session_id, move |sftp| {
+        let dir = retry(|| sftp.realpath(&remote_path(&path))).map_err(|e| format!("Cannot resolve {}: {}", path, e))?;
+        let mut entries: Vec<RemoteEntry> = retry(|| sftp.readdir(&dir))
+            .map_err(|e| format!("Cannot list {}: {}", dir.display(), e))?
+            .iter()
+            .map(|(path, stat)| entry(path, stat))
+            .collect();
+        // Directories first, then by name
+        entries.sort_by(|a, b| {
+            let a_dir = matches!(a.kind, RemoteEntryKind::Directory);
+            let b_dir = matches!(b.kind, RemoteEntryKind::Directory);
+            b_dir.cmp(&a_dir).then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(entries)
+    })
+    .await
+}
+
+#[command]
+pub async fn sftp_stat(state: State<'_, AppState>, session_id: String, path: String) -> Result<RemoteEntry, String> {
+    with_sftp(&state, &session_id, move |sftp| {
+        let path = remote_path(&path);
+        let stat = retry(|| sftp.stat(&path)).map_err(|e| format!("Cannot stat {}: {}", path.display(), e))?;
+        Ok(entry(&path, &stat))
+    })
+    .await
+}
+
+#[command]
+pub async fn sftp_rename(state: State<'_, AppState>, session_id: String, from: String, to: String) -> Result<(), String> {
+    with_sftp(&state, &session_id, move |sftp| {
+        retry(|| sftp.rename(Path::new(&from), Path::new(&to), None))
+            .map_err(|e| format!("Cannot rename {} to {}: {}", from, to, e))
+    })
+    .await
+}
+
+// Deletes a file, or an empty directory
+#[command]
+pub async fn sftp_delete(state: State<'_, AppState>, session_id: String, path: String) -> Result<(), String> {
+    with_sftp(&state, &session_id, move |sftp| {
+        let target = Path::new(&path);
+        let stat = retry(|| sftp.lstat(target)).map_err(|e| format!("Cannot stat {}: {}", path, e))?;
+        if stat.is_dir() {
+            retry(|| sftp.rmdir(target)).map_err(|e| format!("Cannot remove directory {} (is it empty?): {}", path, e))
+        } else {
+            retry(|| sftp.unlink(target)).map_err(|e| format!("Cannot delete {}: {}", path, e))
+        }
+    })
+    .await
+}
+
+// Starts a download in the background and returns its transfer id
+#[command]
+pub async fn sftp_download(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    session_id: String,
+    remote_path: String,
+    local_path: String,
+) -> Result<String, String> {
+    let ssh = session_transport(&state.sessions, &session_id)?;
+    let mut transfer = Transfer::start(
+        app_handle,
+        &state.transfers,
+        &session_id,
+        TransferDirection::Download,
+        &remote_path,
+        &local_path,
+    )?;
+    let transfer_id = transfer.id().to_string();
+
+    task::spawn_blocking(move || {
+        let result = (|| {
+            let sftp = open_sftp(&ssh)?;
+            let source = Path::new(&remote_path);
+            let mut remote = retry(|| sftp.open(source)).map_err(|e| format!("Cannot open {}: {}", remote_path, e))?;
+            transfer.set_total(retry(|| remote.stat()).ok().and_then(|stat| stat.size));
+            let mut local = fs::File::create(&local_path).map_err(|e| format!("Cannot create {}: {}", local_path, e))?;
+            let copied = transfer.copy(&mut remote, &mut local, |r, buf| retry_io(|| r.read(buf)), |w, data| w.write_all(data));
+            if copied.is_err() {
+                // Do not leave a truncated file behind
+                drop(local);
+                let _ = fs::remove_file(&local_path);
+            }
+            copied
+        })();
+        transfer.finish(&result);
+    });
+    Ok(transfer_id)
+}
+
+// Starts an upload in the background and returns its transfer id
+#[command]
+pub async fn sftp_upload(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    session_id: String,
+    local_path: String,
+    remote_path: String,
+) -> Result<String, String> {
+    let ssh = session_transport(&state.sessions, &session_id)?;
+    let mut transfer = Transfer::start(
+        app_handle,
+        &state.transfers,
+        &session_id,
+        TransferDirection::Upload,
+        &remote_path,
+        &local_path,
+    )?;
+    let transfer_id = transfer.id().to_string();
+
+    task::spawn_blocking(move || {
+        let result = (|| {
+            let mut local = fs::File::open(&local_path).map_err(|e| format!("Cannot open {}: {}", local_path, e))?;
+            transfer.set_total(local.metadata().ok().map(|meta| meta.len()));
+            let sftp = open_sftp(&ssh)?;
+            let target = Path::new(&remote_path);
+            let mut remote = retry(|| sftp.create(target)).map_err(|e| format!("Cannot create {}: {}", remote_path, e))?;
+            let copied = transfer.copy(&mut local, &mut remote, |r, buf| r.read(buf), write_all_nonblocking);
+            if copied.is_err() {
+                drop(remote);
+                let _ = retry(|| sftp.unlink(target));
+            }
+            copied
+        })();
+        transfer.finish(&result);
+    });
+    Ok(transfer_id)
+}
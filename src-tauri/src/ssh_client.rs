@@ -0,0 +1,901 @@
+// Native SSH transport built on libssh2 (via the `ssh2` crate).
+// Replaces the old ssh/sshpass/plink subprocesses so connecting works the
+// same on every platform and auth/channel errors come back as real errors.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ssh2::{Channel, ErrorCode, Session};
+use tauri::{command, AppHandle, State};
+use tokio::sync::mpsc::{self, error::TryRecvError, Receiver};
+use tokio::task;
+use zeroize::Zeroizing;
+
+use crate::algorithms::{warn_if_weak, AlgorithmOverrides};
+use crate::certificates::{self, CertificateError, UserCertificate};
+use crate::connect_error::ConnectError;
+use crate::history::{self, Transport};
+use crate::keepalive::{record_latency, Keepalive, KeepaliveConfig};
+use crate::keyboard_interactive::{self, AuthPrompts, InteractivePrompter};
+use crate::keys::{expand_home, home_dir, KeyError, KeyFormat, KeyMaterial, KeyUnlocker};
+use crate::known_hosts::HostKeyVerifier;
+use crate::reconnect::{wait_for_retry, ReconnectPolicy};
+use crate::tunnel;
+use crate::session::{
+    emit_error, emit_output, emit_state, finish_session, session_transport, SessionCommand, SessionHandle,
+    SessionInfo, SessionKind, SessionMap, SessionState, DEFAULT_PTY_COLS, DEFAULT_PTY_ROWS,
+};
+use crate::AppState;
+
+// How long to wait for the TCP connection and each blocking step of the handshake,
+// unless `ssh_connect` is given a timeout
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+// Sleep between polls of a non-blocking session when there is nothing to do
+pub const IDLE_POLL: Duration = Duration::from_millis(10);
+// libssh2's "operation would block" code, returned by non-blocking sessions
+pub const LIBSSH2_ERROR_EAGAIN: i32 = -37;
+// libssh2 could not use the private key data (typically a wrong PEM passphrase)
+const LIBSSH2_ERROR_FILE: i32 = -16;
+// Identity files tried (in order) when no password is given and the agent fails
+const DEFAULT_IDENTITIES: [&str; 3] = ["id_ed25519", "id_ecdsa", "id_rsa"];
+
+// Everything needed to open (or later re-open) a connection
+pub struct ConnectParams {
+    pub hostname: String,
+    pub port: u16,
+    pub username: String,
+    // Secrets are wiped from memory when the params are dropped
+    pub password: Option<Zeroizing<String>>,
+    pub private_key_path: Option<String>,
+    pub passphrase: Option<Zeroizing<String>>, // For the private key; prompted for when missing
+    pub certificate_path: Option<String>, // OpenSSH user certificate; defaults to `<key>-cert.pub` when present
+    pub jump_hosts: Vec<ConnectParams>, // Bastions to tunnel through, in order; empty for direct
+    pub algorithms: AlgorithmOverrides,
+}
+
+// One bastion of a jump chain, as sent by the frontend
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JumpHostConfig {
+    pub hostname: String,
+    pub port: Option<u16>,
+    pub username: String,
+    pub password: Option<String>,
+    pub private_key_path: Option<String>,
+    pub passphrase: Option<String>,
+    pub certificate_path: Option<String>,
+    #[serde(default)]
+    pub algorithms: AlgorithmOverrides,
+}
+
+impl From<JumpHostConfig> for ConnectParams {
+    fn from(hop: JumpHostConfig) -> Self {
+        ConnectParams {
+            hostname: hop.hostname,
+            port: hop.port.unwrap_or(22),
+            username: hop.username,
+            password: hop.password.filter(|p| !p.is_empty()).map(Zeroizing::new),
+            private_key_path: hop.private_key_path.filter(|path| !path.trim().is_empty()),
+            passphrase: hop.passphrase.filter(|p| !p.is_empty()).map(Zeroizing::new),
+            certificate_path: hop.certificate_path.filter(|path| !path.trim().is_empty()),
+            jump_hosts: Vec::new(),
+            algorithms: hop.algorithms,
+        }
+    }
+}
+
+// Interactive hooks used while connecting; each may block waiting on the user
+pub struct ConnectContext {
+    pub app_handle: AppHandle,
+    pub session_id: String, // For events raised while connecting
+    pub host_keys: HostKeyVerifier,
+    pub keys: KeyUnlocker,
+    pub auth_prompts: AuthPrompts, // Keyboard-interactive challenges
+    pub timeout: Duration, // Per blocking step, for every hop
+}
+
+// --- Non-blocking helpers ---
+// After authentication the session is switched to non-blocking mode so the
+// shell loop, exec channels and other users of the session never hold the
+// libssh2 lock while waiting on the network.
+
+// Retries a libssh2 call until it stops returning EAGAIN
+pub fn retry<T>(mut op: impl FnMut() -> Result<T, ssh2::Error>) -> Result<T, ssh2::Error> {
+    loop {
+        match op() {
+            Err(e) if e.code() == ErrorCode::Session(LIBSSH2_ERROR_EAGAIN) => thread::sleep(IDLE_POLL),
+            other => return other,
+        }
+    }
+}
+
+// Same as `retry` for std::io calls on channels (EAGAIN maps to WouldBlock)
+pub fn retry_io<T>(mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    loop {
+        match op() {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(IDLE_POLL),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            other => return other,
+        }
+    }
+}
+
+// Writes the whole buffer to a non-blocking channel
+pub fn write_all_nonblocking<W: Write>(writer: &mut W, mut data: &[u8]) -> io::Result<()> {
+    while !data.is_empty() {
+        let n = retry_io(|| writer.write(data))?;
+        if n == 0 {
+            return Err(io::Error::new(io::ErrorKind::WriteZero, "channel closed while writing"));
+        }
+        data = &data[n..];
+    }
+    retry_io(|| writer.flush())
+}
+
+// --- Connection & Authentication ---
+
+// Connects to the target, tunnelling through each jump host in turn.
+// Errors name the hop that failed. Blocking: call from a blocking thread,
+// never directly from an async command.
+pub fn establish(params: &ConnectParams, context: &ConnectContext) -> Result<Session, ConnectError> {
+    let mut hop_stream: Option<TcpStream> = None;
+    for (index, hop) in params.jump_hosts.iter().enumerate() {
+        let label = jump_label(index, hop);
+        let (next_host, next_port) = next_hop(params, index);
+        println!("{}: connecting, next hop {}:{}", label, next_host, next_port);
+        let session = establish_hop(hop, hop_stream.take(), context).map_err(|e| e.context(&label))?;
+        let stream = tunnel::open_jump_tunnel(session, label.clone(), next_host, next_port)
+            .map_err(|e| ConnectError::Unreachable(format!("{}: {}", label, e)))?;
+        hop_stream = Some(stream);
+    }
+
+    let via_jump = hop_stream.is_some();
+    establish_hop(params, hop_stream, context).map_err(|e| {
+        if via_jump {
+            e.context(&format!("Target {}@{}:{}", params.username, params.hostname, params.port))
+        } else {
+            e
+        }
+    })
+}
+
+// "Jump host 2 (admin@bastion:22)", for the 0-based `index`
+fn jump_label(index: usize, hop: &ConnectParams) -> String {
+    format!("Jump host {} ({}@{}:{})", index + 1, hop.username, hop.hostname, hop.port)
+}
+
+// Where the jump host at `index` tunnels to: the next jump host, or the target
+fn next_hop(params: &ConnectParams, index: usize) -> (&str, u16) {
+    match params.jump_hosts.get(index + 1) {
+        Some(next) => (next.hostname.as_str(), next.port),
+        None => (params.hostname.as_str(), params.port),
+    }
+}
+
+// Runs the SSH handshake, verifies the host key and authenticates one host.
+// `tunnel` carries the connection when the host sits behind a jump host;
+// otherwise a direct TCP connection is opened.
+fn establish_hop(params: &ConnectParams, tunnel: Option<TcpStream>, context: &ConnectContext) -> Result<Session, ConnectError> {
+    // Unlock the key before touching the network so a passphrase prompt
+    // cannot run into the server's login grace time
+    let key = match &params.private_key_path {
+        Some(path) => Some(context.keys.load(path, params.passphrase.as_ref().map(|p| p.as_str()))?),
+        None => None,
+    };
+    let certificate = match (&key, &params.certificate_path) {
+        (Some(key), path) => certificates::for_key(path.as_deref(), key, &context.session_id)?,
+        (None, Some(path)) => return Err(CertificateError::NoPrivateKey(expand_home(path)).into()),
+        (None, None) => None,
+    };
+    if let Some(certificate) = &certificate {
+        certificates::announce(&context.app_handle, &context.session_id, &params.hostname, &params.username, certificate);
+    }
+
+    let stream = match tunnel {
+        Some(stream) => stream,
+        None => connect_tcp(&params.hostname, params.port, context.timeout)?,
+    };
+
+    let timeout_ms = context.timeout.as_millis() as u32;
+    let mut session = Session::new().map_err(|e| ConnectError::Other(format!("Failed to create SSH session: {}", e)))?;
+    session.set_timeout(timeout_ms);
+    session.set_tcp_stream(stream);
+    params.algorithms.apply(&session)?;
+    session.handshake().map_err(|e| {
+        ConnectError::from_ssh(&e, format!("SSH handshake with {}:{} failed: {}", params.hostname, params.port, e))
+    })?;
+    warn_if_weak(&context.app_handle, &context.session_id, &session, &params.hostname, params.port);
+
+    // Never send credentials to a host we have not verified.
+    // No timeout while the user looks at the fingerprint prompt.
+    session.set_timeout(0);
+    context.host_keys.verify(&session, &params.hostname, params.port)?;
+    session.set_timeout(timeout_ms);
+
+    authenticate(&session, params, key.as_ref(), certificate.as_ref(), context)?;
+
+    // Interactive use from here on; reads must not time out
+    session.set_timeout(0);
+    Ok(session)
+}
+
+// Resolves the host and opens a direct TCP connection, trying each address in turn
+pub fn connect_tcp(hostname: &str, port: u16, timeout: Duration) -> Result<TcpStream, ConnectError> {
+    let addrs: Vec<_> = (hostname, port)
+        .to_socket_addrs()
+        .map_err(|e| ConnectError::Dns(format!("Failed to resolve {}: {}", hostname, e)))?
+        .collect();
+    if addrs.is_empty() {
+        return Err(ConnectError::Dns(format!("Failed to resolve {}: no addresses found", hostname)));
+    }
+
+    // Reported by the kind of the last address's failure
+    let mut last_error = None;
+    for addr in &addrs {
+        match TcpStream::connect_timeout(addr, timeout) {
+            Ok(stream) => {
+                let _ = stream.set_nodelay(true);
+                return Ok(stream);
+            }
+            Err(e) => last_error = Some((format!("{}: {}", addr, e), e)),
+        }
+    }
+    let (detail, error) = last_error.expect("at least one address was tried");
+    Err(ConnectError::from_io(&error, format!("Failed to connect to {}:{} ({})", hostname, port, detail)))
+}
+
+fn authenticate(
+    session: &Session,
+    params: &ConnectParams,
+    key: Option<&KeyMaterial>,
+    certificate: Option<&UserCertificate>,
+    context: &ConnectContext,
+) -> Result<(), ConnectError> {
+    let username = params.username.as_str();
+    let methods = session
+        .auth_methods(username)
+        .map_err(|e| ConnectError::from_ssh(&e, format!("Failed to query authentication methods: {}", e)))?
+        .to_string();
+    // Some servers accept "none" authentication straight away
+    if session.authenticated() {
+        return Ok(());
+    }
+    println!("Server offers authentication methods: {}", methods);
+
+    let mut attempted: Vec<String> = Vec::new();
+
+    // An explicitly selected key goes first, presenting its certificate when there is one
+    if let Some(key) = key {
+        if methods.contains("publickey") {
+            let with_certificate = if certificate.is_some() { " with certificate" } else { "" };
+            attempted.push(format!("publickey ({}{})", key.path.display(), with_certificate));
+            let public_key = certificate.map(|certificate| certificate.openssh.as_str()).or(key.public_key.as_deref());
+            match session.userauth_pubkey_memory(
+                username,
+                public_key,
+                &key.private_key,
+                key.passphrase.as_ref().map(|p| p.as_str()),
+            ) {
+                Ok(()) if session.authenticated() => return Ok(()),
+                Ok(()) => {}
+                Err(e) if e.code() == ErrorCode::Session(LIBSSH2_ERROR_FILE) => {
+                    return Err(if key.format == KeyFormat::Pem && key.passphrase.is_some() {
+                        KeyError::BadPassphrase.into()
+                    } else {
+                        ConnectError::AuthFailed(format!("Failed to use private key {}: {}", key.path.display(), e.message()))
+                    });
+                }
+                Err(e) => println!("Key {} rejected: {}", key.path.display(), e),
+            }
+        }
+    }
+
+    if let Some(password) = &params.password {
+        if methods.contains("password") {
+            attempted.push("password".to_string());
+            match session.userauth_password(username, password) {
+                Ok(()) if session.authenticated() => return Ok(()),
+                Ok(()) => {}
+                Err(e) => println!("Password authentication failed: {}", e),
+            }
+        }
+    } else if key.is_none() && methods.contains("publickey") {
+        attempted.push("publickey (agent)".to_string());
+        match session.userauth_agent(username) {
+            Ok(()) if session.authenticated() => return Ok(()),
+            Ok(()) => {}
+            Err(e) => println!("Agent authentication failed: {}", e),
+        }
+
+        for identity in default_identities() {
+            attempted.push(format!("publickey ({})", identity.display()));
+            match session.userauth_pubkey_file(username, None, &identity, None) {
+                Ok(()) if session.authenticated() => return Ok(()),
+                Ok(()) => {}
+                Err(e) => println!("Key {} rejected: {}", identity.display(), e),
+            }
+        }
+    }
+
+    // Last: let the user answer whatever the server asks (password, OTP, ...)
+    if methods.contains("keyboard-interactive") {
+        attempted.push("keyboard-interactive".to_string());
+        let mut prompter = InteractivePrompter {
+            app_handle: &context.app_handle,
+            prompts: &context.auth_prompts,
+            hostname: &params.hostname,
+            password: params.password.as_ref().map(|p| p.as_str()),
+            cancelled: false,
+        };
+        match keyboard_interactive::authenticate(session, username, &mut prompter) {
+            Ok(()) if session.authenticated() => return Ok(()),
+            Ok(()) => {}
+            Err(e) => println!("Keyboard-interactive authentication failed: {}", e),
+        }
+        if prompter.cancelled {
+            return Err(ConnectError::Cancelled(format!("Authentication for {}@{} was cancelled", username, params.hostname)));
+        }
+    }
+
+    if attempted.is_empty() {
+        return Err(ConnectError::AuthFailed(format!(
+            "Authentication failed for {}@{}: no usable method (server offers: {})",
+            username, params.hostname, methods
+        )));
+    }
+    Err(ConnectError::AuthFailed(format!(
+        "Permission denied by server for {}@{} (tried: {}; server offers: {})",
+        username,
+        params.hostname,
+        attempted.join(", "),
+        methods
+    )))
+}
+
+// Default key files in ~/.ssh that actually exist
+fn default_identities() -> Vec<PathBuf> {
+    let home = match home_dir() {
+        Some(home) => home,
+        None => return Vec::new(),
+    };
+    DEFAULT_IDENTITIES
+        .iter()
+        .map(|name| home.join(".ssh").join(name))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+// Opens a session channel with a PTY of the given size and starts the login shell
+fn open_shell(session: &Session, cols: u16, rows: u16) -> Result<Channel, String> {
+    let mut channel = session
+        .channel_session()
+        .map_err(|e| format!("Failed to open shell channel: {}", e))?;
+    channel
+        .request_pty("xterm-256color", None, Some((cols as u32, rows as u32, 0, 0)))
+        .map_err(|e| format!("Failed to request PTY: {}", e))?;
+    channel.shell().map_err(|e| format!("Failed to start remote shell: {}", e))?;
+    Ok(channel)
+}
+
+// --- Shell I/O Loop ---
+
+// How a shell channel ended
+enum ShellEnd {
+    ClosedByUser,
+    Eof,          // The remote shell exited
+    Lost(String), // The transport failed underneath the channel
+}
+
+// Settings and credentials for logging in again after the connection drops
+pub struct Reconnect {
+    pub policy: ReconnectPolicy,
+    pub params: ConnectParams,
+    pub context: ConnectContext,
+}
+
+enum ReconnectOutcome {
+    Reconnected(Session, Channel),
+    Cancelled,
+    GaveUp(String),
+}
+
+// Pumps remote output to the frontend and applies write/resize/disconnect
+// commands until the channel ends. Keeps `size` up to date for reconnects.
+fn pump_shell(
+    app_handle: &AppHandle,
+    session_id: &str,
+    sessions: &SessionMap,
+    channel: &mut Channel,
+    command_receiver: &mut Receiver<SessionCommand>,
+    size: &mut (u16, u16),
+    keepalive: &mut Option<Keepalive>,
+) -> ShellEnd {
+    let mut buffer = [0; 4096]; // Read in chunks
+    loop {
+        let mut idle = true;
+
+        // Apply queued commands first so typing stays responsive
+        loop {
+            match command_receiver.try_recv() {
+                Ok(SessionCommand::Write(data)) => {
+                    idle = false;
+                    if let Err(e) = write_all_nonblocking(channel, &data) {
+                        emit_error(app_handle, session_id, format!("Error writing to SSH channel: {}", e));
+                    }
+                }
+                Ok(SessionCommand::Resize { cols, rows }) => {
+                    idle = false;
+                    *size = (cols, rows);
+                    if let Err(e) = retry(|| channel.request_pty_size(cols as u32, rows as u32, None, None)) {
+                        emit_error(app_handle, session_id, format!("Failed to resize PTY: {}", e));
+                    }
+                }
+                Ok(SessionCommand::Break(_)) => {
+                    emit_error(app_handle, session_id, "BREAK is not supported on SSH sessions".to_string());
+                }
+                Ok(SessionCommand::Disconnect) => {
+                    println!("[{}] I/O thread received disconnect.", session_id);
+                    return ShellEnd::ClosedByUser;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return ShellEnd::ClosedByUser,
+            }
+        }
+
+        // A half-open connection never errors on read; only a missed probe notices it
+        if let Some(keepalive) = keepalive.as_mut() {
+            match keepalive.poll(channel) {
+                Ok(Some(rtt)) => record_latency(app_handle, sessions, session_id, rtt),
+                Ok(None) => {}
+                Err(e) => return ShellEnd::Lost(e),
+            }
+        }
+
+        match channel.read(&mut buffer) {
+            Ok(0) => {
+                println!("[{}] SSH channel EOF reached.", session_id);
+                return ShellEnd::Eof;
+            }
+            Ok(n) => {
+                idle = false;
+                emit_output(app_handle, session_id, &buffer[..n]);
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return ShellEnd::Lost(format!("Error reading SSH channel: {}", e)),
+        }
+
+        if idle {
+            thread::sleep(IDLE_POLL);
+        }
+    }
+}
+
+// Logs in again and opens a new shell, backing off between attempts as the policy says
+fn reconnect_shell(
+    app_handle: &AppHandle,
+    session_id: &str,
+    reconnect: &Reconnect,
+    command_receiver: &mut Receiver<SessionCommand>,
+    size: &mut (u16, u16),
+) -> ReconnectOutcome {
+    let policy = &reconnect.policy;
+    let mut last_error = String::new();
+    for attempt in 1..=policy.max_attempts {
+        let delay = policy.delay(attempt);
+        emit_state(
+            app_handle,
+            session_id,
+            SessionState::Reconnecting,
+            Some(attempt),
+            Some(format!("Attempt {} of {} in {:.1}s", attempt, policy.max_attempts, delay.as_secs_f32())),
+        );
+        if !wait_for_retry(command_receiver, delay, size) {
+            return ReconnectOutcome::Cancelled;
+        }
+
+        let opened = establish(&reconnect.params, &reconnect.context).map_err(String::from).and_then(|ssh| {
+            let channel = open_shell(&ssh, size.0, size.1)?;
+            ssh.set_blocking(false);
+            Ok((ssh, channel))
+        });
+        match opened {
+            Ok((ssh, channel)) => return ReconnectOutcome::Reconnected(ssh, channel),
+            Err(e) => {
+                eprintln!("[{}] Reconnect attempt {} failed: {}", session_id, attempt, e);
+                last_error = e;
+            }
+        }
+    }
+    ReconnectOutcome::GaveUp(last_error)
+}
+
+// Points the session at its new transport. Port forwards ran over the old one
+// and are stopped; the frontend re-adds them from their `closed` status.
+fn replace_transport(sessions: &SessionMap, session_id: &str, ssh: &Session) {
+    if let Ok(mut guard) = sessions.lock() {
+        if let Some(handle) = guard.get_mut(session_id) {
+            handle.ssh = Some(ssh.clone());
+            handle.forwards.clear();
+        }
+    }
+}
+
+// Owns the shell channel for the lifetime of the session, reconnecting when
+// the transport drops if a policy was given.
+#[allow(clippy::too_many_arguments)]
+fn spawn_shell_io(
+    app_handle: AppHandle,
+    session_id: String,
+    sessions: SessionMap,
+    mut ssh: Session,
+    mut channel: Channel,
+    mut command_receiver: Receiver<SessionCommand>,
+    mut size: (u16, u16),
+    mut keepalive: Option<Keepalive>,
+    reconnect: Option<Reconnect>,
+) {
+    thread::spawn(move || {
+        println!("[{}] SSH I/O thread started.", session_id);
+
+        let (state, exit_message) = loop {
+            let end = pump_shell(
+                &app_handle,
+                &session_id,
+                &sessions,
+                &mut channel,
+                &mut command_receiver,
+                &mut size,
+                &mut keepalive,
+            );
+            let error = match end {
+                ShellEnd::ClosedByUser => {
+                    // Single attempt: waiting for the server's ack would hang on a dead link
+                    let _ = channel.close();
+                    break (SessionState::Closed, "Connection closed by user.".to_string());
+                }
+                ShellEnd::Eof => {
+                    let _ = retry(|| channel.close());
+                    let _ = retry(|| channel.wait_close());
+                    let message = match channel.exit_status() {
+                        Ok(status) => format!("Connection closed. Exit status: {}", status),
+                        Err(_) => "Connection closed.".to_string(),
+                    };
+                    break (SessionState::Closed, message);
+                }
+                ShellEnd::Lost(error) => error,
+            };
+
+            emit_error(&app_handle, &session_id, error.clone());
+            let _ = ssh.disconnect(None, "Connection lost", None);
+            let Some(reconnect) = &reconnect else {
+                break (SessionState::Failed, format!("Connection lost. {}", error));
+            };
+            match reconnect_shell(&app_handle, &session_id, reconnect, &mut command_receiver, &mut size) {
+                ReconnectOutcome::Reconnected(new_ssh, new_channel) => {
+                    ssh = new_ssh;
+                    channel = new_channel;
+                    replace_transport(&sessions, &session_id, &ssh);
+                    if let Some(keepalive) = keepalive.as_mut() {
+                        keepalive.reset();
+                    }
+                    for command in &reconnect.policy.replay_commands {
+                        let line = format!("{}\r", command);
+                        if let Err(e) = write_all_nonblocking(&mut channel, line.as_bytes()) {
+                            emit_error(&app_handle, &session_id, format!("Failed to replay {:?}: {}", command, e));
+                        }
+                    }
+                    emit_state(&app_handle, &session_id, SessionState::Connected, None, Some("Reconnected".to_string()));
+                }
+                ReconnectOutcome::Cancelled => break (SessionState::Closed, "Connection closed by user.".to_string()),
+                ReconnectOutcome::GaveUp(e) => {
+                    break (
+                        SessionState::Failed,
+                        format!("Connection lost; gave up after {} reconnect attempts. {}", reconnect.policy.max_attempts, e),
+                    )
+                }
+            }
+        };
+
+        // --- Teardown ---
+        if state == SessionState::Closed {
+            let _ = retry(|| ssh.disconnect(None, "Session closed", None));
+        }
+        finish_session(&app_handle, &sessions, &session_id, state, exit_message);
+        println!("[{}] SSH I/O thread finished.", session_id);
+    });
+}
+
+// --- Tauri Commands ---
+
+// Resolves once the shell is open, or with a typed error once connecting or
+// authentication fails. `timeout_secs` bounds each network step (default 15s).
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn ssh_connect(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    hostname: String,
+    port: u16,
+    username: String,
+    password: Option<String>,
+    private_key_path: Option<String>,
+    passphrase: Option<String>,
+    certificate_path: Option<String>,
+    jump_hosts: Option<Vec<JumpHostConfig>>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+    reconnect: Option<ReconnectPolicy>,
+    keepalive: Option<KeepaliveConfig>,
+    timeout_secs: Option<u64>,
+    algorithms: Option<AlgorithmOverrides>,
+) -> Result<String, ConnectError> {
+    println!("Attempting native SSH connection to {}@{}:{}", username, hostname, port);
+
+    // Take ownership of the secrets right away so every copy is zeroized on drop
+    let password = password.map(Zeroizing::new);
+    if password.as_ref().is_some_and(|p| p.is_empty()) {
+        return Err(ConnectError::AuthFailed("Password provided but is empty.".to_string()));
+    }
+    // Blank fields from the connect dialog mean "not set"
+    let private_key_path = private_key_path.filter(|path| !path.trim().is_empty());
+    let passphrase = passphrase.map(Zeroizing::new).filter(|passphrase| !passphrase.is_empty());
+    let certificate_path = certificate_path.filter(|path| !path.trim().is_empty());
+
+    // Existing sessions stay open; each connection gets its own id
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let jump_hosts = jump_hosts.unwrap_or_default();
+    let algorithms = algorithms.unwrap_or_default();
+    let transport = Transport::ssh(private_key_path.clone(), certificate_path.clone(), &jump_hosts, algorithms.clone());
+    history::record_attempt(&app_handle, &session_id, SessionKind::Ssh, &hostname, port, &username, transport);
+    let cols = cols.unwrap_or(DEFAULT_PTY_COLS);
+    let rows = rows.unwrap_or(DEFAULT_PTY_ROWS);
+    let params = ConnectParams {
+        hostname: hostname.clone(),
+        port,
+        username: username.clone(),
+        password,
+        private_key_path,
+        passphrase,
+        certificate_path,
+        jump_hosts: jump_hosts.into_iter().map(ConnectParams::from).collect(),
+        algorithms,
+    };
+    let context = ConnectContext {
+        app_handle: app_handle.clone(),
+        session_id: session_id.clone(),
+        host_keys: HostKeyVerifier { app_handle: app_handle.clone(), prompts: state.hostkey_prompts.clone() },
+        keys: KeyUnlocker { app_handle: app_handle.clone(), prompts: state.passphrase_prompts.clone() },
+        auth_prompts: state.auth_prompts.clone(),
+        timeout: timeout_secs.filter(|secs| *secs > 0).map(Duration::from_secs).unwrap_or(DEFAULT_CONNECT_TIMEOUT),
+    };
+
+    emit_state(&app_handle, &session_id, SessionState::Connecting, None, None);
+
+    // Connect and authenticate on the blocking pool; libssh2 calls block.
+    // The params come back so a reconnect can log in the same way.
+    let (ssh, channel, params, context) = task::spawn_blocking(move || -> Result<_, ConnectError> {
+        let ssh = establish(&params, &context)?;
+        let channel = open_shell(&ssh, cols, rows).map_err(ConnectError::Protocol)?;
+        ssh.set_blocking(false);
+        Ok((ssh, channel, params, context))
+    })
+    .await
+    .map_err(|e| ConnectError::Other(format!("Connection task failed: {}", e)))
+    .and_then(|connected| connected)
+    .inspect_err(|e| {
+        emit_state(&app_handle, &session_id, SessionState::Failed, None, Some(e.to_string()));
+        history::record_failure(&app_handle, &session_id, e);
+    })?;
+
+    println!("[{}] Authenticated to {}@{}:{}", session_id, username, hostname, port);
+
+    let (command_tx, command_rx) = mpsc::channel::<SessionCommand>(32);
+
+    // --- Store Handle in State ---
+    {
+        let mut sessions_guard =
+            state.sessions.lock().map_err(|_| ConnectError::Other("Failed to lock state mutex".to_string()))?;
+        sessions_guard.insert(session_id.clone(), SessionHandle {
+            command_sender: command_tx,
+            ssh: Some(ssh.clone()),
+            forwards: HashMap::new(),
+            info: SessionInfo {
+                id: session_id.clone(),
+                kind: SessionKind::Ssh,
+                hostname,
+                port,
+                username,
+                connected_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+                latency_ms: None,
+            },
+        });
+    }
+
+    // Without a policy the credentials are dropped (and zeroized) here
+    let reconnect = reconnect
+        .filter(|policy| policy.max_attempts > 0)
+        .map(|policy| Reconnect { policy, params, context });
+    history::record_connected(&app_handle, &session_id);
+    emit_state(&app_handle, &session_id, SessionState::Connected, None, None);
+    let keepalive = Keepalive::new(&keepalive.unwrap_or_default());
+    spawn_shell_io(
+        app_handle,
+        session_id.clone(),
+        state.sessions.clone(),
+        ssh,
+        channel,
+        command_rx,
+        (cols, rows),
+        keepalive,
+        reconnect,
+    );
+
+    println!("[{}] SSH session setup completed successfully.", session_id);
+    Ok(session_id)
+}
+
+// Runs a command on its own exec channel of a non-blocking session.
+// Returns the exit status and the combined stdout/stderr. Blocking.
+pub fn run_exec(ssh: &Session, command: &str) -> Result<(i32, String), String> {
+    let mut channel = retry(|| ssh.channel_session()).map_err(|e| format!("Failed to open exec channel: {}", e))?;
+    retry(|| channel.exec(command)).map_err(|e| format!("Failed to execute command: {}", e))?;
+
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    let mut buffer = [0; 4096];
+    loop {
+        let mut progressed = false;
+        match channel.read(&mut buffer) {
+            Ok(0) => {}
+            Ok(n) => {
+                stdout.extend_from_slice(&buffer[..n]);
+                progressed = true;
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => return Err(format!("Error reading command output: {}", e)),
+        }
+        match channel.stderr().read(&mut buffer) {
+            Ok(0) => {}
+            Ok(n) => {
+                stderr.extend_from_slice(&buffer[..n]);
+                progressed = true;
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => return Err(format!("Error reading command error output: {}", e)),
+        }
+        if channel.eof() && !progressed {
+            break;
+        }
+        if !progressed {
+            thread::sleep(IDLE_POLL);
+        }
+    }
+    let _ = retry(|| channel.wait_close());
+
+    let mut output = String::from_utf8_lossy(&stdout).to_string();
+    output.push_str(&String::from_utf8_lossy(&stderr));
+    let status = channel.exit_status().map_err(|e| format!("Failed to read exit status: {}", e))?;
+    Ok((status, output))
+}
+
+// Runs a single command on its own exec channel and returns its output.
+// The interactive shell of the session is left untouched.
+#[command]
+pub async fn ssh_run_command(state: State<'_, AppState>, session_id: String, command: String) -> Result<String, String> {
+    println!("[{}] Running command over exec channel: {:?}", session_id, command);
+    let ssh = session_transport(&state.sessions, &session_id)?;
+
+    task::spawn_blocking(move || match run_exec(&ssh, &command)? {
+        (0, output) => Ok(output),
+        (status, output) => Err(format!("Command exited with status {}: {}", status, output)),
+    })
+    .await
+    .map_err(|e| format!("Command task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    fn hop(hostname: &str, port: Option<u16>, username: &str) -> JumpHostConfig {
+        JumpHostConfig {
+            hostname: hostname.to_string(),
+            port,
+            username: username.to_string(),
+            password: Some(String::new()),
+            private_key_path: Some("  ".to_string()),
+            passphrase: None,
+            certificate_path: None,
+            algorithms: AlgorithmOverrides::default(),
+        }
+    }
+
+    fn target(jump_hosts: Vec<JumpHostConfig>) -> ConnectParams {
+        ConnectParams {
+            hostname: "router".to_string(),
+            port: 2222,
+            username: "admin".to_string(),
+            password: None,
+            private_key_path: None,
+            passphrase: None,
+            certificate_path: None,
+            jump_hosts: jump_hosts.into_iter().map(ConnectParams::from).collect(),
+            algorithms: AlgorithmOverrides::default(),
+        }
+    }
+
+    #[test]
+    fn jump_hosts_default_port_and_drop_blank_fields() {
+        let params = ConnectParams::from(hop("bastion", None, "ops"));
+        assert_eq!(params.port, 22);
+        assert!(params.password.is_none());
+        assert!(params.private_key_path.is_none());
+    }
+
+    #[test]
+    fn jump_chain_labels_and_next_hops() {
+        let params = target(vec![hop("bastion", None, "ops"), hop("inner", Some(2200), "svc")]);
+        let hops: Vec<_> = params
+            .jump_hosts
+            .iter()
+            .enumerate()
+            .map(|(index, hop)| (jump_label(index, hop), next_hop(&params, index)))
+            .collect();
+        assert_eq!(
+            hops,
+            vec![
+                ("Jump host 1 (ops@bastion:22)".to_string(), ("inner", 2200)),
+                ("Jump host 2 (svc@inner:2200)".to_string(), ("router", 2222)),
+            ]
+        );
+    }
+
+    #[test]
+    fn hop_failures_keep_their_kind() {
+        let params = target(vec![hop("bastion", None, "ops")]);
+        let error = ConnectError::AuthFailed("Password rejected".to_string()).context(&jump_label(0, &params.jump_hosts[0]));
+        assert!(matches!(&error, ConnectError::AuthFailed(message) if message == "Jump host 1 (ops@bastion:22): Password rejected"));
+    }
+
+    #[test]
+    fn closed_port_is_refused() {
+        // Bind then drop to get a local port nothing listens on
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let error = connect_tcp("127.0.0.1", port, Duration::from_secs(5)).unwrap_err();
+        assert!(matches!(error, ConnectError::Refused(_)), "{:?}", error);
+    }
+
+    #[test]
+    fn unresolvable_host_is_dns() {
+        let error = connect_tcp("does-not-exist.invalid", 22, Duration::from_secs(5)).unwrap_err();
+        assert!(matches!(error, ConnectError::Dns(_)), "{:?}", error);
+    }
+
+    // Needs a reachable sshd: TERMAI_TEST_SSH_HOST (default 127.0.0.1),
+    // TERMAI_TEST_SSH_PORT (22), TERMAI_TEST_SSH_USER and TERMAI_TEST_SSH_PASSWORD.
+    // Run with `cargo test -- --ignored`.
+    #[test]
+    #[ignore]
+    fn local_sshd_login_and_exec() {
+        let env = |name: &str| std::env::var(name).ok();
+        let host = env("TERMAI_TEST_SSH_HOST").unwrap_or_else(|| "127.0.0.1".to_string());
+        let port = env("TERMAI_TEST_SSH_PORT").and_then(|port| port.parse().ok()).unwrap_or(22);
+        let user = env("TERMAI_TEST_SSH_USER").expect("TERMAI_TEST_SSH_USER is not set");
+        let password = env("TERMAI_TEST_SSH_PASSWORD").expect("TERMAI_TEST_SSH_PASSWORD is not set");
+
+        let mut session = Session::new().unwrap();
+        session.set_tcp_stream(connect_tcp(&host, port, DEFAULT_CONNECT_TIMEOUT).unwrap());
+        session.handshake().unwrap();
+        assert!(session.userauth_password(&user, "definitely-not-the-password").is_err());
+        session.userauth_password(&user, &password).unwrap();
+        session.set_blocking(false);
+
+        let (status, output) = run_exec(&session, "echo termai").unwrap();
+        assert_eq!((status, output.trim()), (0, "termai"));
+    }
+}
@@ -0,0 +1,533 @@
+// Importer for OpenSSH client configs (~/.ssh/config). Every concrete `Host`
+// alias becomes a profile, with the settings ssh(1) would use for it: the first
+// value of each keyword wins, wildcard blocks apply to the aliases they match,
+// `Include` is followed and `Match` blocks are skipped. Directives a profile
+// cannot express are reported instead of silently dropped.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tauri::{command, AppHandle};
+
+use crate::import::{self, ImportReport, SkippedEntry, UnsupportedLog};
+use crate::keys::{expand_home, home_dir};
+use crate::local_shell::local_username;
+use crate::profiles::{AuthMethod, Profile, ProfileJumpHost, ProfileProtocol};
+
+// Guards against Include loops
+const MAX_INCLUDE_DEPTH: usize = 16;
+// Guards against ProxyJump loops between aliases
+const MAX_JUMP_DEPTH: usize = 8;
+// Keywords carried over into profiles; everything else is reported
+const SUPPORTED: [&str; 9] =
+    ["host", "match", "include", "hostname", "port", "user", "identityfile", "certificatefile", "proxyjump"];
+const IMPORT_TAG: &str = "ssh-config";
+
+// --- Parsing ---
+
+struct Directive {
+    keyword: String, // Lowercased
+    args: Vec<String>,
+    location: String,
+}
+
+// What a run of directives applies to
+enum Condition {
+    Always,            // Before the first Host/Match line
+    Host(Vec<String>), // Host patterns, possibly negated ("!pattern")
+    Match,             // Never evaluated; Match criteria are not supported
+}
+
+struct Section {
+    condition: Condition,
+    directives: Vec<Directive>,
+}
+
+// Splits a line into keyword and arguments. Accepts "Keyword value",
+// "Keyword=value", double-quoted arguments and trailing comments.
+fn split_line(line: &str) -> Option<(String, Vec<String>)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let keyword_end = line.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(line.len());
+    let keyword = line[..keyword_end].to_lowercase();
+    let rest = line[keyword_end..].trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest);
+
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut in_arg = false;
+    for c in rest.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_arg = true;
+            }
+            // An unquoted # starting an argument comments out the rest of the line
+            '#' if !quoted && !in_arg => break,
+            c if c.is_whitespace() && !quoted => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Some((keyword, args))
+}
+
+// Include arguments are relative to the directory of the top-level config (~/.ssh)
+fn include_paths(pattern: &str, base_dir: &Path) -> Vec<PathBuf> {
+    let path = expand_home(pattern);
+    let path = if path.is_absolute() { path } else { base_dir.join(path) };
+    let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    if !file_name.contains(['*', '?']) {
+        return vec![path];
+    }
+    // Wildcards in the file name only, which covers "config.d/*"
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let mut matches: Vec<PathBuf> = fs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| wildcard_match(&file_name, &entry.file_name().to_string_lossy(), false))
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file())
+                .collect()
+        })
+        .unwrap_or_default();
+    // ssh(1) reads glob results in sorted order
+    matches.sort();
+    matches
+}
+
+fn parse_file(
+    path: &Path,
+    base_dir: &Path,
+    depth: usize,
+    sections: &mut Vec<Section>,
+    unsupported: &mut UnsupportedLog,
+) -> Result<(), String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    parse_config(&contents, path, base_dir, depth, sections, unsupported)
+}
+
+// Appends the blocks of one config file's text; `path` is used in locations
+fn parse_config(
+    contents: &str,
+    path: &Path,
+    base_dir: &Path,
+    depth: usize,
+    sections: &mut Vec<Section>,
+    unsupported: &mut UnsupportedLog,
+) -> Result<(), String> {
+    for (index, line) in contents.lines().enumerate() {
+        let Some((keyword, args)) = split_line(line) else {
+            continue;
+        };
+        let location = format!("{}:{}", path.display(), index + 1);
+        match keyword.as_str() {
+            "host" => sections.push(Section { condition: Condition::Host(args), directives: Vec::new() }),
+            "match" => {
+                unsupported.note("Match", location);
+                sections.push(Section { condition: Condition::Match, directives: Vec::new() });
+            }
+            // Included lines continue the current block, just as in ssh(1)
+            "include" => {
+                if depth >= MAX_INCLUDE_DEPTH {
+                    return Err(format!("{}: Include nested too deeply", location));
+                }
+                for pattern in &args {
+                    for included in include_paths(pattern, base_dir) {
+                        if included.is_file() {
+                            parse_file(&included, base_dir, depth + 1, sections, unsupported)?;
+                        }
+                    }
+                }
+            }
+            _ => {
+                let section = sections.last_mut().expect("the first section is always present");
+                // Directives inside Match blocks never apply, so they are not reported one by one
+                if !matches!(section.condition, Condition::Match) && !SUPPORTED.contains(&keyword.as_str()) {
+                    unsupported.note(&keyword, location.clone());
+                }
+                section.directives.push(Directive { keyword, args, location });
+            }
+        }
+    }
+    Ok(())
+}
+
+// --- Matching ---
+
+// Glob match with `*` and `?`, as used by Host patterns and Include
+fn wildcard_match(pattern: &str, text: &str, ignore_case: bool) -> bool {
+    let (pattern, text): (Vec<char>, Vec<char>) = if ignore_case {
+        (pattern.to_lowercase().chars().collect(), text.to_lowercase().chars().collect())
+    } else {
+        (pattern.chars().collect(), text.chars().collect())
+    };
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // Let the last `*` swallow one more character
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn host_matches(patterns: &[String], alias: &str) -> bool {
+    let mut matched = false;
+    for pattern in patterns {
+        match pattern.strip_prefix('!') {
+            Some(negated) if wildcard_match(negated, alias, true) => return false,
+            Some(_) => {}
+            None => matched |= wildcard_match(pattern, alias, true),
+        }
+    }
+    matched
+}
+
+fn is_concrete(pattern: &str) -> bool {
+    !pattern.contains(['*', '?', '!'])
+}
+
+// --- Resolution ---
+
+// The effective settings for one alias; the first value of each keyword wins
+#[derive(Default)]
+struct HostSettings {
+    hostname: Option<String>,
+    port: Option<String>,
+    user: Option<String>,
+    identity_file: Option<String>,
+    certificate_file: Option<String>,
+    proxy_jump: Option<String>,
+}
+
+fn resolve(sections: &[Section], alias: &str) -> (HostSettings, Vec<String>) {
+    let mut settings = HostSettings::default();
+    let mut errors = Vec::new();
+    for section in sections {
+        let applies = match &section.condition {
+            Condition::Always => true,
+            Condition::Host(patterns) => host_matches(patterns, alias),
+            Condition::Match => false,
+        };
+        if !applies {
+            continue;
+        }
+        for directive in &section.directives {
+            let slot = match directive.keyword.as_str() {
+                "hostname" => &mut settings.hostname,
+                "port" => &mut settings.port,
+                "user" => &mut settings.user,
+                "identityfile" => &mut settings.identity_file,
+                "certificatefile" => &mut settings.certificate_file,
+                "proxyjump" => &mut settings.proxy_jump,
+                _ => continue,
+            };
+            if slot.is_some() {
+                continue;
+            }
+            match directive.args.first() {
+                Some(value) => *slot = Some(value.clone()),
+                None => errors.push(format!("{}: {} has no value", directive.location, directive.keyword)),
+            }
+        }
+    }
+    (settings, errors)
+}
+
+// Expands the % tokens ssh(1) allows in HostName, IdentityFile and CertificateFile
+fn expand_tokens(value: &str, alias: &str, hostname: &str, user: &str) -> String {
+    let home = home_dir().map(|home| home.display().to_string()).unwrap_or_default();
+    let mut expanded = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => expanded.push('%'),
+            Some('h') => expanded.push_str(hostname),
+            Some('n') => expanded.push_str(alias),
+            Some('r') => expanded.push_str(user),
+            Some('u') => expanded.push_str(&local_username()),
+            Some('d') => expanded.push_str(&home),
+            Some(other) => {
+                expanded.push('%');
+                expanded.push(other);
+            }
+            None => expanded.push('%'),
+        }
+    }
+    expanded
+}
+
+fn parse_port(value: Option<&str>, alias: &str) -> Result<u16, String> {
+    match value {
+        Some(port) => port.parse().map_err(|_| format!("Invalid port {:?} for {}", port, alias)),
+        None => Ok(22),
+    }
+}
+
+// Turns a ProxyJump value into hops. Hops that are themselves aliases take
+// their settings (including their own ProxyJump) from the config.
+fn jump_hosts(sections: &[Section], value: &str, depth: usize) -> Result<Vec<ProfileJumpHost>, String> {
+    if value.eq_ignore_ascii_case("none") {
+        return Ok(Vec::new());
+    }
+    if depth >= MAX_JUMP_DEPTH {
+        return Err(format!("ProxyJump chain through {} is too long (or loops)", value));
+    }
+    let mut hops = Vec::new();
+    for spec in value.split(',').map(str::trim).filter(|spec| !spec.is_empty()) {
+        let spec = spec.strip_prefix("ssh://").unwrap_or(spec);
+        let (user, host_port) = match spec.rsplit_once('@') {
+            Some((user, host_port)) => (Some(user.to_string()), host_port),
+            None => (None, spec),
+        };
+        let (alias, port) = match host_port.rsplit_once(':') {
+            Some((host, port)) if !host.contains(':') => (host, Some(port)),
+            _ => (host_port, None),
+        };
+
+        let (settings, errors) = resolve(sections, alias);
+        if let Some(error) = errors.into_iter().next() {
+            return Err(error);
+        }
+        if let Some(inner) = &settings.proxy_jump {
+            hops.extend(jump_hosts(sections, inner, depth + 1)?);
+        }
+        let username = user.or(settings.user).unwrap_or_else(local_username);
+        let hostname = settings.hostname.map(|hostname| expand_tokens(&hostname, alias, alias, &username));
+        let hostname = hostname.unwrap_or_else(|| alias.to_string());
+        let port = parse_port(port.or(settings.port.as_deref()), alias)?;
+        hops.push(ProfileJumpHost {
+            private_key_path: settings.identity_file.map(|path| expand_tokens(&path, alias, &hostname, &username)),
+            certificate_path: settings.certificate_file.map(|path| expand_tokens(&path, alias, &hostname, &username)),
+            hostname,
+            port,
+            username,
+            password_secret_id: None,
+            passphrase_secret_id: None,
+            algorithms: Default::default(),
+        });
+    }
+    Ok(hops)
+}
+
+fn profile_for(sections: &[Section], alias: &str) -> Result<Profile, String> {
+    let (settings, errors) = resolve(sections, alias);
+    if let Some(error) = errors.into_iter().next() {
+        return Err(error);
+    }
+    let username = settings.user.unwrap_or_else(local_username);
+    let hostname = match settings.hostname {
+        Some(hostname) => expand_tokens(&hostname, alias, alias, &username),
+        None => alias.to_string(),
+    };
+    let private_key_path = settings.identity_file.map(|path| expand_tokens(&path, alias, &hostname, &username));
+    let certificate_path = settings.certificate_file.map(|path| expand_tokens(&path, alias, &hostname, &username));
+    let jump_hosts = match &settings.proxy_jump {
+        Some(value) => jump_hosts(sections, value, 0)?,
+        None => Vec::new(),
+    };
+    Ok(Profile {
+        id: String::new(),
+        name: alias.to_string(),
+        protocol: ProfileProtocol::Ssh,
+        port: parse_port(settings.port.as_deref(), alias)?,
+        hostname,
+        username,
+        auth_method: if private_key_path.is_some() { AuthMethod::Key } else { AuthMethod::Agent },
+        private_key_path,
+        certificate_path,
+        password_secret_id: None,
+        passphrase_secret_id: None,
+        jump_hosts,
+        tags: vec![IMPORT_TAG.to_string()],
+        metadata: BTreeMap::new(),
+        post_login_commands: Vec::new(),
+        algorithms: Default::default(),
+        reconnect: None,
+        keepalive: None,
+        timeout_secs: None,
+        source: None,
+        created_at: 0,
+        updated_at: 0,
+    })
+}
+
+// --- Tauri Commands ---
+
+// Imports the Host aliases of an OpenSSH config (default ~/.ssh/config) as
+// profiles. With `dryRun` nothing is saved and the report previews the changes.
+#[command]
+pub async fn import_ssh_config(app_handle: AppHandle, path: Option<String>, dry_run: Option<bool>) -> Result<ImportReport, String> {
+    let path = match path.filter(|path| !path.trim().is_empty()) {
+        Some(path) => expand_home(&path),
+        None => home_dir().ok_or("Cannot locate the home directory")?.join(".ssh").join("config"),
+    };
+    let base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    println!("Importing OpenSSH config {}", path.display());
+
+    let mut unsupported = UnsupportedLog::default();
+    let mut sections = vec![Section { condition: Condition::Always, directives: Vec::new() }];
+    parse_file(&path, &base_dir, 0, &mut sections, &mut unsupported)?;
+
+    let mut aliases: Vec<&str> = Vec::new();
+    for section in &sections {
+        if let Condition::Host(patterns) = &section.condition {
+            for pattern in patterns.iter().filter(|pattern| is_concrete(pattern)) {
+                if !aliases.contains(&pattern.as_str()) {
+                    aliases.push(pattern);
+                }
+            }
+        }
+    }
+
+    let mut candidates = Vec::new();
+    let mut skipped = Vec::new();
+    for alias in aliases {
+        match profile_for(&sections, alias) {
+            Ok(profile) => candidates.push(profile),
+            Err(reason) => skipped.push(SkippedEntry { name: alias.to_string(), reason }),
+        }
+    }
+
+    let source = format!("ssh_config:{}", path.display());
+    import::apply(&app_handle, source, candidates, skipped, unsupported, dry_run.unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str, base_dir: &Path) -> (Vec<Section>, UnsupportedLog) {
+        let mut unsupported = UnsupportedLog::default();
+        let mut sections = vec![Section { condition: Condition::Always, directives: Vec::new() }];
+        parse_config(text, Path::new("config"), base_dir, 0, &mut sections, &mut unsupported).unwrap();
+        (sections, unsupported)
+    }
+
+    fn patterns(patterns: &[&str]) -> Vec<String> {
+        patterns.iter().map(|pattern| pattern.to_string()).collect()
+    }
+
+    #[test]
+    fn splits_keywords_and_arguments() {
+        assert_eq!(split_line("  Port=2222"), Some(("port".to_string(), vec!["2222".to_string()])));
+        assert_eq!(
+            split_line(r#"IdentityFile "~/keys/my key" # work"#),
+            Some(("identityfile".to_string(), vec!["~/keys/my key".to_string()]))
+        );
+        assert_eq!(split_line("# comment"), None);
+    }
+
+    #[test]
+    fn wildcard_patterns() {
+        assert!(wildcard_match("web-*", "web-01", false));
+        assert!(wildcard_match("sw?-core", "sw1-core", false));
+        assert!(wildcard_match("*.lab.*", "r1.lab.example", false));
+        assert!(!wildcard_match("sw?-core", "sw10-core", false));
+        assert!(!wildcard_match("Web-*", "web-01", false));
+        assert!(wildcard_match("Web-*", "web-01", true));
+    }
+
+    #[test]
+    fn negated_patterns_exclude() {
+        assert!(host_matches(&patterns(&["*", "!bastion"]), "router"));
+        assert!(!host_matches(&patterns(&["*", "!bastion"]), "bastion"));
+        assert!(!host_matches(&patterns(&["!bastion-*", "bastion-*"]), "bastion-eu"));
+        // A negation alone matches nothing
+        assert!(!host_matches(&patterns(&["!bastion"]), "router"));
+    }
+
+    #[test]
+    fn first_value_wins() {
+        let (sections, _) = parse(
+            "User global\n\
+             Host router\n  Port 2200\n  HostName 10.0.0.1\n\
+             Host r* !relay\n  Port 2222\n  IdentityFile ~/.ssh/lab\n\
+             Host *\n  User fallback\n  HostName %h.example\n",
+            Path::new("/"),
+        );
+        let (router, errors) = resolve(&sections, "router");
+        assert!(errors.is_empty());
+        assert_eq!(router.user.as_deref(), Some("global"));
+        assert_eq!(router.port.as_deref(), Some("2200"));
+        assert_eq!(router.hostname.as_deref(), Some("10.0.0.1"));
+        assert_eq!(router.identity_file.as_deref(), Some("~/.ssh/lab"));
+
+        let (relay, _) = resolve(&sections, "relay");
+        assert_eq!(relay.port, None);
+        assert_eq!(relay.identity_file, None);
+        assert_eq!(relay.hostname.as_deref(), Some("%h.example"));
+    }
+
+    #[test]
+    fn match_blocks_are_skipped_and_reported() {
+        let (sections, unsupported) = parse("Match host router\n  User ignored\nHost router\n  User admin\n  ForwardAgent yes\n", Path::new("/"));
+        assert_eq!(resolve(&sections, "router").0.user.as_deref(), Some("admin"));
+        let reported: Vec<_> = unsupported.0.iter().map(|item| item.directive.as_str()).collect();
+        assert_eq!(reported, ["Match", "forwardagent"]);
+    }
+
+    #[test]
+    fn include_continues_the_current_block() {
+        let dir = std::env::temp_dir().join(format!("termai-ssh-config-{}", std::process::id()));
+        fs::create_dir_all(dir.join("config.d")).unwrap();
+        fs::write(dir.join("config.d").join("10-lab"), "Host lab\n  HostName 10.0.0.5\n").unwrap();
+        fs::write(dir.join("config.d").join("20-lab"), "Host lab\n  HostName 10.0.0.6\n  User lab\n").unwrap();
+        fs::write(dir.join("shared"), "Port 2022\n").unwrap();
+
+        let (sections, _) = parse("Host lab\n  Include shared\nInclude config.d/*\nHost *\n  User ops\n", &dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let (lab, _) = resolve(&sections, "lab");
+        assert_eq!(lab.port.as_deref(), Some("2022"));
+        assert_eq!(lab.hostname.as_deref(), Some("10.0.0.5"));
+        assert_eq!(lab.user.as_deref(), Some("lab"));
+        // `shared` was included inside `Host lab`, so it does not apply elsewhere
+        assert_eq!(resolve(&sections, "other").0.port, None);
+    }
+
+    #[test]
+    fn proxy_jump_aliases_resolve_through_the_config() {
+        let (sections, _) = parse(
+            "Host edge\n  HostName 203.0.113.10\n  User ops\n\
+             Host core\n  HostName 10.0.0.1\n  User ops\n  ProxyJump edge\n\
+             Host router\n  User admin\n  ProxyJump core,admin@10.0.0.2:2200\n",
+            Path::new("/"),
+        );
+        let profile = profile_for(&sections, "router").unwrap();
+        let hops: Vec<_> = profile.jump_hosts.iter().map(|hop| (hop.username.as_str(), hop.hostname.as_str(), hop.port)).collect();
+        assert_eq!(hops, [("ops", "203.0.113.10", 22), ("ops", "10.0.0.1", 22), ("admin", "10.0.0.2", 2200)]);
+        assert_eq!(profile.auth_method, AuthMethod::Agent);
+    }
+}
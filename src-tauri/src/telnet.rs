@@ -0,0 +1,544 @@
+// Telnet transport for older switches and terminal servers without SSH.
+// Speaks just enough of the protocol for a character-mode session (RFC 854
+// option negotiation, NAWS window size, TERMINAL-TYPE, the USER variable of
+// NEW-ENVIRON for the login name) and plugs into the same
+// session registry, `ssh-*` events and write/resize/disconnect commands as SSH.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Read};
+use std::net::{Shutdown, TcpStream};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tauri::{command, AppHandle, State};
+use tokio::sync::mpsc::{self, error::TryRecvError, Receiver};
+use tokio::task;
+
+use crate::history::{self, Transport};
+use crate::session::{
+    emit_error, emit_output, emit_state, finish_session, SessionCommand, SessionHandle, SessionInfo, SessionKind,
+    SessionMap, SessionState, DEFAULT_PTY_COLS, DEFAULT_PTY_ROWS,
+};
+use crate::ssh_client::{connect_tcp, write_all_nonblocking, DEFAULT_CONNECT_TIMEOUT, IDLE_POLL};
+use crate::AppState;
+
+const DEFAULT_TELNET_PORT: u16 = 23;
+// Sent when the server asks for our terminal type and the frontend gave none
+const DEFAULT_TERMINAL_TYPE: &str = "XTERM-256COLOR";
+// Longest subnegotiation we buffer; anything longer is malformed
+const MAX_SUBNEGOTIATION: usize = 1024;
+
+// Commands (RFC 854)
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const BRK: u8 = 243;
+const SE: u8 = 240;
+
+// Options
+const OPT_BINARY: u8 = 0; // RFC 856
+const OPT_ECHO: u8 = 1; // RFC 857
+const OPT_SGA: u8 = 3; // Suppress go-ahead, RFC 858
+const OPT_TTYPE: u8 = 24; // RFC 1091
+const OPT_NAWS: u8 = 31; // Window size, RFC 1073
+const OPT_NEW_ENVIRON: u8 = 39; // RFC 1572
+
+// TERMINAL-TYPE subcommands
+const TTYPE_IS: u8 = 0;
+const TTYPE_SEND: u8 = 1;
+
+// NEW-ENVIRON subcommands and type bytes
+const ENV_IS: u8 = 0;
+const ENV_SEND: u8 = 1;
+const ENV_VAR: u8 = 0;
+const ENV_VALUE: u8 = 1;
+const ENV_ESC: u8 = 2;
+const ENV_USERVAR: u8 = 3;
+
+// --- Protocol ---
+
+#[derive(Clone, Copy)]
+enum ParseState {
+    Data,
+    Cr,            // After a CR in the data stream; a following NUL is dropped
+    Iac,           // After IAC
+    Negotiate(u8), // After IAC DO/DONT/WILL/WONT, waiting for the option
+    Sub,           // Inside IAC SB ... IAC SE
+    SubIac,        // After IAC inside a subnegotiation
+}
+
+// Telnet protocol state for one connection. Only replies to option requests
+// that change an option's state, so negotiation can never loop.
+struct Telnet {
+    state: ParseState,
+    subnegotiation: Vec<u8>,
+    local: HashSet<u8>,  // Options we perform (we said WILL)
+    remote: HashSet<u8>, // Options the server performs (it said WILL)
+    terminal_type: String,
+    username: Option<String>, // Offered as USER so the server can skip its login prompt
+    cols: u16,
+    rows: u16,
+}
+
+impl Telnet {
+    fn new(terminal_type: String, username: Option<String>, cols: u16, rows: u16) -> Self {
+        Telnet {
+            state: ParseState::Data,
+            subnegotiation: Vec::new(),
+            local: HashSet::new(),
+            remote: HashSet::new(),
+            terminal_type,
+            username,
+            cols,
+            rows,
+        }
+    }
+
+    // Offers window size and terminal type and asks for character mode
+    fn initial_requests(&mut self) -> Vec<u8> {
+        self.local.extend([OPT_NAWS, OPT_TTYPE]);
+        self.remote.insert(OPT_SGA);
+        vec![IAC, WILL, OPT_NAWS, IAC, WILL, OPT_TTYPE, IAC, DO, OPT_SGA]
+    }
+
+    // Splits received bytes into terminal data and the replies owed to the server
+    fn receive(&mut self, input: &[u8], data: &mut Vec<u8>, reply: &mut Vec<u8>) {
+        for &byte in input {
+            self.state = match (self.state, byte) {
+                (ParseState::Cr, 0) => ParseState::Data,
+                (ParseState::Data | ParseState::Cr, IAC) => ParseState::Iac,
+                (ParseState::Data | ParseState::Cr, b'\r') => {
+                    data.push(byte);
+                    ParseState::Cr
+                }
+                (ParseState::Data | ParseState::Cr, _) => {
+                    data.push(byte);
+                    ParseState::Data
+                }
+                (ParseState::Iac, IAC) => {
+                    data.push(IAC);
+                    ParseState::Data
+                }
+                (ParseState::Iac, DO | DONT | WILL | WONT) => ParseState::Negotiate(byte),
+                (ParseState::Iac, SB) => {
+                    self.subnegotiation.clear();
+                    ParseState::Sub
+                }
+                // NOP, GA, AYT and friends carry nothing for the terminal
+                (ParseState::Iac, _) => ParseState::Data,
+                (ParseState::Negotiate(verb), option) => {
+                    self.negotiate(verb, option, reply);
+                    ParseState::Data
+                }
+                (ParseState::Sub, IAC) => ParseState::SubIac,
+                (ParseState::Sub, _) => {
+                    if self.subnegotiation.len() < MAX_SUBNEGOTIATION {
+                        self.subnegotiation.push(byte);
+                    }
+                    ParseState::Sub
+                }
+                (ParseState::SubIac, IAC) => {
+                    if self.subnegotiation.len() < MAX_SUBNEGOTIATION {
+                        self.subnegotiation.push(IAC);
+                    }
+                    ParseState::Sub
+                }
+                (ParseState::SubIac, SE) => {
+                    self.subnegotiate(reply);
+                    ParseState::Data
+                }
+                (ParseState::SubIac, _) => ParseState::Data,
+            };
+        }
+    }
+
+    fn negotiate(&mut self, verb: u8, option: u8, reply: &mut Vec<u8>) {
+        let local_supported = matches!(option, OPT_BINARY | OPT_SGA | OPT_TTYPE | OPT_NAWS)
+            || (option == OPT_NEW_ENVIRON && self.username.is_some());
+        let remote_supported = matches!(option, OPT_BINARY | OPT_ECHO | OPT_SGA);
+        match verb {
+            DO if !local_supported => reply.extend([IAC, WONT, option]),
+            DO => {
+                if self.local.insert(option) {
+                    reply.extend([IAC, WILL, option]);
+                }
+                // Send the current size on every DO NAWS, including the reply to our own WILL
+                if option == OPT_NAWS {
+                    reply.extend(self.window_size());
+                }
+            }
+            WILL if !remote_supported => reply.extend([IAC, DONT, option]),
+            WILL if self.remote.insert(option) => reply.extend([IAC, DO, option]),
+            DONT if self.local.remove(&option) => reply.extend([IAC, WONT, option]),
+            WONT if self.remote.remove(&option) => reply.extend([IAC, DONT, option]),
+            // Already in the requested state: no reply
+            _ => {}
+        }
+    }
+
+    fn subnegotiate(&mut self, reply: &mut Vec<u8>) {
+        if self.subnegotiation.as_slice() == [OPT_TTYPE, TTYPE_SEND] && self.local.contains(&OPT_TTYPE) {
+            reply.extend([IAC, SB, OPT_TTYPE, TTYPE_IS]);
+            reply.extend(escape_iac(self.terminal_type.as_bytes()));
+            reply.extend([IAC, SE]);
+        }
+        if let [OPT_NEW_ENVIRON, ENV_SEND, requested @ ..] = self.subnegotiation.as_slice() {
+            // An empty SEND asks for everything; otherwise only answer when USER is listed
+            let wants_user = requested.is_empty() || requested.windows(5).any(|w| w == [ENV_VAR, b'U', b'S', b'E', b'R']);
+            if let Some(username) = self.username.as_ref().filter(|_| wants_user && self.local.contains(&OPT_NEW_ENVIRON)) {
+                reply.extend([IAC, SB, OPT_NEW_ENVIRON, ENV_IS]);
+                reply.extend(escape_iac(&environ_user(username)));
+                reply.extend([IAC, SE]);
+            }
+        }
+    }
+
+    // IAC SB NAWS <cols> <rows> IAC SE, 16-bit big-endian each
+    fn window_size(&self) -> Vec<u8> {
+        let mut size = Vec::with_capacity(4);
+        size.extend(self.cols.to_be_bytes());
+        size.extend(self.rows.to_be_bytes());
+        let mut message = vec![IAC, SB, OPT_NAWS];
+        message.extend(escape_iac(&size));
+        message.extend([IAC, SE]);
+        message
+    }
+
+    // Returns the NAWS update to send, if the server asked for window sizes
+    fn resize(&mut self, cols: u16, rows: u16) -> Option<Vec<u8>> {
+        self.cols = cols;
+        self.rows = rows;
+        self.local.contains(&OPT_NAWS).then(|| self.window_size())
+    }
+
+    // Prepares terminal input for the wire: IAC is doubled and, outside binary
+    // mode, a bare CR becomes CR NUL as the NVT rules require
+    fn encode(&self, input: &[u8]) -> Vec<u8> {
+        let binary = self.local.contains(&OPT_BINARY);
+        let mut output = Vec::with_capacity(input.len() + 8);
+        for (i, &byte) in input.iter().enumerate() {
+            output.push(byte);
+            if byte == IAC {
+                output.push(IAC);
+            } else if byte == b'\r' && !binary && input.get(i + 1) != Some(&b'\n') {
+                output.push(0);
+            }
+        }
+        output
+    }
+}
+
+// VAR "USER" VALUE <username>, escaping bytes that would read as type codes
+fn environ_user(username: &str) -> Vec<u8> {
+    let mut variable = vec![ENV_VAR];
+    variable.extend(b"USER");
+    variable.push(ENV_VALUE);
+    for &byte in username.as_bytes() {
+        if matches!(byte, ENV_VAR | ENV_VALUE | ENV_ESC | ENV_USERVAR) {
+            variable.push(ENV_ESC);
+        }
+        variable.push(byte);
+    }
+    variable
+}
+
+fn escape_iac(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|&byte| if byte == IAC { vec![IAC, IAC] } else { vec![byte] }).collect()
+}
+
+// --- Telnet I/O Loop ---
+
+// Owns the socket: decodes server output for the frontend, answers option
+// negotiation and applies write/resize/disconnect commands until either side closes.
+fn spawn_telnet_io(
+    app_handle: AppHandle,
+    session_id: String,
+    sessions: SessionMap,
+    mut stream: TcpStream,
+    mut telnet: Telnet,
+    mut command_receiver: Receiver<SessionCommand>,
+) {
+    thread::spawn(move || {
+        println!("[{}] Telnet I/O thread started.", session_id);
+        let mut buffer = [0; 4096];
+        let mut data = Vec::with_capacity(4096);
+        let mut reply = Vec::new();
+        let mut exit_message = "Connection closed by remote host.".to_string();
+
+        if let Err(e) = write_all_nonblocking(&mut stream, &telnet.initial_requests()) {
+            emit_error(&app_handle, &session_id, format!("Error negotiating Telnet options: {}", e));
+        }
+
+        'io: loop {
+            let mut idle = true;
+
+            loop {
+                let outgoing = match command_receiver.try_recv() {
+                    Ok(SessionCommand::Write(input)) => telnet.encode(&input),
+                    Ok(SessionCommand::Resize { cols, rows }) => telnet.resize(cols, rows).unwrap_or_default(),
+                    // Terminal servers turn this into a BREAK on their serial line
+                    Ok(SessionCommand::Break(_)) => vec![IAC, BRK],
+                    Ok(SessionCommand::Disconnect) => {
+                        println!("[{}] I/O thread received disconnect.", session_id);
+                        exit_message = "Connection closed by user.".to_string();
+                        break 'io;
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        exit_message = "Connection closed by user.".to_string();
+                        break 'io;
+                    }
+                };
+                idle = false;
+                if let Err(e) = write_all_nonblocking(&mut stream, &outgoing) {
+                    emit_error(&app_handle, &session_id, format!("Error writing to Telnet connection: {}", e));
+                }
+            }
+
+            match stream.read(&mut buffer) {
+                Ok(0) => {
+                    println!("[{}] Telnet connection EOF reached.", session_id);
+                    break;
+                }
+                Ok(n) => {
+                    idle = false;
+                    data.clear();
+                    reply.clear();
+                    telnet.receive(&buffer[..n], &mut data, &mut reply);
+                    if !reply.is_empty() {
+                        if let Err(e) = write_all_nonblocking(&mut stream, &reply) {
+                            emit_error(&app_handle, &session_id, format!("Error negotiating Telnet options: {}", e));
+                        }
+                    }
+                    if !data.is_empty() {
+                        emit_output(&app_handle, &session_id, &data);
+                    }
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    emit_error(&app_handle, &session_id, format!("Error reading Telnet connection: {}", e));
+                    break;
+                }
+            }
+
+            if idle {
+                thread::sleep(IDLE_POLL);
+            }
+        }
+
+        let _ = stream.shutdown(Shutdown::Both);
+        finish_session(&app_handle, &sessions, &session_id, SessionState::Closed, exit_message);
+        println!("[{}] Telnet I/O thread finished.", session_id);
+    });
+}
+
+// --- Tauri Commands ---
+
+// Opens a Telnet session and returns its id; output and errors arrive on the
+// same events as SSH sessions and `write_to_ssh`/`disconnect_ssh` work unchanged.
+// `username` is offered to servers that ask for it (NEW-ENVIRON USER) and
+// `timeout_secs` bounds the TCP connect (default 15s).
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn telnet_connect(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    hostname: String,
+    port: Option<u16>,
+    username: Option<String>,
+    terminal_type: Option<String>,
+    cols: Option<u16>,
+    rows: Option<u16>,
+    timeout_secs: Option<u64>,
+) -> Result<String, String> {
+    let port = port.unwrap_or(DEFAULT_TELNET_PORT);
+    println!("Attempting Telnet connection to {}:{}", hostname, port);
+
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let terminal_type = terminal_type
+        .map(|t| t.trim().to_ascii_uppercase())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| DEFAULT_TERMINAL_TYPE.to_string());
+    let username = username.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+
+    let transport = Transport::Telnet { terminal_type: Some(terminal_type.clone()) };
+    history::record_attempt(&app_handle, &session_id, SessionKind::Telnet, &hostname, port, username.as_deref().unwrap_or_default(), transport);
+    let telnet = Telnet::new(terminal_type, username.clone(), cols.unwrap_or(DEFAULT_PTY_COLS), rows.unwrap_or(DEFAULT_PTY_ROWS));
+
+    let timeout = timeout_secs.filter(|secs| *secs > 0).map(Duration::from_secs).unwrap_or(DEFAULT_CONNECT_TIMEOUT);
+
+    emit_state(&app_handle, &session_id, SessionState::Connecting, None, None);
+
+    let target = hostname.clone();
+    let stream = task::spawn_blocking(move || connect_tcp(&target, port, timeout).map_err(String::from))
+        .await
+        .map_err(|e| format!("Connection task failed: {}", e))
+        .and_then(|connected| connected)
+        .and_then(|stream| {
+            stream.set_nonblocking(true).map_err(|e| format!("Failed to configure Telnet socket: {}", e))?;
+            Ok(stream)
+        })
+        .inspect_err(|e| {
+            emit_state(&app_handle, &session_id, SessionState::Failed, None, Some(e.clone()));
+            history::record_failure(&app_handle, &session_id, e);
+        })?;
+
+    println!("[{}] Connected to {}:{} over Telnet", session_id, hostname, port);
+
+    let (command_tx, command_rx) = mpsc::channel::<SessionCommand>(32);
+    {
+        let mut sessions_guard = state.sessions.lock().map_err(|_| "Failed to lock state mutex".to_string())?;
+        sessions_guard.insert(session_id.clone(), SessionHandle {
+            command_sender: command_tx,
+            ssh: None,
+            forwards: HashMap::new(),
+            info: SessionInfo {
+                id: session_id.clone(),
+                kind: SessionKind::Telnet,
+                hostname,
+                port,
+                username: username.unwrap_or_default(), // Empty when the login happens in the terminal
+                connected_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+                latency_ms: None,
+            },
+        });
+    }
+
+    history::record_connected(&app_handle, &session_id);
+    emit_state(&app_handle, &session_id, SessionState::Connected, None, None);
+    spawn_telnet_io(app_handle, session_id.clone(), state.sessions.clone(), stream, telnet, command_rx);
+    Ok(session_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn telnet() -> Telnet {
+        Telnet::new("VT100".to_string(), None, 80, 24)
+    }
+
+    // Feeds each chunk as a separate read; returns the terminal data and replies
+    fn receive(telnet: &mut Telnet, chunks: &[&[u8]]) -> (Vec<u8>, Vec<u8>) {
+        let (mut data, mut reply) = (Vec::new(), Vec::new());
+        for chunk in chunks {
+            telnet.receive(chunk, &mut data, &mut reply);
+        }
+        (data, reply)
+    }
+
+    #[test]
+    fn replies_only_to_option_changes() {
+        let mut telnet = telnet();
+        let (_, reply) = receive(&mut telnet, &[&[IAC, WILL, OPT_ECHO, IAC, WILL, OPT_ECHO]]);
+        assert_eq!(reply, [IAC, DO, OPT_ECHO]);
+        let (_, reply) = receive(&mut telnet, &[&[IAC, WONT, OPT_ECHO, IAC, WONT, OPT_ECHO]]);
+        assert_eq!(reply, [IAC, DONT, OPT_ECHO]);
+    }
+
+    #[test]
+    fn refuses_unsupported_options() {
+        let mut telnet = telnet();
+        // LINEMODE (34) both ways
+        let (data, reply) = receive(&mut telnet, &[&[IAC, DO, 34, IAC, WILL, 34]]);
+        assert!(data.is_empty());
+        assert_eq!(reply, [IAC, WONT, 34, IAC, DONT, 34]);
+    }
+
+    #[test]
+    fn do_naws_sends_window_size() {
+        let mut telnet = telnet();
+        telnet.initial_requests();
+        let (_, reply) = receive(&mut telnet, &[&[IAC, DO, OPT_NAWS]]);
+        assert_eq!(reply, [IAC, SB, OPT_NAWS, 0, 80, 0, 24, IAC, SE]);
+    }
+
+    #[test]
+    fn naws_escapes_iac_in_sizes() {
+        let mut telnet = telnet();
+        assert_eq!(telnet.resize(255, 24), None);
+        telnet.initial_requests();
+        assert_eq!(telnet.resize(255, 24), Some(vec![IAC, SB, OPT_NAWS, 0, IAC, IAC, 0, 24, IAC, SE]));
+    }
+
+    #[test]
+    fn answers_terminal_type_request() {
+        let mut telnet = telnet();
+        telnet.initial_requests();
+        let (data, reply) = receive(&mut telnet, &[&[b'>', IAC, SB, OPT_TTYPE, TTYPE_SEND, IAC, SE, b'<']]);
+        assert_eq!(data, b"><");
+        assert_eq!(reply, [&[IAC, SB, OPT_TTYPE, TTYPE_IS][..], b"VT100", &[IAC, SE]].concat());
+    }
+
+    #[test]
+    fn sends_username_through_new_environ() {
+        let mut telnet = Telnet::new("VT100".to_string(), Some("ad\x01min".to_string()), 80, 24);
+        let (_, reply) = receive(&mut telnet, &[&[IAC, DO, OPT_NEW_ENVIRON]]);
+        assert_eq!(reply, [IAC, WILL, OPT_NEW_ENVIRON]);
+        let expected = [&[IAC, SB, OPT_NEW_ENVIRON, ENV_IS, ENV_VAR][..], b"USER", &[ENV_VALUE], b"ad", &[ENV_ESC, 1], b"min", &[IAC, SE]].concat();
+        let (_, reply) = receive(&mut telnet, &[&[IAC, SB, OPT_NEW_ENVIRON, ENV_SEND, IAC, SE]]);
+        assert_eq!(reply, expected);
+        let (_, reply) = receive(&mut telnet, &[&[&[IAC, SB, OPT_NEW_ENVIRON, ENV_SEND, ENV_VAR][..], b"USER", &[IAC, SE]].concat()]);
+        assert_eq!(reply, expected);
+        // Only the variables asked for are sent
+        let (_, reply) = receive(&mut telnet, &[&[&[IAC, SB, OPT_NEW_ENVIRON, ENV_SEND, ENV_VAR][..], b"DISPLAY", &[IAC, SE]].concat()]);
+        assert!(reply.is_empty());
+    }
+
+    #[test]
+    fn refuses_new_environ_without_username() {
+        let mut telnet = telnet();
+        let (_, reply) = receive(&mut telnet, &[&[IAC, DO, OPT_NEW_ENVIRON, IAC, SB, OPT_NEW_ENVIRON, ENV_SEND, IAC, SE]]);
+        assert_eq!(reply, [IAC, WONT, OPT_NEW_ENVIRON]);
+    }
+
+    #[test]
+    fn subnegotiation_is_buffered_across_reads() {
+        let mut telnet = telnet();
+        telnet.initial_requests();
+        let (data, reply) = receive(&mut telnet, &[&[IAC, SB], &[OPT_TTYPE], &[TTYPE_SEND, IAC], &[SE, b'x']]);
+        assert_eq!(data, b"x");
+        assert!(reply.starts_with(&[IAC, SB, OPT_TTYPE, TTYPE_IS]));
+    }
+
+    #[test]
+    fn oversized_subnegotiation_is_ignored() {
+        let mut telnet = telnet();
+        telnet.initial_requests();
+        let mut input = vec![IAC, SB, OPT_TTYPE, TTYPE_SEND];
+        input.extend(vec![b'a'; MAX_SUBNEGOTIATION * 2]);
+        input.extend([IAC, SE]);
+        let (data, reply) = receive(&mut telnet, &[&input]);
+        assert!(data.is_empty());
+        assert!(reply.is_empty());
+        assert!(telnet.subnegotiation.len() <= MAX_SUBNEGOTIATION);
+    }
+
+    #[test]
+    fn iac_split_across_reads() {
+        let mut telnet = telnet();
+        let (data, reply) = receive(&mut telnet, &[b"a", &[IAC], &[WILL], &[OPT_SGA, b'b', IAC], &[IAC, b'c']]);
+        assert_eq!(data, [b'a', b'b', IAC, b'c']);
+        assert_eq!(reply, [IAC, DO, OPT_SGA]);
+    }
+
+    #[test]
+    fn drops_nul_after_cr() {
+        let mut telnet = telnet();
+        let (data, _) = receive(&mut telnet, &[b"one\r", &[0], b"two\r\n"]);
+        assert_eq!(data, b"one\rtwo\r\n");
+    }
+
+    #[test]
+    fn encoder_escapes_iac_and_bare_cr() {
+        let mut telnet = telnet();
+        assert_eq!(telnet.encode(&[b'a', IAC, b'b']), [b'a', IAC, IAC, b'b']);
+        assert_eq!(telnet.encode(b"ls\r"), b"ls\r\0");
+        assert_eq!(telnet.encode(b"ls\r\n"), b"ls\r\n");
+        receive(&mut telnet, &[&[IAC, DO, OPT_BINARY]]);
+        assert_eq!(telnet.encode(&[b'\r', IAC]), [b'\r', IAC, IAC]);
+    }
+}
@@ -0,0 +1,195 @@
+// File transfer bookkeeping shared by SFTP and SCP: cancellation flags and
+// the `file-transfer` progress event the frontend uses for its progress bars.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tauri::{command, AppHandle, State};
+
+use crate::session::emit_event;
+use crate::AppState;
+
+// Minimum time between two progress events for the same transfer
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+const CHUNK_SIZE: usize = 32 * 1024;
+
+// Running transfers by id -> their cancel flag
+pub type TransferMap = Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>;
+
+// --- Event Payloads ---
+
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferDirection {
+    Upload,
+    Download,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransferStatus {
+    Progress,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferPayload {
+    pub transfer_id: String,
+    pub session_id: String,
+    pub direction: TransferDirection,
+    pub remote_path: String,
+    pub local_path: String,
+    pub bytes: u64,
+    pub total: Option<u64>, // Unknown when the source size could not be read
+    pub status: TransferStatus,
+    pub message: Option<String>, // Error text for failed transfers
+    pub md5: Option<String>, // Hex digest of the transferred bytes, when computed
+    pub verified: Option<bool>, // Result of the post-transfer check, when one was requested
+}
+
+// --- Transfer Tracking ---
+
+// One running transfer: reports progress and watches its cancel flag.
+// Unregisters itself when dropped.
+pub struct Transfer {
+    app_handle: AppHandle,
+    transfers: TransferMap,
+    cancel: Arc<AtomicBool>,
+    payload: TransferPayload,
+    last_emit: Instant,
+}
+
+impl Transfer {
+    pub fn start(
+        app_handle: AppHandle,
+        transfers: &TransferMap,
+        session_id: &str,
+        direction: TransferDirection,
+        remote_path: &str,
+        local_path: &str,
+    ) -> Result<Transfer, String> {
+        let transfer_id = uuid::Uuid::new_v4().to_string();
+        let cancel = Arc::new(AtomicBool::new(false));
+        transfers
+            .lock()
+            .map_err(|_| "Failed to lock transfer registry".to_string())?
+            .insert(transfer_id.clone(), cancel.clone());
+        Ok(Transfer {
+            app_handle,
+            transfers: transfers.clone(),
+            cancel,
+            payload: TransferPayload {
+                transfer_id,
+                session_id: session_id.to_string(),
+                direction,
+                remote_path: remote_path.to_string(),
+                local_path: local_path.to_string(),
+                bytes: 0,
+                total: None,
+                status: TransferStatus::Progress,
+                message: None,
+                md5: None,
+                verified: None,
+            },
+            last_emit: Instant::now(),
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.payload.transfer_id
+    }
+
+    pub fn set_total(&mut self, total: Option<u64>) {
+        self.payload.total = total;
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.payload.bytes
+    }
+
+    pub fn set_verification(&mut self, md5: Option<String>, verified: Option<bool>) {
+        self.payload.md5 = md5;
+        self.payload.verified = verified;
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
+    }
+
+    // Records progress, emitting at most every PROGRESS_INTERVAL
+    pub fn advance(&mut self, bytes: u64) {
+        self.payload.bytes += bytes;
+        if self.last_emit.elapsed() >= PROGRESS_INTERVAL {
+            self.last_emit = Instant::now();
+            emit_event(&self.app_handle, "file-transfer", self.payload.clone());
+        }
+    }
+
+    // Sends the final event for the transfer
+    pub fn finish(mut self, result: &Result<(), String>) {
+        let (status, message) = match result {
+            Ok(()) => (TransferStatus::Completed, None),
+            Err(_) if self.is_cancelled() => (TransferStatus::Cancelled, None),
+            Err(e) => (TransferStatus::Failed, Some(e.clone())),
+        };
+        println!(
+            "[{}] Transfer {} {:?}: {} bytes ({:?})",
+            self.payload.session_id, self.payload.transfer_id, status, self.payload.bytes, message
+        );
+        self.payload.status = status;
+        self.payload.message = message;
+        emit_event(&self.app_handle, "file-transfer", self.payload.clone());
+    }
+
+    // Copies `reader` into `writer` in chunks, reporting progress and stopping on cancel.
+    // `read`/`write` wrap the raw calls so callers can add non-blocking retries.
+    pub fn copy<R: Read, W: Write>(
+        &mut self,
+        reader: &mut R,
+        writer: &mut W,
+        mut read: impl FnMut(&mut R, &mut [u8]) -> io::Result<usize>,
+        mut write: impl FnMut(&mut W, &[u8]) -> io::Result<()>,
+    ) -> Result<(), String> {
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        loop {
+            if self.is_cancelled() {
+                return Err("Transfer cancelled".to_string());
+            }
+            let n = read(reader, &mut buffer).map_err(|e| format!("Read failed: {}", e))?;
+            if n == 0 {
+                return Ok(());
+            }
+            write(writer, &buffer[..n]).map_err(|e| format!("Write failed: {}", e))?;
+            self.advance(n as u64);
+        }
+    }
+}
+
+impl Drop for Transfer {
+    fn drop(&mut self) {
+        if let Ok(mut guard) = self.transfers.lock() {
+            guard.remove(&self.payload.transfer_id);
+        }
+    }
+}
+
+// --- Tauri Commands ---
+
+#[command]
+pub fn cancel_transfer(state: State<'_, AppState>, transfer_id: String) -> Result<(), String> {
+    let guard = state.transfers.lock().map_err(|_| "Failed to lock transfer registry".to_string())?;
+    match guard.get(&transfer_id) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::SeqCst);
+            println!("Cancellation requested for transfer {}", transfer_id);
+            Ok(())
+        }
+        None => Err(format!("Unknown or finished transfer {}", transfer_id)),
+    }
+}
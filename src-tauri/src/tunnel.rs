@@ -0,0 +1,82 @@
+// Relays bytes between an SSH channel and a local TCP socket.
+// Used to chain jump hosts: the next hop's SSH session runs over a loopback
+// socket whose other end is a direct-tcpip channel on the previous hop.
+
+use std::io::{self, Read};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::thread;
+
+use ssh2::{Channel, Session};
+
+use crate::ssh_client::{retry, write_all_nonblocking, IDLE_POLL};
+
+// Copies data both ways until either side closes. The session must be non-blocking.
+pub fn bridge(channel: &mut Channel, socket: &mut TcpStream) -> io::Result<()> {
+    socket.set_nonblocking(true)?;
+    let mut buffer = [0; 16384];
+    loop {
+        let mut idle = true;
+
+        match channel.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => {
+                idle = false;
+                write_all_nonblocking(socket, &buffer[..n])?;
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+
+        match socket.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => {
+                idle = false;
+                write_all_nonblocking(channel, &buffer[..n])?;
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+
+        if idle {
+            thread::sleep(IDLE_POLL);
+        }
+    }
+    let _ = retry(|| channel.send_eof());
+    let _ = retry(|| channel.close());
+    Ok(())
+}
+
+// A connected loopback socket pair; only accepts the connection we made ourselves
+fn loopback_pair() -> io::Result<(TcpStream, TcpStream)> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let local = TcpStream::connect(listener.local_addr()?)?;
+    loop {
+        let (remote, peer) = listener.accept()?;
+        if peer == local.local_addr()? {
+            return Ok((local, remote));
+        }
+        eprintln!("Dropping unexpected loopback connection from {}", peer);
+    }
+}
+
+// Asks the hop to open a TCP connection to `host:port` and returns a local socket
+// carrying it. The hop session is moved into a relay thread that keeps it alive
+// until the socket is closed (i.e. the next hop disconnects).
+pub fn open_jump_tunnel(hop: Session, label: String, host: &str, port: u16) -> Result<TcpStream, String> {
+    let mut channel = hop
+        .channel_direct_tcpip(host, port, None)
+        .map_err(|e| format!("cannot reach {}:{} from this hop: {}", host, port, e))?;
+    let (local, mut remote) = loopback_pair().map_err(|e| format!("failed to create local tunnel socket: {}", e))?;
+    let _ = local.set_nodelay(true);
+
+    hop.set_blocking(false);
+    thread::spawn(move || {
+        println!("[{}] Tunnel relay started.", label);
+        if let Err(e) = bridge(&mut channel, &mut remote) {
+            eprintln!("[{}] Tunnel relay failed: {}", label, e);
+        }
+        let _ = retry(|| hop.disconnect(None, "Tunnel closed", None));
+        println!("[{}] Tunnel relay finished.", label);
+    });
+    Ok(local)
+}
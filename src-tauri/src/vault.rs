@@ -0,0 +1,626 @@
+// Encrypted store for passwords, key passphrases and API keys. Everything is
+// sealed under a master passphrase: an Argon2id-derived key encrypts the whole
+// secret list with XChaCha20-Poly1305, with the cleartext header (version, KDF
+// settings, auto-lock timeout) as associated data, so a wrong passphrase or a
+// tampered file fails to open. The key is only held in memory while unlocked and is dropped
+// after `autoLockSecs` without use. Profiles refer to secrets by id.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use argon2::{Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use tauri::{command, AppHandle, Manager, State};
+use tokio::task;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::session::emit_event;
+use crate::AppState;
+
+const VAULT_FILE: &str = "vault.json";
+const VAULT_VERSION: u32 = 2;
+// Version 1 files did not authenticate their header; they are still opened
+// and rewritten as version 2 on unlock
+const LEGACY_VAULT_VERSION: u32 = 1;
+// Argon2id cost for new vaults (64 MiB, 3 passes); stored in the file so it can change later
+const KDF_MEMORY_KIB: u32 = 64 * 1024;
+const KDF_ITERATIONS: u32 = 3;
+const KDF_PARALLELISM: u32 = 1;
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const DEFAULT_AUTO_LOCK_SECS: u64 = 15 * 60;
+// How often the auto-lock thread checks for an idle vault
+const AUTO_LOCK_POLL: Duration = Duration::from_secs(5);
+
+// Well-known id of the Gemini API key; `send_to_gemini` prefers it over GEMINI_API_KEY
+pub const GEMINI_API_KEY_SECRET: &str = "gemini-api-key";
+
+pub type VaultHandle = Arc<Mutex<Vault>>;
+
+fn default_auto_lock_secs() -> u64 {
+    DEFAULT_AUTO_LOCK_SECS
+}
+
+// --- Errors ---
+
+#[derive(Debug, Clone)]
+pub enum VaultError {
+    Locked,
+    BadPassphrase,
+    NotFound(String),
+    Store(String),
+}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VaultError::Locked => write!(f, "The vault is locked; unlock it with the master passphrase first"),
+            VaultError::BadPassphrase => write!(f, "Incorrect master passphrase (or the vault file is damaged)"),
+            VaultError::NotFound(id) => write!(f, "No secret {} in the vault", id),
+            VaultError::Store(msg) => write!(f, "Vault error: {}", msg),
+        }
+    }
+}
+
+impl From<VaultError> for String {
+    fn from(e: VaultError) -> Self {
+        e.to_string()
+    }
+}
+
+// --- Types ---
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SecretKind {
+    Password,
+    Passphrase, // For a private key
+    ApiKey,
+    Other,
+}
+
+// A secret as listed to the frontend; the value never leaves the backend this way
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretEntry {
+    pub id: String,
+    pub label: String,
+    pub kind: SecretKind,
+    pub updated_at: u64,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultStatus {
+    pub exists: bool, // False until the first unlock sets the master passphrase
+    pub unlocked: bool,
+    pub auto_lock_secs: u64,
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultLockedPayload {
+    pub reason: String, // "user" or "timeout"
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KdfParams {
+    algorithm: String, // Always "argon2id" for now
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    salt: String, // Base64
+}
+
+// On-disk layout of vault.json; only the KDF settings and auto-lock timeout are in clear
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VaultFile {
+    version: u32,
+    kdf: KdfParams,
+    #[serde(default = "default_auto_lock_secs")]
+    auto_lock_secs: u64,
+    nonce: String,      // Base64, fresh for every write
+    ciphertext: String, // Base64 of the encrypted `VaultContents` JSON
+}
+
+// The cleartext part of the file, authenticated as associated data
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VaultHeader<'a> {
+    version: u32,
+    kdf: &'a KdfParams,
+    auto_lock_secs: u64,
+}
+
+impl VaultHeader<'_> {
+    fn associated_data(&self) -> Result<Vec<u8>, VaultError> {
+        serde_json::to_vec(self).map_err(|e| VaultError::Store(format!("Failed to encode vault header: {}", e)))
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredSecret {
+    label: String,
+    kind: SecretKind,
+    value: String,
+    updated_at: u64,
+}
+
+// Wiped wherever it is dropped: on lock, or when replaced or deleted
+impl Drop for StoredSecret {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct VaultContents {
+    secrets: BTreeMap<String, StoredSecret>,
+}
+
+struct Unlocked {
+    key: Zeroizing<[u8; KEY_LEN]>,
+    kdf: KdfParams,
+    contents: VaultContents,
+    last_used: Instant,
+}
+
+pub struct Vault {
+    unlocked: Option<Unlocked>,
+    auto_lock: Duration, // Zero disables auto-lock
+}
+
+impl Default for Vault {
+    fn default() -> Self {
+        Vault { unlocked: None, auto_lock: Duration::from_secs(DEFAULT_AUTO_LOCK_SECS) }
+    }
+}
+
+// --- Crypto & Storage ---
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn store_path(app_handle: &AppHandle) -> Result<PathBuf, VaultError> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| VaultError::Store(format!("Failed to locate app data directory: {}", e)))?;
+    fs::create_dir_all(&dir).map_err(|e| VaultError::Store(format!("Failed to create {}: {}", dir.display(), e)))?;
+    Ok(dir.join(VAULT_FILE))
+}
+
+fn new_kdf_params() -> KdfParams {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    KdfParams {
+        algorithm: "argon2id".to_string(),
+        memory_kib: KDF_MEMORY_KIB,
+        iterations: KDF_ITERATIONS,
+        parallelism: KDF_PARALLELISM,
+        salt: STANDARD.encode(salt),
+    }
+}
+
+// Slow on purpose (tens to hundreds of milliseconds); run off the async runtime
+fn derive_key(passphrase: &str, kdf: &KdfParams) -> Result<Zeroizing<[u8; KEY_LEN]>, VaultError> {
+    if kdf.algorithm != "argon2id" {
+        return Err(VaultError::Store(format!("Unsupported key derivation {}", kdf.algorithm)));
+    }
+    let salt = STANDARD.decode(&kdf.salt).map_err(|e| VaultError::Store(format!("Bad salt: {}", e)))?;
+    let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(KEY_LEN))
+        .map_err(|e| VaultError::Store(format!("Bad key derivation parameters: {}", e)))?;
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    Argon2::new(argon2::Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), &salt, key.as_mut())
+        .map_err(|e| VaultError::Store(format!("Key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+fn read_file(path: &Path) -> Result<Option<VaultFile>, VaultError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = fs::read_to_string(path).map_err(|e| VaultError::Store(format!("Failed to read {}: {}", path.display(), e)))?;
+    let file: VaultFile = serde_json::from_str(&contents)
+        .map_err(|e| VaultError::Store(format!("Failed to parse {}: {}", path.display(), e)))?;
+    if file.version != VAULT_VERSION && file.version != LEGACY_VAULT_VERSION {
+        return Err(VaultError::Store(format!("{} has unsupported format version {}", path.display(), file.version)));
+    }
+    Ok(Some(file))
+}
+
+fn open(file: &VaultFile, key: &[u8; KEY_LEN]) -> Result<VaultContents, VaultError> {
+    let nonce = STANDARD.decode(&file.nonce).map_err(|e| VaultError::Store(format!("Bad nonce: {}", e)))?;
+    if nonce.len() != 24 {
+        return Err(VaultError::Store("Bad nonce length".to_string()));
+    }
+    let ciphertext = STANDARD.decode(&file.ciphertext).map_err(|e| VaultError::Store(format!("Bad ciphertext: {}", e)))?;
+    // An edited header fails authentication just like edited ciphertext
+    let aad = match file.version {
+        LEGACY_VAULT_VERSION => Vec::new(),
+        version => VaultHeader { version, kdf: &file.kdf, auto_lock_secs: file.auto_lock_secs }.associated_data()?,
+    };
+    let plaintext = Zeroizing::new(
+        XChaCha20Poly1305::new(key.into())
+            .decrypt(XNonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: &aad })
+            .map_err(|_| VaultError::BadPassphrase)?,
+    );
+    serde_json::from_slice(&plaintext).map_err(|e| VaultError::Store(format!("Failed to decode vault contents: {}", e)))
+}
+
+// Re-encrypts everything with a fresh nonce and replaces the file atomically
+fn seal(path: &Path, unlocked: &Unlocked, auto_lock: Duration) -> Result<(), VaultError> {
+    let plaintext = Zeroizing::new(
+        serde_json::to_vec(&unlocked.contents).map_err(|e| VaultError::Store(format!("Failed to encode vault: {}", e)))?,
+    );
+    let auto_lock_secs = auto_lock.as_secs();
+    let aad = VaultHeader { version: VAULT_VERSION, kdf: &unlocked.kdf, auto_lock_secs }.associated_data()?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(unlocked.key.as_ref().into())
+        .encrypt(&nonce, Payload { msg: &plaintext, aad: &aad })
+        .map_err(|_| VaultError::Store("Encryption failed".to_string()))?;
+    let file = VaultFile {
+        version: VAULT_VERSION,
+        kdf: unlocked.kdf.clone(),
+        auto_lock_secs,
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+    };
+    let contents = serde_json::to_string_pretty(&file).map_err(|e| VaultError::Store(format!("Failed to encode vault: {}", e)))?;
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, contents).map_err(|e| VaultError::Store(format!("Failed to write {}: {}", temp.display(), e)))?;
+    fs::rename(&temp, path).map_err(|e| VaultError::Store(format!("Failed to replace {}: {}", path.display(), e)))
+}
+
+// --- Vault ---
+
+impl Vault {
+    fn unlocked(&mut self) -> Result<&mut Unlocked, VaultError> {
+        let unlocked = self.unlocked.as_mut().ok_or(VaultError::Locked)?;
+        unlocked.last_used = Instant::now();
+        Ok(unlocked)
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.unlocked.is_some()
+    }
+
+    pub fn lock(&mut self) {
+        // Dropping the key and contents zeroizes them
+        self.unlocked = None;
+    }
+
+    // Value of a stored secret; fails while locked
+    pub fn secret(&mut self, id: &str) -> Result<Zeroizing<String>, VaultError> {
+        let unlocked = self.unlocked()?;
+        let secret = unlocked.contents.secrets.get(id).ok_or_else(|| VaultError::NotFound(id.to_string()))?;
+        Ok(Zeroizing::new(secret.value.clone()))
+    }
+
+    fn list(&mut self) -> Result<Vec<SecretEntry>, VaultError> {
+        let unlocked = self.unlocked()?;
+        Ok(unlocked
+            .contents
+            .secrets
+            .iter()
+            .map(|(id, secret)| SecretEntry {
+                id: id.clone(),
+                label: secret.label.clone(),
+                kind: secret.kind,
+                updated_at: secret.updated_at,
+            })
+            .collect())
+    }
+
+    // Locks once the vault has sat unused for the auto-lock timeout as of `now`
+    fn lock_if_idle(&mut self, now: Instant) -> bool {
+        let idle = match &self.unlocked {
+            Some(unlocked) => !self.auto_lock.is_zero() && now.saturating_duration_since(unlocked.last_used) >= self.auto_lock,
+            None => false,
+        };
+        if idle {
+            self.lock();
+        }
+        idle
+    }
+}
+
+fn lock_vault(vault: &VaultHandle) -> Result<std::sync::MutexGuard<'_, Vault>, VaultError> {
+    vault.lock().map_err(|_| VaultError::Store("Failed to lock vault mutex".to_string()))
+}
+
+// Locks idle vaults in the background and tells the frontend (`vault-locked`)
+pub fn spawn_auto_lock(app_handle: AppHandle, vault: VaultHandle) {
+    thread::spawn(move || loop {
+        thread::sleep(AUTO_LOCK_POLL);
+        let locked = match vault.lock() {
+            Ok(mut vault) => vault.lock_if_idle(Instant::now()),
+            Err(_) => return,
+        };
+        if locked {
+            println!("Vault locked after inactivity.");
+            emit_event(&app_handle, "vault-locked", VaultLockedPayload { reason: "timeout".to_string() });
+        }
+    });
+}
+
+// --- Tauri Commands ---
+
+#[command]
+pub async fn vault_status(app_handle: AppHandle, state: State<'_, AppState>) -> Result<VaultStatus, String> {
+    let path = store_path(&app_handle)?;
+    let file = read_file(&path)?;
+    let mut vault = lock_vault(&state.vault)?;
+    // Before the first unlock the timeout in the file is shown; it is only
+    // trusted once unlocking has authenticated the header
+    if let (false, Some(file)) = (vault.is_unlocked(), &file) {
+        vault.auto_lock = Duration::from_secs(file.auto_lock_secs);
+    }
+    Ok(VaultStatus { exists: file.is_some(), unlocked: vault.is_unlocked(), auto_lock_secs: vault.auto_lock.as_secs() })
+}
+
+// Opens the vault with the master passphrase. The first unlock creates the
+// vault, so the passphrase given then becomes the master passphrase.
+#[command]
+pub async fn vault_unlock(app_handle: AppHandle, state: State<'_, AppState>, passphrase: String) -> Result<(), String> {
+    let passphrase = Zeroizing::new(passphrase);
+    if passphrase.is_empty() {
+        return Err("Master passphrase must not be empty".to_string());
+    }
+    let path = store_path(&app_handle)?;
+    let file = read_file(&path)?;
+
+    let (unlocked, auto_lock) = task::spawn_blocking(move || -> Result<_, VaultError> {
+        match file {
+            Some(file) => {
+                let key = derive_key(&passphrase, &file.kdf)?;
+                let contents = open(&file, &key)?;
+                let auto_lock = Duration::from_secs(file.auto_lock_secs);
+                let unlocked = Unlocked { key, kdf: file.kdf, contents, last_used: Instant::now() };
+                if file.version == LEGACY_VAULT_VERSION {
+                    seal(&path, &unlocked, auto_lock)?;
+                    println!("Upgraded vault at {} to format version {}", path.display(), VAULT_VERSION);
+                }
+                Ok((unlocked, auto_lock))
+            }
+            None => {
+                let kdf = new_kdf_params();
+                let key = derive_key(&passphrase, &kdf)?;
+                let unlocked = Unlocked { key, kdf, contents: VaultContents::default(), last_used: Instant::now() };
+                let auto_lock = Duration::from_secs(DEFAULT_AUTO_LOCK_SECS);
+                seal(&path, &unlocked, auto_lock)?;
+                println!("Created vault at {}", path.display());
+                Ok((unlocked, auto_lock))
+            }
+        }
+    })
+    .await
+    .map_err(|e| format!("Vault task failed: {}", e))??;
+
+    let mut vault = lock_vault(&state.vault)?;
+    vault.unlocked = Some(unlocked);
+    vault.auto_lock = auto_lock;
+    println!("Vault unlocked.");
+    Ok(())
+}
+
+#[command]
+pub async fn vault_lock(app_handle: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    lock_vault(&state.vault)?.lock();
+    println!("Vault locked.");
+    emit_event(&app_handle, "vault-locked", VaultLockedPayload { reason: "user".to_string() });
+    Ok(())
+}
+
+// 0 keeps the vault unlocked until it is locked by hand or the app exits
+#[command]
+pub async fn vault_set_auto_lock(app_handle: AppHandle, state: State<'_, AppState>, seconds: u64) -> Result<(), String> {
+    let path = store_path(&app_handle)?;
+    let mut vault = lock_vault(&state.vault)?;
+    let auto_lock = Duration::from_secs(seconds);
+    seal(&path, vault.unlocked()?, auto_lock)?;
+    vault.auto_lock = auto_lock;
+    Ok(())
+}
+
+#[command]
+pub async fn vault_list_secrets(state: State<'_, AppState>) -> Result<Vec<SecretEntry>, String> {
+    Ok(lock_vault(&state.vault)?.list()?)
+}
+
+// Stores a new secret (no id) or replaces one; returns its id
+#[command]
+pub async fn vault_save_secret(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    id: Option<String>,
+    label: String,
+    kind: SecretKind,
+    value: String,
+) -> Result<String, String> {
+    let value = Zeroizing::new(value);
+    let id = id.filter(|id| !id.trim().is_empty()).unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    let path = store_path(&app_handle)?;
+    let mut vault = lock_vault(&state.vault)?;
+    let auto_lock = vault.auto_lock;
+    let unlocked = vault.unlocked()?;
+    let previous = unlocked.contents.secrets.insert(
+        id.clone(),
+        StoredSecret { label, kind, value: value.to_string(), updated_at: now() },
+    );
+    if let Err(e) = seal(&path, unlocked, auto_lock) {
+        // Keep memory and disk in step
+        match previous {
+            Some(previous) => unlocked.contents.secrets.insert(id.clone(), previous),
+            None => unlocked.contents.secrets.remove(&id),
+        };
+        return Err(e.into());
+    }
+    Ok(id)
+}
+
+#[command]
+pub async fn vault_delete_secret(app_handle: AppHandle, state: State<'_, AppState>, id: String) -> Result<(), String> {
+    let path = store_path(&app_handle)?;
+    let mut vault = lock_vault(&state.vault)?;
+    let auto_lock = vault.auto_lock;
+    let unlocked = vault.unlocked()?;
+    let removed = unlocked.contents.secrets.remove(&id).ok_or_else(|| VaultError::NotFound(id.clone()))?;
+    if let Err(e) = seal(&path, unlocked, auto_lock) {
+        unlocked.contents.secrets.insert(id, removed);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Argon2id at its minimum cost so the tests stay fast
+    fn test_kdf() -> KdfParams {
+        KdfParams {
+            algorithm: "argon2id".to_string(),
+            memory_kib: 8,
+            iterations: 1,
+            parallelism: 1,
+            salt: STANDARD.encode([0x5a; SALT_LEN]),
+        }
+    }
+
+    fn test_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("termai-vault-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(VAULT_FILE)
+    }
+
+    fn unlocked_with_secret(passphrase: &str) -> Unlocked {
+        let kdf = test_kdf();
+        let key = derive_key(passphrase, &kdf).unwrap();
+        let mut contents = VaultContents::default();
+        contents.secrets.insert(
+            "router".to_string(),
+            StoredSecret { label: "Core router".to_string(), kind: SecretKind::Password, value: "s3cret".to_string(), updated_at: 1 },
+        );
+        Unlocked { key, kdf, contents, last_used: Instant::now() }
+    }
+
+    // Seals a vault with one secret and returns the file as written
+    fn sealed(name: &str, passphrase: &str) -> VaultFile {
+        let path = test_path(name);
+        seal(&path, &unlocked_with_secret(passphrase), Duration::from_secs(600)).unwrap();
+        let file = read_file(&path).unwrap().unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        file
+    }
+
+    #[test]
+    fn seal_then_open_round_trips() {
+        let file = sealed("round-trip", "correct horse");
+        assert_eq!(file.version, VAULT_VERSION);
+        assert_eq!(file.auto_lock_secs, 600);
+        let key = derive_key("correct horse", &file.kdf).unwrap();
+        let contents = open(&file, &key).unwrap();
+        let secret = &contents.secrets["router"];
+        assert_eq!(secret.label, "Core router");
+        assert_eq!(secret.kind, SecretKind::Password);
+        assert_eq!(secret.value, "s3cret");
+    }
+
+    #[test]
+    fn wrong_passphrase_is_rejected() {
+        let file = sealed("wrong-passphrase", "correct horse");
+        let key = derive_key("battery staple", &file.kdf).unwrap();
+        assert!(matches!(open(&file, &key), Err(VaultError::BadPassphrase)));
+    }
+
+    #[test]
+    fn flipped_ciphertext_byte_is_rejected() {
+        let mut file = sealed("flipped-byte", "correct horse");
+        let mut ciphertext = STANDARD.decode(&file.ciphertext).unwrap();
+        ciphertext[0] ^= 0x01;
+        file.ciphertext = STANDARD.encode(ciphertext);
+        let key = derive_key("correct horse", &file.kdf).unwrap();
+        assert!(matches!(open(&file, &key), Err(VaultError::BadPassphrase)));
+    }
+
+    #[test]
+    fn edited_header_is_rejected() {
+        let file = sealed("edited-header", "correct horse");
+        let key = derive_key("correct horse", &file.kdf).unwrap();
+
+        // Turning auto-lock off without the passphrase
+        let mut edited = file.clone();
+        edited.auto_lock_secs = 0;
+        assert!(matches!(open(&edited, &key), Err(VaultError::BadPassphrase)));
+
+        // Downgrading to the unauthenticated legacy format
+        let mut edited = file.clone();
+        edited.version = LEGACY_VAULT_VERSION;
+        assert!(matches!(open(&edited, &key), Err(VaultError::BadPassphrase)));
+
+        // Weakening the KDF cost in the header (the key itself is unchanged)
+        let mut edited = file;
+        edited.kdf.iterations = 2;
+        assert!(matches!(open(&edited, &key), Err(VaultError::BadPassphrase)));
+    }
+
+    #[test]
+    fn legacy_file_without_header_authentication_opens() {
+        let unlocked = unlocked_with_secret("correct horse");
+        let plaintext = serde_json::to_vec(&unlocked.contents).unwrap();
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = XChaCha20Poly1305::new(unlocked.key.as_ref().into()).encrypt(&nonce, plaintext.as_slice()).unwrap();
+        let file = VaultFile {
+            version: LEGACY_VAULT_VERSION,
+            kdf: unlocked.kdf.clone(),
+            auto_lock_secs: 600,
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        };
+        let contents = open(&file, &unlocked.key).unwrap();
+        assert_eq!(contents.secrets["router"].value, "s3cret");
+    }
+
+    #[test]
+    fn auto_lock_expires_after_idle_timeout() {
+        let start = Instant::now();
+        let mut vault = Vault { unlocked: Some(unlocked_with_secret("correct horse")), auto_lock: Duration::from_secs(60) };
+        vault.unlocked.as_mut().unwrap().last_used = start;
+
+        assert!(!vault.lock_if_idle(start + Duration::from_secs(59)));
+        assert!(vault.is_unlocked());
+        assert_eq!(vault.secret("router").unwrap().as_str(), "s3cret");
+
+        // Using a secret resets the idle clock
+        let used = vault.unlocked.as_ref().unwrap().last_used;
+        assert!(!vault.lock_if_idle(used + Duration::from_secs(59)));
+        assert!(vault.lock_if_idle(used + Duration::from_secs(60)));
+        assert!(!vault.is_unlocked());
+        assert!(matches!(vault.secret("router"), Err(VaultError::Locked)));
+        assert!(!vault.lock_if_idle(used + Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn zero_timeout_disables_auto_lock() {
+        let start = Instant::now();
+        let mut vault = Vault { unlocked: Some(unlocked_with_secret("correct horse")), auto_lock: Duration::ZERO };
+        vault.unlocked.as_mut().unwrap().last_used = start;
+        assert!(!vault.lock_if_idle(start + Duration::from_secs(365 * 24 * 3600)));
+        assert!(vault.is_unlocked());
+    }
+}
@@ -9,8 +9,239 @@ export type ConnectionDetails = {
   authMethod: 'password' | 'key';
   password?: string;
   privateKeyPath?: string;
+  certificatePath?: string; // OpenSSH user certificate for the key
+  jumpHosts?: JumpHost[]; // Bastions to tunnel through, in order
 };
 
+// One hop of a jump chain; hops without credentials use the SSH agent,
+// default keys or keyboard-interactive prompts
+export type JumpHost = {
+  hostname: string;
+  port?: number;
+  username: string;
+  password?: string;
+  privateKeyPath?: string;
+};
+
+// Parses "user@bastion1:2222, user@bastion2" into jump hosts
+export function parseJumpHosts(spec: string, defaultUser: string): JumpHost[] {
+  return spec
+    .split(',')
+    .map(part => part.trim())
+    .filter(part => part.length > 0)
+    .map(part => {
+      const at = part.lastIndexOf('@');
+      const username = at >= 0 ? part.slice(0, at) : defaultUser;
+      const hostPort = at >= 0 ? part.slice(at + 1) : part;
+      const match = hostPort.match(/^(.*):(\d+)$/);
+      return match
+        ? { hostname: match[1], port: Number(match[2]), username }
+        : { hostname: hostPort, username };
+    });
+}
+
+// Why ssh_connect failed, as sent by the backend
+export type ConnectError = {
+  kind: 'dns' | 'refused' | 'unreachable' | 'timeout' | 'authFailed' | 'hostKeyMismatch'
+    | 'hostKeyRejected' | 'protocol' | 'cancelled' | 'other';
+  message: string;
+};
+
+// Turns an error thrown by invoke (a ConnectError or a plain string) into display text
+export function describeError(error: unknown): string {
+  if (error && typeof error === 'object' && 'message' in error) {
+    return String((error as ConnectError).message);
+  }
+  return `${error}`;
+}
+
+// Preferred algorithm lists; empty lists keep the defaults
+export type AlgorithmOverrides = { kex?: string[]; hostKey?: string[]; cipher?: string[]; mac?: string[] };
+
+// A saved connection profile, as stored by the backend (secrets are never saved)
+export type Profile = {
+  id: string; // Empty for a new profile
+  name: string;
+  protocol: 'ssh' | 'telnet';
+  hostname: string;
+  port: number; // 0 for the protocol's default (22 or 23)
+  username: string;
+  authMethod: 'password' | 'key' | 'agent';
+  privateKeyPath?: string | null;
+  certificatePath?: string | null;
+  passwordSecretId?: string | null; // Vault secrets used when connecting
+  passphraseSecretId?: string | null;
+  jumpHosts: {
+    hostname: string;
+    port: number;
+    username: string;
+    privateKeyPath?: string | null;
+    certificatePath?: string | null;
+    passwordSecretId?: string | null;
+    passphraseSecretId?: string | null;
+    algorithms?: AlgorithmOverrides;
+  }[];
+  tags: string[];
+  metadata?: Record<string, string>; // Inventory variables of imported devices
+  postLoginCommands: string[];
+  algorithms?: AlgorithmOverrides;
+  reconnect?: { maxAttempts: number; initialDelayMs?: number; maxDelayMs?: number; replayCommands?: string[] } | null;
+  keepalive?: { intervalSecs?: number; maxMissed?: number } | null;
+  timeoutSecs?: number | null;
+  source?: string | null; // Set on imported profiles, e.g. "ssh_config:/home/me/.ssh/config"
+  createdAt?: number;
+  updatedAt?: number;
+};
+
+export function listProfiles(): Promise<Profile[]> {
+  return invoke<Profile[]>('list_profiles');
+}
+
+export function saveProfile(profile: Profile): Promise<Profile> {
+  return invoke<Profile>('save_profile', { profile });
+}
+
+export function deleteProfile(profileId: string): Promise<void> {
+  return invoke('delete_profile', { profileId });
+}
+
+// Resolves with the backend session id; throws a ConnectError
+export function connectProfile(profileId: string, password?: string, passphrase?: string): Promise<string> {
+  return invoke<string>('connect_profile', { profileId, password: password || null, passphrase: passphrase || null });
+}
+
+// Encrypted credential vault. The first unlock sets the master passphrase.
+export type VaultStatus = { exists: boolean; unlocked: boolean; autoLockSecs: number };
+export type SecretKind = 'password' | 'passphrase' | 'apiKey' | 'other';
+export type SecretEntry = { id: string; label: string; kind: SecretKind; updatedAt: number };
+
+// Secret id the Gemini integration reads its API key from
+export const GEMINI_API_KEY_SECRET = 'gemini-api-key';
+
+export function vaultStatus(): Promise<VaultStatus> {
+  return invoke<VaultStatus>('vault_status');
+}
+
+export function unlockVault(passphrase: string): Promise<void> {
+  return invoke('vault_unlock', { passphrase });
+}
+
+export function lockVault(): Promise<void> {
+  return invoke('vault_lock');
+}
+
+export function setVaultAutoLock(seconds: number): Promise<void> {
+  return invoke('vault_set_auto_lock', { seconds });
+}
+
+export function listSecrets(): Promise<SecretEntry[]> {
+  return invoke<SecretEntry[]>('vault_list_secrets');
+}
+
+// Returns the secret's id (a new one unless `id` is given)
+export function saveSecret(label: string, kind: SecretKind, value: string, id?: string): Promise<string> {
+  return invoke<string>('vault_save_secret', { id: id ?? null, label, kind, value });
+}
+
+export function deleteSecret(id: string): Promise<void> {
+  return invoke('vault_delete_secret', { id });
+}
+
+// One past (or still open) session, from `list_connection_history`
+export type HistoryEntry = {
+  id: string;
+  profileId: string | null;
+  profileName: string | null;
+  kind: 'ssh' | 'telnet' | 'serial' | 'local';
+  hostname: string;
+  port: number;
+  username: string;
+  startedAt: number; // Unix seconds, when the attempt began
+  connectedAt?: number | null; // Null if the attempt failed
+  endedAt: number | null;
+  endState: 'closed' | 'failed' | null;
+  exitStatus: number | null;
+  message: string | null;
+  bytesIn: number;
+  bytesOut: number;
+  transport?: HistoryTransport | null; // Missing on entries recorded by older versions
+};
+
+// How a history entry's session was opened (no secrets)
+export type HistoryTransport =
+  | {
+      type: 'ssh';
+      privateKeyPath: string | null;
+      certificatePath: string | null;
+      jumpHosts: Profile['jumpHosts'];
+      algorithms: AlgorithmOverrides;
+    }
+  | { type: 'telnet'; terminalType: string | null }
+  | {
+      type: 'serial';
+      baudRate: number;
+      dataBits: number;
+      parity: 'none' | 'odd' | 'even';
+      stopBits: number;
+      flowControl: 'none' | 'software' | 'hardware';
+    }
+  | { type: 'local'; shell: string | null; cwd: string | null };
+
+export type HistoryFilter = {
+  query?: string;
+  kind?: HistoryEntry['kind'];
+  profileId?: string;
+  since?: number;
+  until?: number;
+  failedOnly?: boolean;
+  limit?: number;
+};
+
+// Newest first
+export function listConnectionHistory(filter: HistoryFilter = {}): Promise<HistoryEntry[]> {
+  return invoke<HistoryEntry[]>('list_connection_history', { filter });
+}
+
+// Opens a new session like the given history entry; resolves with its session id
+export function reconnectFromHistory(entryId: string, password?: string): Promise<string> {
+  return invoke<string>('reconnect_from_history', { entryId, password: password || null });
+}
+
+// Outcome of a profile import; with dryRun nothing was saved yet
+export type ImportReport = {
+  source: string;
+  dryRun: boolean;
+  created: Profile[];
+  updated: Profile[];
+  unchanged: string[]; // Profile names
+  removed: Profile[]; // Only filled by inventory imports, which sync
+  skipped: { name: string; reason: string }[];
+  unsupported: { directive: string; location: string; count: number }[];
+};
+
+// Defaults to ~/.ssh/config
+export function importSshConfig(path?: string, dryRun = false): Promise<ImportReport> {
+  return invoke<ImportReport>('import_ssh_config', { path: path || null, dryRun });
+}
+
+export type SessionFormat = 'putty' | 'secureCrt' | 'mobaXterm';
+
+// `path` is a .reg export (PuTTY), a session folder, .ini or .xml export
+// (SecureCRT) or a .mxtsessions file (MobaXterm)
+export function importSessions(format: SessionFormat, path: string, dryRun = false): Promise<ImportReport> {
+  return invoke<ImportReport>('import_sessions', { format, path, dryRun });
+}
+
+// An Ansible inventory (INI, YAML or `ansible-inventory --list` JSON) or a CSV file; re-importing the same file syncs it
+export function importInventory(path: string, dryRun = false): Promise<ImportReport> {
+  return invoke<ImportReport>('import_inventory', { path, dryRun });
+}
+
+// One report per inventory that profiles were imported from
+export function resyncInventories(dryRun = false): Promise<ImportReport[]> {
+  return invoke<ImportReport[]>('resync_inventories', { dryRun });
+}
+
 export type ActiveConnection = {
   id: string;
   name: string;
@@ -49,7 +280,7 @@ export async function runCommand(command: string): Promise<string> {
   try {
     // Execute command via Rust backend
     const result = await invoke('ssh_run_command', {
-      connectionId: connection.connectionId,
+      sessionId: connection.connectionId,
       command
     });
     
@@ -74,8 +305,8 @@ export async function disconnect(id: string): Promise<boolean> {
   
   try {
     // Call Rust backend to close the connection
-    await invoke('ssh_disconnect', {
-      connectionId: connection.connectionId
+    await invoke('disconnect_ssh', {
+      sessionId: connection.connectionId
     });
     
     // Remove from store